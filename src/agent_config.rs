@@ -0,0 +1,76 @@
+use std::time::Duration;
+use anyhow::Result;
+
+/// Files reviewed at once when `--jobs` isn't given: one per CPU.
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism().map_or(4, |cpus| cpus.get())
+}
+
+/// Resource bounds for a `DevAgent`. The CLI uses the defaults apart from
+/// `max_concurrency`, which comes from `--jobs`; embedders running the
+/// agent inside a constrained service can tighten them here instead of
+/// patching limits spread across the agents.
+#[derive(Debug, Clone)]
+pub struct DevAgentConfig {
+    /// Files reviewed at the same time
+    pub max_concurrency: usize,
+    /// Files larger than this many bytes are skipped without being read
+    pub max_file_size: u64,
+    /// Budget for file contents kept by the memory store; the least
+    /// recently updated entries are dropped past it
+    pub max_cache_bytes: usize,
+    /// Per-request timeout for the LLM backend
+    pub llm_timeout: Duration,
+    /// Upper bound on reviewing a single file, LLM wait included
+    pub file_timeout: Duration,
+}
+
+impl Default for DevAgentConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 4,
+            max_file_size: 1024 * 1024,
+            max_cache_bytes: 64 * 1024 * 1024,
+            llm_timeout: Duration::from_secs(30),
+            file_timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+impl DevAgentConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_concurrency == 0 {
+            anyhow::bail!("max_concurrency must be at least 1");
+        }
+        if self.max_file_size == 0 {
+            anyhow::bail!("max_file_size must be at least 1 byte");
+        }
+        if self.llm_timeout.is_zero() || self.file_timeout.is_zero() {
+            anyhow::bail!("timeouts must be greater than zero");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn defaults_are_valid_and_zero_limits_are_rejected() {
+        let config = DevAgentConfig::default();
+        assert!(config.validate().is_ok());
+        assert_eq!((config.max_concurrency, config.max_file_size), (4, 1024 * 1024));
+        assert!(default_jobs() >= 1);
+        
+        let invalid = [
+            (DevAgentConfig { max_concurrency: 0, ..config.clone() }, "max_concurrency must be at least 1"),
+            (DevAgentConfig { max_file_size: 0, ..config.clone() }, "max_file_size must be at least 1 byte"),
+            (DevAgentConfig { llm_timeout: Duration::ZERO, ..config.clone() }, "timeouts must be greater than zero"),
+            (DevAgentConfig { file_timeout: Duration::ZERO, ..config }, "timeouts must be greater than zero"),
+        ];
+        for (config, message) in invalid {
+            assert_eq!(config.validate().unwrap_err().to_string(), message);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use tracing::{info, warn};
+
+// Entries larger than this are skipped rather than read into memory
+pub const MAX_ENTRY_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug)]
+pub struct ArchiveEntry {
+    /// Path of the entry inside the archive, using `/` separators
+    pub path: String,
+    pub content: String,
+}
+
+impl ArchiveEntry {
+    /// How the entry is named in reports and memory: `archive!path`, e.g.
+    /// `release.tar.gz!src/main.rs`.
+    pub fn display_path(&self, archive: &str) -> String {
+        format!("{}!{}", archive, self.path)
+    }
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_lowercase();
+    name.ends_with(".zip") || name.ends_with(".tar") || name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+/// Reads the code files out of a `.zip`, `.tar` or `.tar.gz` without
+/// extracting anything to disk.
+pub fn read_code_entries(archive: &Path, is_code_file: impl Fn(&Path) -> bool) -> Result<Vec<ArchiveEntry>> {
+    info!("Reading archive: {}", archive.display());
+    
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open archive {}", archive.display()))?;
+    let name = archive.to_string_lossy().to_lowercase();
+    
+    let entries = if name.ends_with(".zip") {
+        read_zip(file, &is_code_file)?
+    } else if name.ends_with(".tar") {
+        read_tar(file, &is_code_file)?
+    } else {
+        read_tar(GzDecoder::new(file), &is_code_file)?
+    };
+    
+    info!("Found {} code files in {}", entries.len(), archive.display());
+    Ok(entries)
+}
+
+fn read_zip(file: File, is_code_file: &impl Fn(&Path) -> bool) -> Result<Vec<ArchiveEntry>> {
+    let mut archive = zip::ZipArchive::new(file).context("Failed to read zip archive")?;
+    let mut entries = Vec::new();
+    
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if !entry.is_file() {
+            continue;
+        }
+        
+        let path = entry.name().to_string();
+        if !is_code_file(Path::new(&path)) {
+            continue;
+        }
+        
+        if let Some(content) = read_entry(&path, entry.size(), &mut entry) {
+            entries.push(ArchiveEntry { path, content });
+        }
+    }
+    
+    Ok(entries)
+}
+
+fn read_tar<R: Read>(reader: R, is_code_file: &impl Fn(&Path) -> bool) -> Result<Vec<ArchiveEntry>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+    
+    for entry in archive.entries().context("Failed to read tar archive")? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        
+        let path = entry.path()?.to_string_lossy().replace('\\', "/");
+        let path = path.trim_start_matches("./").to_string();
+        if !is_code_file(Path::new(&path)) {
+            continue;
+        }
+        
+        let size = entry.size();
+        if let Some(content) = read_entry(&path, size, &mut entry) {
+            entries.push(ArchiveEntry { path, content });
+        }
+    }
+    
+    Ok(entries)
+}
+
+fn read_entry(path: &str, size: u64, reader: &mut impl Read) -> Option<String> {
+    if size > MAX_ENTRY_BYTES {
+        warn!("Skipping oversized archive entry {} ({} bytes)", path, size);
+        return None;
+    }
+    
+    let mut bytes = Vec::with_capacity(size as usize);
+    if let Err(e) = reader.take(MAX_ENTRY_BYTES).read_to_end(&mut bytes) {
+        warn!("Failed to read archive entry {}: {}", path, e);
+        return None;
+    }
+    
+    match String::from_utf8(bytes) {
+        Ok(content) => Some(content),
+        Err(_) => {
+            warn!("Skipping non-UTF-8 archive entry {}", path);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    
+    fn is_rust(path: &Path) -> bool {
+        path.extension().is_some_and(|ext| ext == "rs")
+    }
+    
+    fn paths(entries: &[ArchiveEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.path.as_str()).collect()
+    }
+    
+    #[test]
+    fn zip_entries_are_listed_without_extracting() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("release.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.add_directory("src/", options).unwrap();
+        zip.start_file("src/main.rs", options).unwrap();
+        zip.write_all(b"fn main() {}\n").unwrap();
+        zip.start_file("README.txt", options).unwrap();
+        zip.write_all(b"Not code\n").unwrap();
+        zip.start_file("src/huge.rs", options).unwrap();
+        zip.write_all(&vec![b' '; MAX_ENTRY_BYTES as usize + 1]).unwrap();
+        zip.finish().unwrap();
+        
+        assert!(is_archive(&path));
+        let entries = read_code_entries(&path, is_rust).unwrap();
+        assert_eq!(paths(&entries), ["src/main.rs"]);
+        assert_eq!(entries[0].content, "fn main() {}\n");
+        assert_eq!(entries[0].display_path("release.zip"), "release.zip!src/main.rs");
+    }
+    
+    #[test]
+    fn tar_gz_entries_are_listed_without_extracting() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archive.tar.gz");
+        let gz = flate2::write::GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        let mut append = |name: &str, content: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            tar.append_data(&mut header, name, content).unwrap();
+        };
+        append("./src/main.rs", b"fn main() {}\n");
+        append("src/lib.rs", b"pub fn f() {}\n");
+        append("notes.md", b"# Notes\n");
+        append("src/huge.rs", &vec![b' '; MAX_ENTRY_BYTES as usize + 1]);
+        tar.into_inner().unwrap().finish().unwrap();
+        
+        let entries = read_code_entries(&path, is_rust).unwrap();
+        assert_eq!(paths(&entries), ["src/main.rs", "src/lib.rs"]);
+        assert_eq!(entries[0].display_path("archive.tar.gz"), "archive.tar.gz!src/main.rs");
+        assert!(!is_archive(Path::new("archive.gz")));
+    }
+}
@@ -0,0 +1,187 @@
+use std::path::Path;
+use anyhow::{bail, Context, Result};
+
+/// Extensions `load` can decode; MP3 and FLAC need the `compressed-audio`
+/// feature.
+#[cfg(feature = "compressed-audio")]
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "mp3", "flac"];
+#[cfg(not(feature = "compressed-audio"))]
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["wav"];
+
+/// Decodes an audio file to mono `f32` samples in -1.0..=1.0 at
+/// `sample_rate`, averaging the channels of stereo input.
+pub fn load(path: &Path, sample_rate: u32) -> Result<Vec<f32>> {
+    if !path.is_file() {
+        bail!("Audio file not found: {}", path.display());
+    }
+    
+    let extension = path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let (samples, channels, source_rate) = match extension.as_str() {
+        "wav" => decode_wav(path)?,
+        #[cfg(feature = "compressed-audio")]
+        "mp3" | "flac" => decode_compressed(path, &extension)?,
+        _ => bail!(
+            "Unsupported audio format for {} (supported: {})",
+            path.display(),
+            SUPPORTED_EXTENSIONS.join(", ")
+        ),
+    };
+    
+    Ok(resample(&downmix(&samples, channels), source_rate, sample_rate))
+}
+
+/// Interleaved samples, channel count and sample rate of a WAV file.
+fn decode_wav(path: &Path) -> Result<(Vec<f32>, usize, u32)> {
+    let mut reader = hound::WavReader::open(path)
+        .with_context(|| format!("Failed to read WAV file {}", path.display()))?;
+    let spec = reader.spec();
+    
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>()?,
+        hound::SampleFormat::Int => {
+            // Full scale for the bit depth maps to 1.0
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader.samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / scale))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+    };
+    
+    Ok((samples, spec.channels as usize, spec.sample_rate))
+}
+
+#[cfg(feature = "compressed-audio")]
+fn decode_compressed(path: &Path, extension: &str) -> Result<(Vec<f32>, usize, u32)> {
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::DecoderOptions;
+    use symphonia::core::errors::Error as DecodeError;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+    
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension(extension);
+    
+    let mut format = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .format;
+    let track = format.default_track()
+        .with_context(|| format!("No audio track in {}", path.display()))?;
+    let track_id = track.id;
+    let mut decoder = symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+    
+    let mut samples = Vec::new();
+    let mut channels = track.codec_params.channels.map_or(1, |channels| channels.count());
+    let mut source_rate = track.codec_params.sample_rate.unwrap_or(0);
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // The end of the stream is reported as an EOF error
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e).with_context(|| format!("Failed to decode {}", path.display())),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        
+        let decoded = decoder.decode(&packet)?;
+        let spec = *decoded.spec();
+        channels = spec.channels.count();
+        source_rate = spec.rate;
+        
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buffer.samples());
+    }
+    
+    if source_rate == 0 {
+        bail!("Unknown sample rate in {}", path.display());
+    }
+    Ok((samples, channels, source_rate))
+}
+
+/// Averages each frame of interleaved samples into one.
+fn downmix(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples.chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Linear interpolation from `from` Hz to `to` Hz; the result is as long
+/// as the input in time.
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    
+    let len = (samples.len() as u64 * to as u64 / from as u64) as usize;
+    let step = from as f64 / to as f64;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let next = samples.get(index + 1).unwrap_or(&samples[index]);
+            let fraction = (position - index as f64) as f32;
+            samples[index] + (next - samples[index]) * fraction
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn write_wav(path: &Path, channels: u16, sample_rate: u32, frames: usize) {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for frame in 0..frames {
+            // Left and right cancel out when averaged
+            let sample = ((frame % 100) as i16 - 50) * 100;
+            writer.write_sample(sample).unwrap();
+            if channels == 2 {
+                writer.write_sample(-sample).unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+    }
+    
+    #[test]
+    fn wav_files_decode_to_mono_at_the_requested_rate() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        
+        // Half a second of stereo at 44.1 kHz
+        let stereo = dir.join("stereo.wav");
+        write_wav(&stereo, 2, 44_100, 22_050);
+        let samples = load(&stereo, 16_000).unwrap();
+        assert_eq!(samples.len(), 8_000);
+        assert!(samples.iter().all(|sample| sample.abs() < 1e-6));
+        
+        // A quarter second of mono, already at the target rate
+        let mono = dir.join("mono.wav");
+        write_wav(&mono, 1, 16_000, 4_000);
+        let samples = load(&mono, 16_000).unwrap();
+        assert_eq!(samples.len(), 4_000);
+        assert!((samples[0] - (-5_000.0 / 32_768.0)).abs() < 1e-6);
+        
+        assert!(load(&dir.join("missing.wav"), 16_000).unwrap_err().to_string().contains("not found"));
+        std::fs::write(dir.join("notes.txt"), "not audio").unwrap();
+        assert!(load(&dir.join("notes.txt"), 16_000).unwrap_err().to_string().contains("Unsupported audio format"));
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+
+use crate::agent_config::DevAgentConfig;
+use crate::llm_agent::{LlmBackend, LlmConfig};
+use crate::{Args, DevAgent};
+
+/// One timed review of the whole tree.
+#[derive(Debug)]
+pub struct BenchRun {
+    pub jobs: usize,
+    pub files: usize,
+    pub elapsed: Duration,
+}
+
+impl BenchRun {
+    pub fn files_per_sec(&self) -> f64 {
+        self.files as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Runs at each `--jobs` value, the first being the sequential baseline.
+#[derive(Debug)]
+pub struct BenchReport {
+    pub latency: Duration,
+    pub runs: Vec<BenchRun>,
+}
+
+impl BenchReport {
+    /// Throughput of the run at `jobs` relative to the sequential one.
+    pub fn speedup(&self, run: &BenchRun) -> f64 {
+        match self.runs.first() {
+            Some(baseline) => run.files_per_sec() / baseline.files_per_sec().max(f64::EPSILON),
+            None => 1.0,
+        }
+    }
+}
+
+/// Reviews `path` once sequentially and once per `jobs` value, with the
+/// mock LLM backend answering after `latency` so timings don't depend on
+/// a model. Each run gets a fresh agent and a scratch memory file, removed
+/// with its temp dir when the benchmark ends.
+pub async fn run(path: &Path, jobs: &[usize], latency: Duration) -> Result<BenchReport> {
+    let mut levels = vec![1];
+    levels.extend(jobs.iter().copied().filter(|&jobs| jobs > 1));
+    levels.sort();
+    levels.dedup();
+    
+    let scratch = tempfile::tempdir()?;
+    let memory_file = scratch.path().join("memory.json");
+    let mut runs = Vec::new();
+    for jobs in levels {
+        let args = Args::for_review(path.to_path_buf(), None, false);
+        let config = DevAgentConfig { max_concurrency: jobs, ..DevAgentConfig::default() };
+        
+        let mut agent = DevAgent::new(args, config).await?;
+        agent.llm_agent.set_config(LlmConfig {
+            backend: LlmBackend::Mock,
+            mock_latency_ms: latency.as_millis() as u64,
+            ..LlmConfig::default()
+        });
+        agent.memory_system.get_mut().set_memory_file(memory_file.to_string_lossy());
+        
+        let started = Instant::now();
+        let report = agent.review_codebase().await?;
+        runs.push(BenchRun { jobs, files: report.reviews.len(), elapsed: started.elapsed() });
+    }
+    
+    Ok(BenchReport { latency, runs })
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Mock LLM latency: {} ms", self.latency.as_millis())?;
+        writeln!(f, "{:>5} {:>7} {:>10} {:>10} {:>8}", "jobs", "files", "seconds", "files/sec", "speedup")?;
+        for run in &self.runs {
+            writeln!(
+                f,
+                "{:>5} {:>7} {:>10.2} {:>10.2} {:>7.2}x",
+                run.jobs,
+                run.files,
+                run.elapsed.as_secs_f64(),
+                run.files_per_sec(),
+                self.speedup(run),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn every_file_is_reviewed_at_any_job_count() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..6 {
+            std::fs::write(dir.path().join(format!("file_{}.rs", i)), format!("pub fn f{}() {{}}\n", i)).unwrap();
+        }
+        
+        let report = run(dir.path(), &[2, 6, 16], Duration::from_millis(5)).await.unwrap();
+        let counts: Vec<(usize, usize)> = report.runs.iter().map(|run| (run.jobs, run.files)).collect();
+        assert_eq!(counts, [(1, 6), (2, 6), (6, 6), (16, 6)]);
+    }
+    
+    #[test]
+    fn speedup_is_relative_to_the_sequential_run() {
+        let run = |jobs, millis| BenchRun { jobs, files: 8, elapsed: Duration::from_millis(millis) };
+        let report = BenchReport { latency: Duration::from_millis(100), runs: vec![run(1, 800), run(4, 200)] };
+        assert!((report.speedup(&report.runs[1]) - 4.0).abs() < 1e-9);
+        
+        let table = report.to_string();
+        assert!(table.starts_with("Mock LLM latency: 100 ms\n"), "{}", table);
+        assert!(table.lines().any(|line| line.split_whitespace().eq(["4", "8", "0.20", "40.00", "4.00x"])), "{}", table);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::report_meta;
+
+/// Written to the report root while a review runs.
+pub const FILE_NAME: &str = ".devagent-progress";
+
+/// One finished file, as a line of JSON.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    path: String,
+    content_hash: String,
+    config_hash: String,
+    review: serde_json::Value,
+}
+
+/// Reviews finished so far in this run, appended as each file completes so
+/// an interrupted run can pick up where it stopped with `--resume`.
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    config_hash: String,
+    done: HashMap<String, Entry>,
+    file: Mutex<File>,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint in `dir`. With `resume` the reviews already in
+    /// it are kept, except those made under a different config; otherwise
+    /// it starts empty.
+    pub fn open(dir: &Path, config_hash: &str, resume: bool) -> Result<Self> {
+        let path = dir.join(FILE_NAME);
+        let previous = match std::fs::read_to_string(&path) {
+            Ok(content) if resume => content,
+            Ok(_) => String::new(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        
+        // A line cut short by the interruption is dropped; later entries
+        // for the same path replace earlier ones
+        let done = previous.lines()
+            .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+            .filter(|entry| entry.config_hash == config_hash)
+            .map(|entry| (entry.path.clone(), entry))
+            .collect();
+        
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        if !resume {
+            file.set_len(0)?;
+        } else if !previous.is_empty() && !previous.ends_with('\n') {
+            // Keep new entries off the cut-short line
+            file.write_all(b"\n")?;
+        }
+        
+        Ok(Self { path, config_hash: config_hash.to_string(), done, file: Mutex::new(file) })
+    }
+    
+    /// Files carried over from the interrupted run.
+    pub fn resumed(&self) -> usize {
+        self.done.len()
+    }
+    
+    /// The stored review of `path` if it finished before and `content` hasn't
+    /// changed since.
+    pub fn completed(&self, path: &str, content: &str) -> Option<&serde_json::Value> {
+        self.done.get(path)
+            .filter(|entry| entry.content_hash == report_meta::stable_hash(content))
+            .map(|entry| &entry.review)
+    }
+    
+    pub fn record(&self, path: &str, content: &str, review: &impl Serialize) -> Result<()> {
+        let entry = Entry {
+            path: path.to_string(),
+            content_hash: report_meta::stable_hash(content),
+            config_hash: self.config_hash.clone(),
+            review: serde_json::to_value(review)?,
+        };
+        let line = format!("{}\n", serde_json::to_string(&entry)?);
+        
+        // One write per entry, so a crash leaves at most one partial line
+        let mut file = self.file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+    
+    /// Removes the checkpoint once the run has finished and been saved.
+    pub fn clear(self) -> Result<()> {
+        drop(self.file);
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", self.path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn resumed_runs_skip_only_unchanged_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let review = serde_json::json!({ "file_path": "src/a.rs", "score": 0.9 });
+        
+        let checkpoint = Checkpoint::open(dir, "config-1", false).unwrap();
+        checkpoint.record("src/a.rs", "fn a() {}", &review).unwrap();
+        checkpoint.record("src/b.rs", "fn b() {}", &review).unwrap();
+        drop(checkpoint);
+        // Interrupted halfway through writing the next entry
+        let mut file = OpenOptions::new().append(true).open(dir.join(FILE_NAME)).unwrap();
+        file.write_all(b"{\"path\":\"src/c.rs\",\"cont").unwrap();
+        
+        let resumed = Checkpoint::open(dir, "config-1", true).unwrap();
+        assert_eq!(resumed.resumed(), 2);
+        assert_eq!(resumed.completed("src/a.rs", "fn a() {}"), Some(&review));
+        assert_eq!(resumed.completed("src/b.rs", "fn b() { todo!() }"), None);
+        assert_eq!(resumed.completed("src/c.rs", ""), None);
+        resumed.record("src/c.rs", "fn c() {}", &review).unwrap();
+        drop(resumed);
+        assert_eq!(Checkpoint::open(dir, "config-1", true).unwrap().resumed(), 3);
+        
+        // Reviews under another config don't carry over, and a fresh run starts empty
+        assert_eq!(Checkpoint::open(dir, "config-2", true).unwrap().resumed(), 0);
+        assert_eq!(Checkpoint::open(dir, "config-1", false).unwrap().resumed(), 0);
+        assert_eq!(Checkpoint::open(dir, "config-1", true).unwrap().resumed(), 0);
+        
+        Checkpoint::open(dir, "config-1", true).unwrap().clear().unwrap();
+        assert!(!dir.join(FILE_NAME).exists());
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+/// Rough characters per token for source code, used to fit text into a
+/// context window without a tokenizer.
+pub const CHARS_PER_TOKEN: usize = 4;
+
+// Width of the "  123 | " line-number gutter added to each chunk line
+const GUTTER_WIDTH: usize = 8;
+
+/// A run of whole lines from a file, at most the size it was split for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// 1-based, inclusive
+    pub first_line: usize,
+    pub last_line: usize,
+    /// The lines prefixed with their line numbers in the whole file, so
+    /// line references the model makes point at the right place
+    pub numbered: String,
+}
+
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Splits `content` into chunks of at most `max_tokens` (numbered text),
+/// each repeating the last `overlap_lines` lines of the one before so code
+/// on a boundary is seen whole at least once. A single line longer than
+/// the budget becomes a chunk of its own.
+pub fn split(content: &str, max_tokens: usize, overlap_lines: usize) -> Vec<Chunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    let max_chars = max_tokens * CHARS_PER_TOKEN;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    
+    while start < lines.len() {
+        let mut end = start;
+        let mut size = 0;
+        while end < lines.len() {
+            let line_size = lines[end].len() + GUTTER_WIDTH + 1;
+            if end > start && size + line_size > max_chars {
+                break;
+            }
+            size += line_size;
+            end += 1;
+        }
+        
+        let numbered = (start..end)
+            .map(|i| format!("{:>5} | {}", i + 1, lines[i]))
+            .collect::<Vec<_>>()
+            .join("\n");
+        chunks.push(Chunk { first_line: start + 1, last_line: end, numbered });
+        
+        if end == lines.len() {
+            break;
+        }
+        // Always move forward, even if the overlap covers the whole chunk
+        start = end.saturating_sub(overlap_lines).max(start + 1);
+    }
+    
+    chunks
+}
+
+/// Suggestions from every chunk, tagged with the chunk's line range and
+/// with repeats (usually from the overlap) dropped.
+pub fn merge(per_chunk: Vec<(&Chunk, Vec<String>)>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    
+    for (chunk, suggestions) in per_chunk {
+        for suggestion in suggestions {
+            let key = suggestion.to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ");
+            if seen.insert(key) {
+                merged.push(format!("Lines {}-{}: {}", chunk.first_line, chunk.last_line, suggestion));
+            }
+        }
+    }
+    
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn oversized_files_are_split_into_overlapping_numbered_chunks() {
+        let content: String = (1..=10).map(|i| format!("let line_{} = {};\n", i, i)).collect();
+        // 15-char lines plus gutter and newline: two fit in a 16-token (64-char) chunk
+        let chunks = split(&content, 16, 1);
+        assert_eq!(chunks.iter().map(|c| (c.first_line, c.last_line)).collect::<Vec<_>>(), vec![(1, 2), (2, 3), (3, 4), (4, 5), (5, 6), (6, 7), (7, 8), (8, 9), (9, 10)]);
+        assert_eq!(chunks[1].numbered, "    2 | let line_2 = 2;\n    3 | let line_3 = 3;");
+        assert_eq!(split(&content, 1000, 20).len(), 1);
+        
+        let merged = merge(vec![
+            (&chunks[0], vec!["Rename line_1".to_string(), "Use  const".to_string()]),
+            (&chunks[1], vec!["use const".to_string(), "Inline line_3".to_string()]),
+        ]);
+        assert_eq!(merged, vec!["Lines 1-2: Rename line_1", "Lines 1-2: Use  const", "Lines 2-3: Inline line_3"]);
+    }
+}
@@ -1,19 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::Subcommand;
 use std::path::PathBuf;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use tokio::process::Command;
 
-#[derive(Parser)]
-#[command(name = "kov-code-agent")]
-#[command(about = "Kowalski Code Agent - AI-powered code review and refactoring")]
-#[command(version)]
-pub struct Cli {
-    #[command(subcommand)]
-    command: Commands,
-}
-
-#[derive(Subcommand)]
-enum Commands {
+#[derive(Subcommand, Debug, Clone)]
+pub enum Commands {
     /// Review code in a directory
     Review {
         /// Path to review
@@ -49,6 +40,85 @@ enum Commands {
         /// Review before committing
         #[arg(short, long)]
         review: bool,
+        
+        /// Path to commit; git runs in the repo containing it
+        #[arg(default_value = "./src")]
+        path: PathBuf,
+    },
+    
+    /// Review only lines changed since a base ref and emit inline PR comments
+    PrReview {
+        /// Base ref to diff against
+        #[arg(short, long, default_value = "origin/main")]
+        base: String,
+        
+        /// Path to review
+        #[arg(default_value = "./src")]
+        path: PathBuf,
+        
+        /// Output file for the review comments JSON (stdout if omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    
+    /// Maintain the review memory store
+    Memory {
+        #[command(subcommand)]
+        action: MemoryCommands,
+    },
+    
+    /// Write a commented starter .devagent.toml with every setting
+    Init {
+        /// Where to write the config
+        #[arg(default_value = crate::config::CONFIG_FILE)]
+        path: PathBuf,
+        
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    
+    /// Inspect the .devagent.toml review config
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+    
+    /// Print the profile, config hash and rules a JSON report was made with
+    ReportMeta {
+        /// JSON report to inspect
+        path: PathBuf,
+    },
+    
+    /// Compare two JSON reports: issues added, removed and changed, and
+    /// the score delta per file and overall
+    Diff {
+        /// Older report
+        old: PathBuf,
+        /// Newer report
+        new: PathBuf,
+        /// Exit non-zero if any file got worse
+        #[arg(long)]
+        fail_on_regression: bool,
+    },
+    
+    /// Measure review throughput sequentially and at several job counts,
+    /// with a mock LLM of fixed latency
+    Bench {
+        /// Tree to review
+        #[arg(default_value = "./src")]
+        path: PathBuf,
+        /// Job counts to compare against the sequential run
+        #[arg(long, value_delimiter = ',', default_value = "2,4,8")]
+        jobs: Vec<usize>,
+        /// Mock LLM response time in milliseconds
+        #[arg(long, default_value = "250")]
+        latency_ms: u64,
+        /// Exit non-zero if the highest job count is less than this many
+        /// times faster than sequential, e.g. because a lock serializes
+        /// the reviews
+        #[arg(long)]
+        min_speedup: Option<f64>,
     },
     
     /// Run interactive mode
@@ -59,98 +129,173 @@ enum Commands {
     },
 }
 
-pub async fn run_cli() -> Result<()> {
-    let cli = Cli::parse();
-    
-    match cli.command {
+#[derive(Subcommand, Debug, Clone)]
+pub enum MemoryCommands {
+    /// Drop entries for deleted files and stale analyses, then compact the file
+    Vacuum {
+        /// Clear analyses not updated within this many days
+        #[arg(long, default_value = "30")]
+        max_age_days: i64,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommands {
+    /// Parse and validate a config file, listing every problem found
+    Check {
+        /// Config file to check
+        #[arg(default_value = crate::config::CONFIG_FILE)]
+        path: PathBuf,
+    },
+}
+
+impl Commands {
+    /// Whether the command asked for debug logging.
+    pub fn verbose(&self) -> bool {
+        matches!(self, Commands::Review { verbose: true, .. })
+    }
+}
+
+pub async fn run_cli(command: Commands) -> Result<()> {
+    match command {
         Commands::Review { path, output, verbose } => {
             println!("Reviewing code in: {}", path.display());
             
             // Run the review using our DevAgent
-            let args = crate::Args {
-                path,
-                output,
-                verbose,
-                interactive: false,
-            };
+            let args = crate::Args::for_review(path, output, verbose);
             
-            let agent = crate::DevAgent::new(args).await?;
-            let reviews = agent.review_codebase().await?;
-            agent.save_reviews(&reviews).await?;
+            let agent = crate::DevAgent::new(args, crate::agent_config::DevAgentConfig::default()).await?;
+            let report = agent.review_codebase().await?;
+            agent.save_reviews(&report).await?;
             
-            println!("Review completed! Found {} files with issues.", reviews.len());
+            println!("Review completed! Found {} files with issues.", report.reviews.len());
         }
         
         Commands::Patch { path, output } => {
             println!("Generating patches for: {}", path.display());
             
-            let args = crate::Args {
-                path,
-                output: None,
-                verbose: false,
-                interactive: false,
-            };
+            let mut args = crate::Args::for_review(path, None, false);
+            args.patch_dir = output.clone();
             
-            let agent = crate::DevAgent::new(args).await?;
-            let reviews = agent.review_codebase().await?;
-            agent.generate_patches(&reviews).await?;
+            let agent = crate::DevAgent::new(args, crate::agent_config::DevAgentConfig::default()).await?;
+            let report = agent.review_codebase().await?;
+            agent.generate_patches(&report.reviews).await?;
             
             println!("Patches generated in: {}", output.display());
         }
         
-        Commands::Commit { message, review } => {
+        Commands::Commit { message, review, path } => {
             if review {
                 println!("Running review before commit...");
-                let args = crate::Args {
-                    path: PathBuf::from("./src"),
-                    output: None,
-                    verbose: false,
-                    interactive: false,
-                };
+                let args = crate::Args::for_review(path.clone(), None, false);
                 
-                let agent = crate::DevAgent::new(args).await?;
-                let reviews = agent.review_codebase().await?;
+                let agent = crate::DevAgent::new(args, crate::agent_config::DevAgentConfig::default()).await?;
+                let report = agent.review_codebase().await?;
                 
-                if !reviews.is_empty() {
+                if !report.reviews.is_empty() {
                     println!("Found {} issues. Proceeding with commit...", 
-                        reviews.iter().map(|r| r.issues.len()).sum::<usize>());
+                        report.reviews.iter().map(|r| r.issues.len()).sum::<usize>());
                 }
             }
             
             println!("Committing changes with message: {}", message);
             
-            let status = Command::new("git")
-                .args(["add", "."])
-                .status()
-                .await?;
+            if crate::git_ops::commit_all(&path, &message).await? {
+                println!("Changes committed successfully!");
+            } else {
+                println!("No changes to commit.");
+            }
+        }
+        
+        Commands::PrReview { base, path, output } => {
+            let args = crate::Args::for_review(path, None, false);
+            
+            let agent = crate::DevAgent::new(args, crate::agent_config::DevAgentConfig::default()).await?;
+            let payload = crate::pr_review::review_changes(&agent, &base).await?;
+            let json = serde_json::to_string_pretty(&payload)?;
             
-            if status.success() {
-                let status = Command::new("git")
-                    .args(["commit", "-m", &message])
-                    .status()
-                    .await?;
+            match output {
+                Some(output) => {
+                    tokio::fs::write(&output, json).await?;
+                    println!("Wrote {} review comments to {}", payload.comments.len(), output.display());
+                }
+                None => println!("{}", json),
+            }
+        }
+        
+        Commands::Memory { action } => match action {
+            MemoryCommands::Vacuum { max_age_days } => {
+                let mut memory = crate::memory_system::MemorySystem::new().await?;
+                let report = memory.vacuum(chrono::Duration::days(max_age_days)).await?;
+                
+                println!("Removed {} entries for missing files", report.removed_entries);
+                println!("Cleared {} analyses older than {} days", report.cleared_analyses, max_age_days);
+                println!("Reclaimed {} bytes ({} -> {})", report.bytes_reclaimed(), report.bytes_before, report.bytes_after);
+            }
+        },
+        
+        Commands::Init { path, force } => {
+            crate::config::write_starter(&path, force).await?;
+            println!("Wrote {}", path.display());
+        }
+        
+        Commands::Config { action } => match action {
+            ConfigCommands::Check { path } => {
+                let content = tokio::fs::read_to_string(&path).await
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
                 
-                if status.success() {
-                    println!("Changes committed successfully!");
-                } else {
-                    println!("No changes to commit.");
+                match crate::config::ReviewConfig::check(&content) {
+                    Ok(_) => println!("{}: OK", path.display()),
+                    Err(problems) => {
+                        for problem in &problems {
+                            println!("{}: {}", path.display(), problem);
+                        }
+                        return Err(anyhow::anyhow!("{} has {} problem(s)", path.display(), problems.len()));
+                    }
+                }
+            }
+        },
+        
+        Commands::ReportMeta { path } => {
+            let (format_version, meta) = crate::report_meta::read(&path).await?;
+            println!("Report format: {}", format_version);
+            println!("{}", meta);
+        }
+        
+        Commands::Diff { old, new, fail_on_regression } => {
+            let old = crate::report_diff::Snapshot::load(&old).await?;
+            let new = crate::report_diff::Snapshot::load(&new).await?;
+            let diff = crate::report_diff::diff(old, new);
+            println!("{}", diff);
+            
+            let regressions = diff.regressions();
+            if fail_on_regression && !regressions.is_empty() {
+                return Err(anyhow::anyhow!("{} file(s) regressed", regressions.len()));
+            }
+        }
+        
+        Commands::Bench { path, jobs, latency_ms, min_speedup } => {
+            let report = crate::bench::run(&path, &jobs, std::time::Duration::from_millis(latency_ms)).await?;
+            print!("{}", report);
+            
+            if let (Some(min_speedup), Some(fastest)) = (min_speedup, report.runs.last()) {
+                let speedup = report.speedup(fastest);
+                if speedup < min_speedup {
+                    return Err(anyhow::anyhow!(
+                        "{} jobs were only {:.2}x faster than sequential, expected at least {:.2}x",
+                        fastest.jobs, speedup, min_speedup
+                    ));
                 }
-            } else {
-                println!("Failed to add files to git.");
             }
         }
         
         Commands::Interactive { path } => {
             println!("Starting interactive mode for: {}", path.display());
             
-            let args = crate::Args {
-                path,
-                output: None,
-                verbose: false,
-                interactive: true,
-            };
+            let mut args = crate::Args::for_review(path, None, false);
+            args.interactive = true;
             
-            let agent = crate::DevAgent::new(args).await?;
+            let agent = crate::DevAgent::new(args, crate::agent_config::DevAgentConfig::default()).await?;
             agent.run_interactive_mode().await?;
         }
     }
@@ -183,4 +328,37 @@ pub async fn check_kowalski_available() -> bool {
         .await
         .map(|output| output.status.success())
         .unwrap_or(false)
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ReviewConfig;
+    
+    #[tokio::test]
+    async fn config_check_lists_every_problem_and_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = |path: PathBuf| run_cli(Commands::Config { action: ConfigCommands::Check { path } });
+        
+        let valid = dir.path().join("valid.toml");
+        std::fs::write(&valid, "max_line_length = 100\n").unwrap();
+        assert!(check(valid).await.is_ok());
+        
+        let content = "[duplicate_literals]\nthreshold = 1\n\n[semantic_search]\nchunk_lines = 0\n";
+        let problems: Vec<String> = ReviewConfig::check(content).unwrap_err().iter().map(|problem| problem.to_string()).collect();
+        assert_eq!(problems, [
+            "line 2: duplicate_literals.threshold must be at least 2",
+            "line 5: semantic_search.chunk_lines must be at least 1",
+        ]);
+        let invalid = dir.path().join("invalid.toml");
+        std::fs::write(&invalid, content).unwrap();
+        let error = check(invalid.clone()).await.unwrap_err();
+        assert_eq!(error.to_string(), format!("{} has 2 problem(s)", invalid.display()));
+        
+        // Parse errors stop at the first problem, with its line
+        let problems = ReviewConfig::check("max_line_length = 100\nunknown_key = true\n").unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(2));
+        
+        assert!(check(dir.path().join("missing.toml")).await.unwrap_err().to_string().starts_with("Failed to read"));
+    }
+}
@@ -0,0 +1,94 @@
+/// Comment syntax: line comment markers and an optional block delimiter
+/// pair.
+struct Syntax {
+    line: &'static [&'static str],
+    block: Option<(&'static str, &'static str)>,
+}
+
+const HASH: Syntax = Syntax { line: &["#"], block: None };
+const C_STYLE: Syntax = Syntax { line: &["//"], block: Some(("/*", "*/")) };
+const PHP: Syntax = Syntax { line: &["//", "#"], block: Some(("/*", "*/")) };
+
+// `language` is a language name or a file extension; anything unknown is
+// taken to use C-style comments
+fn syntax(language: &str) -> Syntax {
+    match language {
+        "python" | "py" | "ruby" | "rb" | "shell" | "sh" | "bash" | "yaml" | "yml" | "toml" => HASH,
+        "php" => PHP,
+        _ => C_STYLE,
+    }
+}
+
+/// What starts a line comment in `language`.
+pub fn line_comment(language: &str) -> &'static str {
+    syntax(language).line[0]
+}
+
+/// Non-blank lines that hold any comment: whole-line and trailing line
+/// comments, and every line a block comment touches, including ones that
+/// open it after code (`let x = 5; /* why`). Comment markers inside string
+/// literals don't count.
+pub fn count_comment_lines(content: &str, language: &str) -> usize {
+    let syntax = syntax(language);
+    let mut in_block = false;
+    let mut count = 0;
+    
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        
+        let mut has_comment = in_block;
+        let mut quote = None;
+        let mut rest = line;
+        while let Some(c) = rest.chars().next() {
+            if in_block {
+                let Some((_, close)) = syntax.block else { break };
+                match rest.find(close) {
+                    Some(end) => {
+                        in_block = false;
+                        rest = &rest[end + close.len()..];
+                    }
+                    None => break,
+                }
+                continue;
+            }
+            
+            if let Some(open) = quote {
+                if c == '\\' {
+                    // Skip the escaped character along with the backslash
+                    let mut chars = rest.chars();
+                    chars.next();
+                    chars.next();
+                    rest = chars.as_str();
+                    continue;
+                }
+                if c == open {
+                    quote = None;
+                }
+            } else if rest.starts_with("'\"'") {
+                // A quote character literal doesn't start a string
+                rest = &rest[3..];
+                continue;
+            } else if c == '"' {
+                quote = Some(c);
+            } else if syntax.line.iter().any(|marker| rest.starts_with(marker)) {
+                has_comment = true;
+                break;
+            } else if let Some((open, _)) = syntax.block.filter(|(open, _)| rest.starts_with(open)) {
+                has_comment = true;
+                in_block = true;
+                rest = &rest[open.len()..];
+                continue;
+            }
+            
+            rest = &rest[c.len_utf8()..];
+        }
+        
+        if has_comment {
+            count += 1;
+        }
+    }
+    
+    count
+}
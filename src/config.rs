@@ -0,0 +1,726 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+
+use crate::code_analyzer::{IssueCategory, Severity};
+use crate::dependency_policy::DependencyPolicy;
+use crate::docs::MissingDocsConfig;
+use crate::duplication::DuplicateBlockConfig;
+use crate::literals::DuplicateLiteralConfig;
+use crate::llm_agent::LlmConfig;
+use crate::naming::NamingConventions;
+use crate::scoring::SeverityWeights;
+use crate::semantic_search::SemanticSearchConfig;
+use crate::size_limits::SizeLimits;
+use crate::macro_usage::MacroUsageConfig;
+
+pub const CONFIG_FILE: &str = ".devagent.toml";
+
+/// Commented starter config written by `init`. Active keys are at their
+/// defaults; keys that replace built-in lists are left commented out.
+pub const STARTER_CONFIG: &str = r#"# DevAgent review settings. Every key is optional and the active values
+# below are the built-in defaults. Validate edits with `config check`.
+
+# Name recorded in report headers, so reports made under different
+# settings aren't compared as if they were alike
+# profile = "ci"
+
+# Lines longer than this are flagged, 120 by default
+# (DEVAGENT_MAX_LINE_LENGTH overrides)
+# max_line_length = 100
+
+# Fail the run when any issue is at or above this severity: Low, Medium,
+# High or Critical (--fail-on and DEVAGENT_FAIL_ON override)
+# fail_on = "High"
+
+# Rule ids to turn off. A .devagent.toml in a subdirectory overrides this
+# file for that subtree, e.g. to relax rules under examples/
+# disabled_rules = ["rust/unwrap"]
+
+# Severity overrides keyed by rule id
+[rule_severity]
+# "all/todo" = "Medium"
+
+# Extra file extensions to review as a known language, without the dot.
+# A `devagent:language=<name>` comment in a file still wins over this.
+[languages]
+# tsx = "typescript"
+
+[llm]
+# Model name sent to the local LLM endpoint (DEVAGENT_LLM_MODEL or LLM_MODEL overrides)
+model = "phi-3-mini-instruct"
+# Most AI suggestions kept per file
+max_suggestions = 5
+# Must contain {content}; {language} and {max_suggestions} are optional
+# prompt_template = "Review this {language} code:\n\n{content}"
+# "http" calls the endpoint at LLM_ENDPOINT; "subprocess" runs the model
+# in a worker process that is restarted if it crashes; "mock" answers
+# after mock_latency_ms with a canned suggestion, for benchmarks
+backend = "http"
+# mock_latency_ms = 250
+# The model's context window in tokens; larger files are analyzed in
+# chunks, each repeating chunk_overlap_lines lines of the one before
+context_tokens = 4096
+chunk_overlap_lines = 20
+# HTTP requests are tried up to max_attempts times, waiting
+# retry_backoff_ms before the first retry and doubling after; timeouts,
+# connection errors, 429 and 5xx (e.g. a model still loading) are retried
+max_attempts = 3
+retry_backoff_ms = 500
+attempt_timeout_secs = 120
+
+# Worker process for backend = "subprocess"
+[llm.worker]
+# Local model weights, downloaded by default
+# model_path = "/models/phi-3-mini"
+# Address space cap for the worker in MiB (Unix only)
+# memory_limit_mb = 8192
+# Crash restarts before falling back to static suggestions
+max_restarts = 3
+# Seconds for a fresh worker to load the model and answer a health check
+startup_timeout_secs = 120
+# Seconds for one generate request
+request_timeout_secs = 60
+
+# Search the memory store by meaning as well as by substring. Needs a
+# local BERT embedding model and a running Qdrant
+[semantic_search]
+enabled = false
+# Directory with model.safetensors, tokenizer.json and config.json
+model_path = "models/bge-small-en"
+qdrant_url = "http://localhost:6334"
+collection = "devagent_memory"
+# Lines per embedded chunk of a file
+chunk_lines = 40
+
+# String literals repeated in one file
+[duplicate_literals]
+# Report literals that appear at least this many times
+threshold = 3
+# Ignore literals shorter than this many characters
+min_length = 4
+
+# Score penalty per issue by severity, spread over the file's lines
+[severity_weights]
+critical = 0.4
+high = 0.2
+medium = 0.1
+low = 0.05
+
+# Copy-pasted blocks in one file
+[duplicate_blocks]
+# Report runs of at least this many non-blank lines that repeat
+window_lines = 6
+
+# Overly broad or empty exception handlers
+[broad_catch]
+severity = "Medium"
+
+# Where unwrap() is accepted without an issue
+[unwrap]
+# #[test] functions and #[cfg(test)] modules
+in_tests = true
+# .lock().unwrap(), .read().unwrap(), .write().unwrap()
+lock_guards = true
+# Regex::new("literal").unwrap()
+constant_regex = true
+# Let `devagent:ignore` comments on the same or previous line silence unwraps
+suppression_comments = true
+# Only flag unwraps inside pub fns
+only_public_fns = false
+
+# Hard length limits, off unless set
+[size_limits]
+# max_file_lines = 500
+# max_function_lines = 80
+severity = "Medium"
+
+# Public items without doc comments (Rust) or docstrings (Python)
+[missing_docs]
+enabled = true
+severity = "Low"
+
+# Macro-heavy Rust (test code isn't counted)
+[macros]
+# Flag files with more macro invocations than this per 100 lines...
+max_density = 10.0
+# ...once they have at least this many
+min_invocations = 20
+# Flag macro_rules! definitions longer than this many lines
+max_macro_rules_lines = 40
+# Macros that don't count besides the standard library's
+ignore = ["trace", "debug", "info", "warn", "error"]
+
+# Checks on Cargo.toml dependencies; empty lists turn each check off
+[dependency_policy]
+# Crates that must not be used; `git_only = true` denies only git sources
+# deny = [{ name = "openssl", reason = "use rustls instead" }]
+# When non-empty, every dependency must be on this list
+allow = []
+# Crates that should be used with `default-features = false`
+lean = []
+
+# Banned calls per language ("rust", "python", "javascript", ...) or "all".
+# Listing a language replaces its built-in list.
+# [[banned_calls.python]]
+# pattern = "requests.get("
+# message = "Use the shared HTTP client"
+# severity = "Medium"        # default Critical
+# category = "Security"      # default Security
+
+# Extra anti-patterns per language, matched as substrings of each line.
+# A pattern that's already built in takes these settings instead.
+# [[rules.rust.anti_patterns]]
+# pattern = "expect("
+# message = "Prefer ? over expect()"
+# severity = "Medium"        # default Medium
+# category = "ErrorHandling" # default Maintainability
+# rule = "rust/expect"       # default from the pattern's words
+
+# Naming conventions per language: snake_case, camel_case, pascal_case or
+# screaming_snake_case. Listing a language replaces its defaults; omitted
+# kinds aren't checked.
+# [naming.rust]
+# functions = "snake_case"
+# variables = "snake_case"
+# types = "pascal_case"
+
+# Ignored paths and rules live in .devagentignore, one `glob [rules...]`
+# per line, e.g. `tests/ rust/unwrap` or `generated/`.
+"#;
+
+/// Project-level review settings read from `.devagent.toml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReviewConfig {
+    /// Name recorded in report headers, e.g. "ci" or "strict"
+    pub profile: Option<String>,
+    /// Banned calls keyed by language ("rust", "python", "javascript", ...)
+    /// or "all" for every file. Listing a language replaces its defaults.
+    pub banned_calls: HashMap<String, Vec<BannedCall>>,
+    /// Naming conventions keyed by language. Listing a language replaces
+    /// its defaults; omitted kinds are not checked.
+    pub naming: HashMap<String, NamingConventions>,
+    pub llm: LlmConfig,
+    /// Extension (without the dot) to language name, e.g. `tsx = "typescript"`.
+    /// A `devagent:language=` comment in a file still wins over this.
+    pub languages: HashMap<String, String>,
+    pub duplicate_literals: DuplicateLiteralConfig,
+    pub duplicate_blocks: DuplicateBlockConfig,
+    /// Score penalty per issue by severity
+    pub severity_weights: SeverityWeights,
+    /// Embedding search over the memory store, off by default
+    pub semantic_search: SemanticSearchConfig,
+    pub broad_catch: BroadCatchConfig,
+    pub unwrap: UnwrapAllowlist,
+    /// Hard file and function length limits, off unless set
+    pub size_limits: SizeLimits,
+    /// Lines longer than this are flagged (default 120)
+    pub max_line_length: Option<usize>,
+    /// Fail the run when any issue is at or above this severity
+    pub fail_on: Option<Severity>,
+    /// Denied, allowed and lean crates for Cargo.toml dependencies
+    pub dependency_policy: DependencyPolicy,
+    pub missing_docs: MissingDocsConfig,
+    /// Macro density and `macro_rules!` length limits for Rust
+    pub macros: MacroUsageConfig,
+    /// Rule ids to turn off, e.g. `["rust/unwrap"]`
+    pub disabled_rules: Vec<String>,
+    /// Severity overrides keyed by rule id
+    pub rule_severity: HashMap<String, Severity>,
+    /// Extra line-pattern rules keyed by language, merged into the
+    /// built-in ones (see `LanguageRuleConfig`)
+    pub rules: HashMap<String, LanguageRuleConfig>,
+}
+
+/// Rules added to one language's built-ins. A pattern that's already
+/// built in replaces that rule's message, severity and category.
+///
+/// ```toml
+/// [[rules.rust.anti_patterns]]
+/// pattern = "expect("                  # matched as a substring of each line
+/// message = "Prefer ? over expect()"
+/// severity = "Medium"                  # Low, Medium, High or Critical; default Medium
+/// category = "ErrorHandling"           # Security, Performance, Maintainability, Style,
+///                                      # Documentation or ErrorHandling; default Maintainability
+/// rule = "rust/expect"                 # optional; defaults to `<language>/<pattern words>`
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LanguageRuleConfig {
+    pub anti_patterns: Vec<AntiPatternConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AntiPatternConfig {
+    pub pattern: String,
+    pub message: String,
+    #[serde(default = "AntiPatternConfig::default_severity")]
+    pub severity: Severity,
+    #[serde(default = "AntiPatternConfig::default_category")]
+    pub category: IssueCategory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+impl AntiPatternConfig {
+    fn default_severity() -> Severity {
+        Severity::Medium
+    }
+    
+    fn default_category() -> IssueCategory {
+        IssueCategory::Maintainability
+    }
+    
+    /// The configured rule id, or one made from the pattern's words:
+    /// `.expect(` in Rust becomes `rust/expect`.
+    pub fn rule_id(&self, language: &str) -> String {
+        if let Some(rule) = &self.rule {
+            return rule.clone();
+        }
+        
+        let words: Vec<String> = self.pattern
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_ascii_lowercase())
+            .collect();
+        format!("{}/{}", language, if words.is_empty() { "custom".to_string() } else { words.join("-") })
+    }
+}
+
+/// Contexts where `unwrap()` is accepted without an issue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UnwrapAllowlist {
+    /// `#[test]` functions and `#[cfg(test)]` modules
+    pub in_tests: bool,
+    /// `.lock().unwrap()`, `.read().unwrap()`, `.write().unwrap()`
+    pub lock_guards: bool,
+    /// `Regex::new("literal").unwrap()`
+    pub constant_regex: bool,
+    /// Let `devagent:ignore` comments on the same or previous line
+    /// silence unwraps, as they do other rules
+    pub suppression_comments: bool,
+    /// Only flag unwraps inside `pub fn`s
+    pub only_public_fns: bool,
+}
+
+impl Default for UnwrapAllowlist {
+    fn default() -> Self {
+        Self {
+            in_tests: true,
+            lock_guards: true,
+            constant_regex: true,
+            suppression_comments: true,
+            only_public_fns: false,
+        }
+    }
+}
+
+/// Settings for the broad/empty exception handler check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BroadCatchConfig {
+    pub severity: Severity,
+}
+
+impl Default for BroadCatchConfig {
+    fn default() -> Self {
+        Self {
+            severity: Severity::Medium,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BannedCall {
+    pub pattern: String,
+    pub message: String,
+    #[serde(default = "BannedCall::default_severity")]
+    pub severity: Severity,
+    #[serde(default = "BannedCall::default_category")]
+    pub category: IssueCategory,
+}
+
+impl BannedCall {
+    fn new(pattern: &str, message: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            message: message.to_string(),
+            severity: Self::default_severity(),
+            category: Self::default_category(),
+        }
+    }
+    
+    fn default_severity() -> Severity {
+        Severity::Critical
+    }
+    
+    fn default_category() -> IssueCategory {
+        IssueCategory::Security
+    }
+    
+    pub fn defaults() -> HashMap<String, Vec<BannedCall>> {
+        let mut defaults = HashMap::new();
+        
+        defaults.insert("all".to_string(), vec![
+            BannedCall::new("eval(", "Dangerous code execution pattern detected"),
+            BannedCall::new("exec(", "Dangerous code execution pattern detected"),
+        ]);
+        
+        defaults.insert("rust".to_string(), vec![
+            BannedCall::new("unsafe {", "Unsafe block bypasses the borrow checker"),
+            BannedCall::new("mem::transmute", "mem::transmute reinterprets memory without checks"),
+        ]);
+        
+        defaults.insert("python".to_string(), vec![
+            BannedCall::new("os.system(", "os.system() runs commands through the shell"),
+            BannedCall::new("shell=True", "Subprocess call with shell=True allows shell injection"),
+            BannedCall::new("pickle.loads(", "pickle.loads() can execute arbitrary code on untrusted data"),
+        ]);
+        
+        defaults.insert("javascript".to_string(), vec![
+            BannedCall::new("dangerouslySetInnerHTML", "dangerouslySetInnerHTML can introduce XSS"),
+            BannedCall::new("new Function(", "new Function() compiles code from strings"),
+        ]);
+        
+        defaults
+    }
+}
+
+/// A config problem, with the 1-based line it was found on when known.
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl ReviewConfig {
+    /// Parses and validates config text, collecting every problem rather
+    /// than stopping at the first.
+    pub fn check(content: &str) -> std::result::Result<Self, Vec<ConfigProblem>> {
+        // Unknown keys, bad severities/categories and type errors all
+        // surface here with a span
+        let config: Self = toml::from_str(content).map_err(|e| {
+            vec![ConfigProblem {
+                line: e.span().map(|span| line_of(content, span.start)),
+                message: e.message().to_string(),
+            }]
+        })?;
+        
+        let problems = config.validate(content);
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(problems)
+        }
+    }
+    
+    fn validate(&self, content: &str) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        
+        if let Err(e) = self.llm.validate() {
+            problems.push(ConfigProblem {
+                line: key_line(content, "prompt_template").or_else(|| key_line(content, "max_suggestions")),
+                message: e.to_string(),
+            });
+        }
+        
+        let mut languages: Vec<&String> = self.banned_calls.keys().collect();
+        languages.sort();
+        for language in languages {
+            if self.banned_calls[language].iter().any(|call| call.pattern.trim().is_empty()) {
+                problems.push(ConfigProblem {
+                    line: find_line(content, |line| line.starts_with("pattern") && line.ends_with("\"\"")),
+                    message: format!("banned_calls.{} has an empty pattern, which would match every line", language),
+                });
+            }
+        }
+        
+        if self.duplicate_literals.threshold < 2 {
+            problems.push(ConfigProblem {
+                line: key_line(content, "threshold"),
+                message: "duplicate_literals.threshold must be at least 2".to_string(),
+            });
+        }
+        
+        let weights = &self.severity_weights;
+        if [weights.critical, weights.high, weights.medium, weights.low].iter().any(|weight| *weight < 0.0) {
+            problems.push(ConfigProblem {
+                line: find_line(content, |line| line == "[severity_weights]"),
+                message: "severity_weights must not be negative".to_string(),
+            });
+        }
+        
+        if self.semantic_search.chunk_lines == 0 {
+            problems.push(ConfigProblem {
+                line: key_line(content, "chunk_lines"),
+                message: "semantic_search.chunk_lines must be at least 1".to_string(),
+            });
+        }
+        
+        if self.duplicate_blocks.window_lines < 2 {
+            problems.push(ConfigProblem {
+                line: key_line(content, "window_lines"),
+                message: "duplicate_blocks.window_lines must be at least 2".to_string(),
+            });
+        }
+        
+        if self.macros.max_density <= 0.0 {
+            problems.push(ConfigProblem {
+                line: key_line(content, "max_density"),
+                message: "macros.max_density must be greater than 0".to_string(),
+            });
+        }
+        
+        for (key, limit) in [
+            ("max_macro_rules_lines", Some(self.macros.max_macro_rules_lines)),
+            ("max_file_lines", self.size_limits.max_file_lines),
+            ("max_function_lines", self.size_limits.max_function_lines),
+            ("max_line_length", self.max_line_length),
+        ] {
+            if limit == Some(0) {
+                problems.push(ConfigProblem {
+                    line: key_line(content, key),
+                    message: format!("{} must be at least 1", key),
+                });
+            }
+        }
+        
+        if self.dependency_policy.deny.iter().any(|denied| denied.name.trim().is_empty()) {
+            problems.push(ConfigProblem {
+                line: find_line(content, |line| line.starts_with("name") && line.ends_with("\"\"")),
+                message: "dependency_policy.deny has an entry with an empty name".to_string(),
+            });
+        }
+        
+        if self.profile.as_ref().is_some_and(|profile| profile.trim().is_empty()) {
+            problems.push(ConfigProblem {
+                line: key_line(content, "profile"),
+                message: "profile must not be empty".to_string(),
+            });
+        }
+        
+        let mut rule_ids: Vec<&String> = self.disabled_rules.iter().chain(self.rule_severity.keys()).collect();
+        rule_ids.sort();
+        for rule in rule_ids.into_iter().filter(|rule| !rule.contains('/')) {
+            problems.push(ConfigProblem {
+                line: find_line(content, |line| line.contains(&format!("\"{}\"", rule))),
+                message: format!("`{}` is not a rule id like `rust/unwrap`", rule),
+            });
+        }
+        
+        let mut rule_languages: Vec<&String> = self.rules.keys().collect();
+        rule_languages.sort();
+        for language in rule_languages {
+            if self.rules[language].anti_patterns.iter().any(|rule| rule.pattern.trim().is_empty()) {
+                problems.push(ConfigProblem {
+                    line: find_line(content, |line| line.starts_with("pattern") && line.ends_with("\"\"")),
+                    message: format!("rules.{} has an empty pattern, which would match every line", language),
+                });
+            }
+        }
+        
+        let mut extensions: Vec<(&String, &String)> = self.languages.iter().collect();
+        extensions.sort();
+        for (extension, language) in extensions {
+            if language.trim().is_empty() {
+                problems.push(ConfigProblem {
+                    line: key_line(content, extension),
+                    message: format!("languages.{} maps to an empty language name", extension),
+                });
+            }
+        }
+        
+        problems
+    }
+    
+    /// Loads `.devagent.toml` from the review path (or its parent for a
+    /// single file), then the working directory. Missing files mean defaults.
+    pub async fn load(review_path: &Path) -> Result<Self> {
+        let candidates = [config_dir(review_path).join(CONFIG_FILE), PathBuf::from(CONFIG_FILE)];
+        let mut config = Self::default();
+        
+        for candidate in &candidates {
+            if let Some(found) = Self::read(candidate).await? {
+                config = found;
+                break;
+            }
+        }
+        
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+    
+    /// The config file at `path`, or `None` if there isn't one. Unlike
+    /// `load`, environment overrides aren't applied.
+    pub async fn read(path: &Path) -> Result<Option<Self>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+        
+        info!("Loading review config from {}", path.display());
+        
+        let content = fs::read_to_string(path).await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::check(&content).map(Some).map_err(|problems| invalid_config(path, &problems))
+    }
+    
+    /// Applies environment overrides found through `lookup`, for runs where
+    /// mounting a file is awkward: `DEVAGENT_FAIL_ON`,
+    /// `DEVAGENT_MAX_LINE_LENGTH` and `DEVAGENT_LLM_MODEL`. Precedence,
+    /// highest first: CLI flags, these variables, `.devagent.toml`, defaults.
+    /// Empty values are treated as unset.
+    pub fn apply_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let var = |name: &str| lookup(name).filter(|value| !value.trim().is_empty());
+        
+        if let Some(value) = var("DEVAGENT_FAIL_ON") {
+            self.fail_on = Some(value.trim().parse().map_err(|e: String| anyhow::anyhow!("DEVAGENT_FAIL_ON: {}", e))?);
+        }
+        
+        if let Some(value) = var("DEVAGENT_MAX_LINE_LENGTH") {
+            let max: usize = value.trim().parse()
+                .with_context(|| format!("DEVAGENT_MAX_LINE_LENGTH: `{}` is not a line count", value))?;
+            if max == 0 {
+                anyhow::bail!("DEVAGENT_MAX_LINE_LENGTH must be at least 1");
+            }
+            self.max_line_length = Some(max);
+        }
+        
+        if let Some(model) = var("DEVAGENT_LLM_MODEL") {
+            self.llm.model = model.trim().to_string();
+        }
+        
+        Ok(())
+    }
+    
+    /// Built-in banned calls with per-language overrides applied.
+    pub fn banned_calls(&self) -> HashMap<String, Vec<BannedCall>> {
+        let mut banned = BannedCall::defaults();
+        banned.extend(self.banned_calls.clone());
+        banned
+    }
+    
+    /// Built-in naming conventions with per-language overrides applied.
+    pub fn naming_conventions(&self) -> HashMap<String, NamingConventions> {
+        let mut naming = NamingConventions::defaults();
+        naming.extend(self.naming.clone());
+        naming
+    }
+}
+
+/// Writes `STARTER_CONFIG` to `path`, refusing to replace an existing
+/// file unless `force` is set.
+pub async fn write_starter(path: &Path, force: bool) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    if force {
+        options.write(true).create(true).truncate(true);
+    } else {
+        options.write(true).create_new(true);
+    }
+    
+    let mut file = options.open(path).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => anyhow::anyhow!("{} already exists; pass --force to overwrite it", path.display()),
+        _ => anyhow::Error::new(e).context(format!("Failed to create {}", path.display())),
+    })?;
+    
+    // tokio buffers file writes until flushed
+    let written = async {
+        file.write_all(STARTER_CONFIG.as_bytes()).await?;
+        file.flush().await
+    };
+    written.await.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Directory whose `.devagent.toml` applies to a review path: the path
+/// itself, or its parent for a single file.
+pub fn config_dir(review_path: &Path) -> &Path {
+    if review_path.is_dir() {
+        review_path
+    } else {
+        review_path.parent().unwrap_or(Path::new("."))
+    }
+}
+
+pub fn invalid_config(path: &Path, problems: &[ConfigProblem]) -> anyhow::Error {
+    let details: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+    anyhow::anyhow!("Invalid config {}:\n  {}", path.display(), details.join("\n  "))
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+fn find_line(content: &str, predicate: impl Fn(&str) -> bool) -> Option<usize> {
+    content.lines().position(|line| predicate(line.trim())).map(|i| i + 1)
+}
+
+fn key_line(content: &str, key: &str) -> Option<usize> {
+    find_line(content, |line| {
+        line.strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_analyzer::CodeAnalyzer;
+    
+    #[tokio::test]
+    async fn environment_overrides_config_file_values() {
+        let mut config = ReviewConfig::check("max_line_length = 100\n[llm]\nmodel = \"from-file\"\n").unwrap();
+        let env: std::collections::HashMap<&str, &str> = [
+            ("DEVAGENT_MAX_LINE_LENGTH", "20"),
+            ("DEVAGENT_LLM_MODEL", "from-env"),
+            ("DEVAGENT_FAIL_ON", ""),
+        ].into_iter().collect();
+        
+        config.apply_env(|name| env.get(name).map(|value| value.to_string())).unwrap();
+        assert_eq!(config.max_line_length, Some(20));
+        assert_eq!(config.llm.model, "from-env");
+        assert_eq!(config.fail_on, None);
+        
+        let mut analyzer = CodeAnalyzer::new().await.unwrap();
+        analyzer.set_max_line_length(config.max_line_length.unwrap());
+        let issues = analyzer.analyze_code("x = \"a fairly long line\"\n", Path::new("a.py")).await.unwrap();
+        assert!(issues.iter().any(|i| i.message == "Line too long (over 20 characters)"));
+        
+        assert!(config.apply_env(|_| Some("severe".to_string())).is_err());
+    }
+    
+    #[tokio::test]
+    async fn starter_config_parses_to_the_defaults() {
+        use crate::report_meta::config_hash;
+        
+        let starter = ReviewConfig::check(STARTER_CONFIG).unwrap();
+        assert_eq!(config_hash(&starter), config_hash(&ReviewConfig::default()));
+        
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("devagent.toml");
+        std::fs::write(&path, "profile = \"mine\"\n").unwrap();
+        
+        let refused = write_starter(&path, false).await.unwrap_err();
+        assert!(refused.to_string().contains("--force"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "profile = \"mine\"\n");
+        
+        write_starter(&path, true).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), STARTER_CONFIG);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::{Context, Result};
+use tokio::fs;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::config::{self, ReviewConfig, CONFIG_FILE};
+
+/// Run-wide settings, which a subdirectory config may not set.
+const ROOT_ONLY_KEYS: [&str; 3] = ["profile", "fail_on", "llm"];
+
+/// `.devagent.toml` files in subdirectories of the review root, merged
+/// over the root config like `.editorconfig`: tables merge key by key and
+/// the file nearest to a reviewed file wins. Lists such as
+/// `disabled_rules` are replaced, not appended to. Run-wide settings
+/// (`profile`, `fail_on`, `[llm]`) belong in the root config only; a
+/// subdirectory config that sets them is an error.
+pub struct ConfigCascade {
+    root: PathBuf,
+    base: toml::Value,
+    /// Directory of the nearest config -> effective config below it
+    effective: Mutex<HashMap<PathBuf, Arc<ReviewConfig>>>,
+}
+
+impl ConfigCascade {
+    pub fn new(review_path: &Path, base: &ReviewConfig) -> Result<Self> {
+        Ok(Self {
+            root: config::config_dir(review_path).to_path_buf(),
+            base: toml::Value::try_from(base).context("Failed to serialize the root config")?,
+            effective: Mutex::new(HashMap::new()),
+        })
+    }
+    
+    /// The effective config for `file_path`, keyed by the directory of the
+    /// nearest config that shaped it. `None` when only the root config
+    /// applies.
+    pub async fn resolve(&self, file_path: &Path) -> Result<Option<(PathBuf, Arc<ReviewConfig>)>> {
+        let chain = file_path.parent().map(|dir| self.chain(dir)).unwrap_or_default();
+        let Some(nearest) = chain.first().cloned() else {
+            return Ok(None);
+        };
+        
+        let mut effective = self.effective.lock().await;
+        if let Some(config) = effective.get(&nearest) {
+            return Ok(Some((nearest, config.clone())));
+        }
+        
+        let config = Arc::new(self.merge(&chain).await?);
+        effective.insert(nearest.clone(), config.clone());
+        Ok(Some((nearest, config)))
+    }
+    
+    // Directories from `dir` up to, but not including, the root that have
+    // their own config, nearest first
+    fn chain(&self, dir: &Path) -> Vec<PathBuf> {
+        if dir.strip_prefix(&self.root).is_err() {
+            return Vec::new();
+        }
+        
+        dir.ancestors()
+            .take_while(|ancestor| *ancestor != self.root)
+            .filter(|ancestor| ancestor.join(CONFIG_FILE).is_file())
+            .map(Path::to_path_buf)
+            .collect()
+    }
+    
+    async fn merge(&self, chain: &[PathBuf]) -> Result<ReviewConfig> {
+        let mut merged = self.base.clone();
+        
+        for dir in chain.iter().rev() {
+            let path = dir.join(CONFIG_FILE);
+            info!("Loading directory config from {}", path.display());
+            
+            let content = fs::read_to_string(&path).await
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            // Checked on its own first, so problems come with line numbers
+            ReviewConfig::check(&content).map_err(|problems| config::invalid_config(&path, &problems))?;
+            let overlay: toml::Value = toml::from_str(&content)?;
+            if let Some(key) = ROOT_ONLY_KEYS.iter().find(|key| overlay.get(**key).is_some()) {
+                anyhow::bail!("{}: `{}` is run-wide and only read from the root {}", path.display(), key, CONFIG_FILE);
+            }
+            merge_tables(&mut merged, overlay);
+        }
+        
+        let mut config: ReviewConfig = merged.try_into()
+            .with_context(|| format!("Invalid merged config for {}", chain[0].display()))?;
+        // Environment overrides still beat every file
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+}
+
+fn merge_tables(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_tables(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_analyzer::{CodeAnalyzer, Issue, Severity};
+    
+    #[tokio::test]
+    async fn nearest_directory_config_wins() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("examples/deep")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join(".devagent.toml"), "[rule_severity]\n\"all/todo\" = \"High\"\n").unwrap();
+        std::fs::write(root.join("examples/.devagent.toml"), "disabled_rules = [\"rust/unwrap\"]\n").unwrap();
+        std::fs::write(root.join("src/.devagent.toml"), "[rule_severity]\n\"rust/unwrap\" = \"Critical\"\n").unwrap();
+        
+        let base = ReviewConfig::load(root).await.unwrap();
+        let cascade = ConfigCascade::new(root, &base).unwrap();
+        let mut root_analyzer = CodeAnalyzer::new().await.unwrap();
+        root_analyzer.apply_config(&base);
+        
+        let code = "fn main() {\n    // TODO: handle errors\n    std::env::args().next().unwrap();\n}\n";
+        let severities = |issues: Vec<Issue>| -> Vec<(String, Severity)> {
+            issues.into_iter().map(|i| (i.rule, i.severity)).filter(|(rule, _)| rule == "all/todo" || rule == "rust/unwrap").collect()
+        };
+        
+        assert!(cascade.resolve(&root.join("main.rs")).await.unwrap().is_none());
+        let issues = root_analyzer.analyze_code(code, &root.join("main.rs")).await.unwrap();
+        assert_eq!(severities(issues), vec![("all/todo".to_string(), Severity::High), ("rust/unwrap".to_string(), Severity::High)]);
+        
+        // examples/deep has no config of its own and inherits examples/
+        let file = root.join("examples/deep/demo.rs");
+        let (dir, config) = cascade.resolve(&file).await.unwrap().unwrap();
+        assert_eq!(dir, root.join("examples"));
+        let analyzer = root_analyzer.for_config(&config).await.unwrap();
+        assert_eq!(severities(analyzer.analyze_code(code, &file).await.unwrap()), vec![("all/todo".to_string(), Severity::High)]);
+        assert!(!analyzer.enabled_rules().contains(&"rust/unwrap".to_string()));
+        
+        let file = root.join("src/lib.rs");
+        let (_, config) = cascade.resolve(&file).await.unwrap().unwrap();
+        let analyzer = root_analyzer.for_config(&config).await.unwrap();
+        assert_eq!(
+            severities(analyzer.analyze_code(code, &file).await.unwrap()),
+            vec![("all/todo".to_string(), Severity::High), ("rust/unwrap".to_string(), Severity::Critical)],
+        );
+        
+        assert!(ReviewConfig::check("disabled_rules = [\"unwrap\"]\n").is_err());
+    }
+    
+    #[tokio::test]
+    async fn run_wide_settings_are_rejected_below_the_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("tools")).unwrap();
+        std::fs::write(root.join("tools/.devagent.toml"), "disabled_rules = [\"all/todo\"]\n[llm]\nmodel = \"other\"\n").unwrap();
+        
+        let cascade = ConfigCascade::new(root, &ReviewConfig::default()).unwrap();
+        let error = cascade.resolve(&root.join("tools/build.rs")).await.unwrap_err().to_string();
+        assert!(error.contains("tools/.devagent.toml") && error.contains("`llm`"), "{}", error);
+    }
+}
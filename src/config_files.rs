@@ -0,0 +1,152 @@
+use std::ops::Range;
+
+use crate::code_analyzer::{Issue, IssueCategory, Severity};
+use crate::redact;
+
+/// Languages handled here rather than by the source rule sets.
+pub const LANGUAGES: [&str; 3] = ["yaml", "json", "toml"];
+
+/// Unquoted values (in any case) YAML 1.1 parsers read as booleans but
+/// YAML 1.2 parsers read as strings, e.g. the country code `NO`
+const AMBIGUOUS_BOOLEANS: [&str; 6] = ["yes", "no", "on", "off", "y", "n"];
+
+/// Parses a YAML, JSON or TOML file, reporting the first syntax error
+/// (duplicate keys included) with its line and column, then runs the
+/// line checks: plaintext secrets and, for YAML, ambiguous booleans.
+pub fn check(language: &str, content: &str) -> Vec<Issue> {
+    let mut issues: Vec<Issue> = parse_error(language, content).into_iter().collect();
+    
+    for (i, line) in content.lines().enumerate() {
+        let line_num = i + 1;
+        if has_plaintext_secret(line) {
+            issues.push(issue(
+                "all/hardcoded-secret",
+                Severity::High,
+                "Secret in plain text; reference it from a secret store or environment variable".to_string(),
+                line_num,
+                line,
+                IssueCategory::Security,
+            ));
+        }
+        
+        if language == "yaml" {
+            if let Some(value) = ambiguous_boolean(line) {
+                issues.push(issue(
+                    "yaml/ambiguous-boolean",
+                    Severity::Low,
+                    format!("`{}` is a boolean to YAML 1.1 parsers and a string to YAML 1.2 ones; quote it or use true/false", value),
+                    line_num,
+                    line,
+                    IssueCategory::Maintainability,
+                ));
+            }
+        }
+    }
+    
+    issues
+}
+
+// Messages say where, like "... at line 3 column 5"
+fn parse_error(language: &str, content: &str) -> Option<Issue> {
+    let (rule, message, line) = match language {
+        "yaml" => {
+            // Every document in a multi-document stream
+            let error = serde_yaml::Deserializer::from_str(content)
+                .find_map(|document| <serde_yaml::Value as serde::Deserialize>::deserialize(document).err())?;
+            let line = error.location().map_or(1, |location| location.line());
+            match duplicate_key(&error.to_string()) {
+                // Located at the mapping; point at the repeat instead
+                Some(key) => ("yaml/duplicate-key", error.to_string(), repeated_key_line(content, line, key).unwrap_or(line)),
+                None => ("yaml/syntax-error", error.to_string(), line),
+            }
+        }
+        "json" => {
+            let error = serde_json::from_str::<serde_json::Value>(content).err()?;
+            let rule = if error.to_string().contains("trailing comma") { "json/trailing-comma" } else { "json/syntax-error" };
+            (rule, error.to_string(), error.line().max(1))
+        }
+        "toml" => {
+            let error = content.parse::<toml::Table>().err()?;
+            let (line, column) = error.span().map_or((1, 1), |span| line_and_column(content, span));
+            ("toml/syntax-error", format!("{} at line {} column {}", error.message().trim_end(), line, column), line)
+        }
+        _ => return None,
+    };
+    
+    let code = content.lines().nth(line - 1).unwrap_or_default();
+    Some(issue(rule, Severity::High, format!("Invalid {}: {}", language.to_uppercase(), message), line, code, IssueCategory::Maintainability))
+}
+
+// The key in serde_yaml's "duplicate entry with key \"name\"" message
+fn duplicate_key(message: &str) -> Option<&str> {
+    let rest = message.split_once("duplicate entry with key \"")?.1;
+    Some(&rest[..rest.find('"')?])
+}
+
+// The second `key:` at one indentation level, from the mapping's line on
+fn repeated_key_line(content: &str, mapping_line: usize, key: &str) -> Option<usize> {
+    let prefix = format!("{}:", key);
+    let mut first_indent = None;
+    
+    for (i, line) in content.lines().enumerate().skip(mapping_line.saturating_sub(1)) {
+        let trimmed = line.trim_start();
+        if !trimmed.starts_with(&prefix) {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        match first_indent {
+            None => first_indent = Some(indent),
+            Some(first) if first == indent => return Some(i + 1),
+            Some(_) => {}
+        }
+    }
+    None
+}
+
+fn line_and_column(content: &str, span: Range<usize>) -> (usize, usize) {
+    let before = &content[..span.start.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+    (line, column)
+}
+
+// A value the secret masker would hide that isn't a reference to a secret
+// kept elsewhere (`${{ secrets.TOKEN }}`, `${DB_PASSWORD}`, `{{ .Values.key }}`)
+fn has_plaintext_secret(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') || trimmed.starts_with("//") {
+        return false;
+    }
+    if redact::mask_secrets(line) == line {
+        return false;
+    }
+    
+    let value = line.split_once([':', '=']).map_or(line, |(_, value)| value);
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'' || c == ',');
+    !(value.is_empty() || value.starts_with('$') || value.starts_with("{{") || value.starts_with('<'))
+}
+
+fn ambiguous_boolean(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') {
+        return None;
+    }
+    
+    let value = match trimmed.split_once(": ") {
+        Some((_, value)) => value,
+        None => trimmed.strip_prefix("- ")?,
+    };
+    let value = value.split(" #").next().unwrap_or(value).trim();
+    AMBIGUOUS_BOOLEANS.contains(&value.to_ascii_lowercase().as_str()).then_some(value)
+}
+
+fn issue(rule: &str, severity: Severity, message: String, line: usize, code: &str, category: IssueCategory) -> Issue {
+    Issue {
+        rule: rule.to_string(),
+        severity,
+        message,
+        line: Some(line),
+        code: Some(code.to_string()),
+        category,
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use walkdir::WalkDir;
+
+use crate::code_analyzer::{Impact, Suggestion, SuggestionCategory};
+use crate::rust_attrs::is_test;
+
+/// Heuristic test-gap finder for Rust (`--coverage-hints`). A public
+/// function counts as tested if its name appears inside a `#[test]` fn or
+/// `#[cfg(test)]` module of the same file, or anywhere in the crate's
+/// `tests/` directory. This is name matching, not real coverage.
+#[derive(Default)]
+pub struct CoverageHints {
+    // Concatenated `tests/` sources per crate root
+    integration_tests: Mutex<HashMap<PathBuf, Arc<String>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UntestedFunction {
+    pub name: String,
+    pub line: usize,
+}
+
+impl CoverageHints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn suggestions(&self, file_path: &Path, content: &str) -> Vec<Suggestion> {
+        if file_path.extension().is_none_or(|ext| ext != "rs") || is_test_file(file_path) {
+            return Vec::new();
+        }
+        
+        let integration_tests = self.integration_tests_for(file_path);
+        let lines: Vec<&str> = content.lines().collect();
+        
+        untested_public_functions(content, &integration_tests)
+            .into_iter()
+            .map(|function| Suggestion {
+                title: "Add a test".to_string(),
+                description: format!(
+                    "Public function `{}` (line {}) is not referenced by any test in this file or the crate's tests/ directory",
+                    function.name, function.line
+                ),
+                line: Some(function.line),
+                code: lines.get(function.line - 1).map(|line| line.to_string()),
+                impact: Impact::Medium,
+                category: SuggestionCategory::Testing,
+            })
+            .collect()
+    }
+    
+    fn integration_tests_for(&self, file_path: &Path) -> Arc<String> {
+        let Some(crate_root) = crate_root(file_path) else {
+            return Arc::default();
+        };
+        
+        let mut cache = self.integration_tests.lock().unwrap_or_else(|e| e.into_inner());
+        cache.entry(crate_root.clone())
+            .or_insert_with(|| {
+                let sources: Vec<String> = WalkDir::new(crate_root.join("tests"))
+                    .sort_by_file_name()
+                    .into_iter()
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rs"))
+                    .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+                    .collect();
+                Arc::new(sources.join("\n"))
+            })
+            .clone()
+    }
+}
+
+/// `pub fn`s outside test code whose names don't appear in this file's
+/// test code or in `integration_tests`.
+pub fn untested_public_functions(content: &str, integration_tests: &str) -> Vec<UntestedFunction> {
+    let Ok(ast) = syn::parse_file(content) else {
+        return Vec::new();
+    };
+    
+    let lines: Vec<&str> = content.lines().collect();
+    let mut collector = Collector::default();
+    collector.items(&ast.items, false);
+    
+    let test_code: String = collector.test_ranges
+        .iter()
+        .flat_map(|(start, end)| lines.get(start - 1..(*end).min(lines.len())).unwrap_or_default())
+        .copied()
+        .collect::<Vec<&str>>()
+        .join("\n");
+    
+    collector.public_fns
+        .into_iter()
+        .filter(|function| function.name != "main")
+        .filter(|function| !mentions(&test_code, &function.name) && !mentions(integration_tests, &function.name))
+        .collect()
+}
+
+#[derive(Default)]
+struct Collector {
+    public_fns: Vec<UntestedFunction>,
+    // Inclusive 1-based line ranges of test fns and test modules
+    test_ranges: Vec<(usize, usize)>,
+}
+
+impl Collector {
+    fn items(&mut self, items: &[syn::Item], in_test: bool) {
+        for item in items {
+            match item {
+                syn::Item::Fn(function) => {
+                    if is_test(&function.attrs) {
+                        self.test_ranges.push(block_lines(&function.sig.ident, &function.block));
+                    } else if !in_test && is_public(&function.vis) {
+                        self.push(&function.sig.ident);
+                    }
+                }
+                syn::Item::Impl(block) if !in_test && block.trait_.is_none() => {
+                    for impl_item in &block.items {
+                        if let syn::ImplItem::Fn(method) = impl_item {
+                            if is_public(&method.vis) && !is_test(&method.attrs) {
+                                self.push(&method.sig.ident);
+                            }
+                        }
+                    }
+                }
+                syn::Item::Mod(module) => {
+                    let Some((brace, items)) = &module.content else { continue };
+                    
+                    if is_test(&module.attrs) {
+                        self.test_ranges.push((
+                            module.ident.span().start().line,
+                            brace.span.close().end().line,
+                        ));
+                        self.items(items, true);
+                    } else {
+                        self.items(items, in_test);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    fn push(&mut self, ident: &syn::Ident) {
+        self.public_fns.push(UntestedFunction {
+            name: ident.to_string(),
+            line: ident.span().start().line,
+        });
+    }
+}
+
+fn block_lines(ident: &syn::Ident, block: &syn::Block) -> (usize, usize) {
+    (ident.span().start().line, block.brace_token.span.close().end().line)
+}
+
+fn is_public(vis: &syn::Visibility) -> bool {
+    matches!(vis, syn::Visibility::Public(_))
+}
+
+// Whole-word match, so `load` isn't satisfied by `load_all`.
+fn mentions(haystack: &str, name: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    
+    haystack.match_indices(name).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + name.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+fn is_test_file(path: &Path) -> bool {
+    let in_test_dir = path.components().any(|component| {
+        matches!(component.as_os_str().to_str(), Some("tests" | "benches"))
+    });
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    
+    in_test_dir || stem == "tests" || stem.ends_with("_test") || stem.ends_with("_tests")
+}
+
+fn crate_root(file_path: &Path) -> Option<PathBuf> {
+    let absolute = std::fs::canonicalize(file_path).ok()?;
+    absolute.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn coverage_hints_flag_public_fns_without_tests() {
+        let content = r#"
+pub fn parse(input: &str) -> u32 { input.len() as u32 }
+pub fn render() {}
+pub fn load_all() {}
+fn private_helper() {}
+
+#[cfg(test)]
+mod tests {
+    pub fn fixture() {}
+    
+    #[test]
+    fn parses() { assert_eq!(super::parse("ab"), 2); }
+}
+"#;
+        
+        let untested: Vec<String> = untested_public_functions(content, "fn it() { load(); }")
+            .into_iter()
+            .map(|function| function.name)
+            .collect();
+        assert_eq!(untested, vec!["render", "load_all"]);
+        
+        let untested = untested_public_functions(content, "fn it() { render(); load_all(); }");
+        assert!(untested.is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{Context, Result};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use notify::{Event, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tracing::{info, warn, error};
+
+use crate::DevAgent;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    pub port: u16,
+    pub review_interval: Duration,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub started_at: DateTime<Utc>,
+    pub last_review: Option<DateTime<Utc>>,
+    pub files_reviewed: usize,
+    pub dirty_files: usize,
+    pub last_error: Option<String>,
+}
+
+struct DaemonState {
+    agent: DevAgent,
+    dirty: Mutex<HashSet<PathBuf>>,
+    status: RwLock<DaemonStatus>,
+}
+
+pub async fn run(agent: DevAgent, config: DaemonConfig) -> Result<()> {
+    info!("Starting DevAgent daemon for {}", agent.args.path.display());
+    
+    let root = agent.args.path.clone();
+    
+    // Everything is dirty on startup so the index is rebuilt once
+    let initial: HashSet<PathBuf> = agent.code_files()?.into_iter().collect();
+    
+    let state = Arc::new(DaemonState {
+        status: RwLock::new(DaemonStatus {
+            started_at: Utc::now(),
+            last_review: None,
+            files_reviewed: 0,
+            dirty_files: initial.len(),
+            last_error: None,
+        }),
+        dirty: Mutex::new(initial),
+        agent,
+    });
+    
+    // The watcher callback runs on notify's own thread
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let _ = event_tx.send(event);
+    })
+    .context("Failed to create file watcher")?;
+    watcher.watch(&root, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", root.display()))?;
+    
+    tokio::spawn(collect_changes(state.clone(), event_rx));
+    tokio::spawn(review_worker(state.clone(), config.review_interval));
+    
+    let app = Router::new()
+        .route("/", get(health_check))
+        .route("/status", get(status))
+        .route("/metrics", get(metrics))
+        .with_state(state);
+    
+    let addr = format!("0.0.0.0:{}", config.port);
+    info!("Daemon API listening on {}", addr);
+    
+    let listener = tokio::net::TcpListener::bind(&addr).await
+        .with_context(|| format!("Failed to bind {}", addr))?;
+    axum::serve(listener, app).await?;
+    
+    // Keep the watcher alive for the lifetime of the server
+    drop(watcher);
+    Ok(())
+}
+
+async fn collect_changes(
+    state: Arc<DaemonState>,
+    mut events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+) {
+    while let Some(event) = events.recv().await {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("File watcher error: {}", e);
+                continue;
+            }
+        };
+        
+        let changed: Vec<PathBuf> = event.paths
+            .into_iter()
+            .filter(|path| state.agent.is_code_file(path))
+            .collect();
+        
+        if changed.is_empty() {
+            continue;
+        }
+        
+        let dirty_files = {
+            let mut dirty = state.dirty.lock().await;
+            dirty.extend(changed);
+            dirty.len()
+        };
+        state.status.write().await.dirty_files = dirty_files;
+    }
+}
+
+async fn review_worker(state: Arc<DaemonState>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    
+    loop {
+        ticker.tick().await;
+        
+        let batch: Vec<PathBuf> = state.dirty.lock().await.drain().collect();
+        if batch.is_empty() {
+            continue;
+        }
+        
+        info!("Re-indexing {} changed files", batch.len());
+        
+        for path in batch {
+            // Deleted files show up as change events too
+            if !path.exists() {
+                continue;
+            }
+            
+            match state.agent.review_file(&path).await {
+                Ok(_) => {
+                    let mut status = state.status.write().await;
+                    status.files_reviewed += 1;
+                    status.last_review = Some(Utc::now());
+                }
+                Err(e) => {
+                    error!("Failed to review {}: {}", path.display(), e);
+                    state.status.write().await.last_error = Some(format!("{}: {}", path.display(), e));
+                }
+            }
+        }
+        
+        let dirty_files = state.dirty.lock().await.len();
+        state.status.write().await.dirty_files = dirty_files;
+    }
+}
+
+async fn health_check() -> StatusCode {
+    StatusCode::OK
+}
+
+async fn status(State(state): State<Arc<DaemonState>>) -> Json<DaemonStatus> {
+    Json(state.status.read().await.clone())
+}
+
+async fn metrics(State(state): State<Arc<DaemonState>>) -> impl IntoResponse {
+    state.agent.refresh_queue_metrics().await;
+    let dirty_files = state.dirty.lock().await.len();
+    state.agent.metrics.set_queue_depth("daemon_dirty_files", dirty_files);
+    
+    match state.agent.metrics.render() {
+        Ok(body) => (StatusCode::OK, [(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response(),
+        Err(e) => {
+            error!("Failed to render metrics: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::EventKind;
+    
+    use crate::tests::mock_agent;
+    use crate::Args;
+    
+    async fn wait_for(state: &DaemonState, done: impl Fn(&DaemonStatus) -> bool) {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !done(&*state.status.read().await) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("daemon status never reached the expected state");
+    }
+    
+    #[tokio::test]
+    async fn changed_code_files_are_reviewed_on_the_next_tick() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "pub fn f() {}\n").unwrap();
+        
+        let agent = mock_agent(Args::for_review(dir.path().to_path_buf(), None, false), scratch.path()).await;
+        let state = Arc::new(DaemonState {
+            status: RwLock::new(DaemonStatus {
+                started_at: Utc::now(),
+                last_review: None,
+                files_reviewed: 0,
+                dirty_files: 0,
+                last_error: None,
+            }),
+            dirty: Mutex::new(HashSet::new()),
+            agent,
+        });
+        
+        // What the watcher sends: an edit alongside a non-code file, and a
+        // code file deleted before the review runs
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        tokio::spawn(collect_changes(state.clone(), event_rx));
+        event_tx.send(Ok(Event::new(EventKind::Any).add_path(file).add_path(dir.path().join("notes.txt")))).unwrap();
+        event_tx.send(Ok(Event::new(EventKind::Any).add_path(dir.path().join("gone.rs")))).unwrap();
+        wait_for(&state, |status| status.dirty_files == 2).await;
+        
+        tokio::spawn(review_worker(state.clone(), Duration::from_millis(10)));
+        wait_for(&state, |status| status.dirty_files == 0).await;
+        
+        let status = state.status.read().await;
+        assert_eq!(status.files_reviewed, 1);
+        assert!(status.last_review.is_some());
+        assert!(status.last_error.is_none(), "{:?}", status.last_error);
+    }
+}
@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+
+use crate::code_analyzer::{Issue, IssueCategory, Severity};
+
+/// Supply-chain policy for `Cargo.toml` dependencies, from the
+/// `[dependency_policy]` table of `.devagent.toml`:
+///
+/// ```toml
+/// [dependency_policy]
+/// allow = []                      # when non-empty, only these crates
+/// lean = ["tokio", "reqwest"]     # must set default-features = false
+///
+/// [[dependency_policy.deny]]
+/// name = "openssl"
+/// reason = "use rustls"
+///
+/// [[dependency_policy.deny]]
+/// name = "git2"
+/// reason = "use the crates.io release, not a fork"
+/// git_only = true
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DependencyPolicy {
+    pub deny: Vec<DeniedCrate>,
+    pub allow: Vec<String>,
+    pub lean: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeniedCrate {
+    pub name: String,
+    pub reason: String,
+    /// Only deny the crate when it comes from a git source
+    #[serde(default)]
+    pub git_only: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Dependency {
+    /// Crate name, after resolving `package = "..."` renames
+    name: String,
+    /// Key as written in the manifest
+    key: String,
+    table: String,
+    git: bool,
+    default_features: bool,
+}
+
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+impl DependencyPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.deny.is_empty() && self.allow.is_empty() && self.lean.is_empty()
+    }
+    
+    /// Issues for a `Cargo.toml`. Manifests that don't parse are left to
+    /// cargo to report.
+    pub fn check(&self, manifest: &str) -> Vec<Issue> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        
+        let Ok(value) = toml::from_str::<toml::Value>(manifest) else {
+            return Vec::new();
+        };
+        
+        let lines: Vec<&str> = manifest.lines().collect();
+        let mut issues = Vec::new();
+        
+        for dependency in dependencies(&value) {
+            let line = dependency_line(&lines, &dependency.table, &dependency.key);
+            let issue = |rule: &str, severity: Severity, category: IssueCategory, message: String| Issue {
+                rule: format!("cargo/{}", rule),
+                severity,
+                message,
+                line,
+                code: line.and_then(|line| lines.get(line - 1)).map(|line| line.to_string()),
+                category,
+            };
+            
+            let denied = self.deny.iter()
+                .find(|denied| denied.name == dependency.name && (!denied.git_only || dependency.git));
+            
+            if let Some(denied) = denied {
+                let source = if dependency.git { " from git" } else { "" };
+                issues.push(issue(
+                    "denied-dependency",
+                    Severity::High,
+                    IssueCategory::Security,
+                    format!("Dependency `{}`{} is denied: {}", dependency.name, source, denied.reason),
+                ));
+            } else if !self.allow.is_empty() && !self.allow.contains(&dependency.name) {
+                issues.push(issue(
+                    "unlisted-dependency",
+                    Severity::Medium,
+                    IssueCategory::Security,
+                    format!("Dependency `{}` in [{}] is not on the allowlist", dependency.name, dependency.table),
+                ));
+            }
+            
+            if dependency.default_features && self.lean.contains(&dependency.name) {
+                issues.push(issue(
+                    "default-features",
+                    Severity::Low,
+                    IssueCategory::Maintainability,
+                    format!(
+                        "Dependency `{}` enables its default features; set `default-features = false` and opt into what you use",
+                        dependency.name
+                    ),
+                ));
+            }
+        }
+        
+        issues.sort_by_key(|issue| issue.line);
+        issues
+    }
+}
+
+// Dependencies from the regular, dev, build, workspace and per-target tables.
+fn dependencies(manifest: &toml::Value) -> Vec<Dependency> {
+    let mut tables: Vec<(String, &toml::Value)> = Vec::new();
+    
+    for table in DEPENDENCY_TABLES {
+        if let Some(deps) = manifest.get(table) {
+            tables.push((table.to_string(), deps));
+        }
+    }
+    if let Some(deps) = manifest.get("workspace").and_then(|workspace| workspace.get("dependencies")) {
+        tables.push(("workspace.dependencies".to_string(), deps));
+    }
+    if let Some(targets) = manifest.get("target").and_then(|targets| targets.as_table()) {
+        for (target, config) in targets {
+            for table in DEPENDENCY_TABLES {
+                if let Some(deps) = config.get(table) {
+                    tables.push((format!("target.{}.{}", target, table), deps));
+                }
+            }
+        }
+    }
+    
+    let mut dependencies = Vec::new();
+    for (table, deps) in tables {
+        let Some(deps) = deps.as_table() else { continue };
+        
+        for (key, spec) in deps {
+            let field = |name: &str| spec.get(name);
+            
+            dependencies.push(Dependency {
+                name: field("package").and_then(|name| name.as_str()).unwrap_or(key).to_string(),
+                key: key.clone(),
+                table: table.clone(),
+                git: field("git").is_some(),
+                default_features: field("default-features")
+                    .or_else(|| field("default_features"))
+                    .and_then(|enabled| enabled.as_bool())
+                    .unwrap_or(true),
+            });
+        }
+    }
+    
+    dependencies
+}
+
+// Line of `key = ...` inside `[table]`, or of a `[table.key]` header.
+fn dependency_line(lines: &[&str], table: &str, key: &str) -> Option<usize> {
+    // Target keys may be quoted: [target.'cfg(unix)'.dependencies]
+    let normalize = |header: &str| header.replace(['\'', '"', ' '], "");
+    let table = normalize(table);
+    let own_header = format!("{}.{}", table, key);
+    let mut current = String::new();
+    
+    for (index, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        
+        if let Some(header) = line.strip_prefix('[').and_then(|rest| rest.split(']').next()) {
+            current = normalize(header);
+            if current == own_header {
+                return Some(index + 1);
+            }
+            continue;
+        }
+        
+        let assigned = line.strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with('='));
+        if assigned && current == table {
+            return Some(index + 1);
+        }
+    }
+    
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::code_analyzer::CodeAnalyzer;
+    use crate::config::ReviewConfig;
+    
+    #[tokio::test]
+    async fn dependency_policy_flags_denied_unlisted_and_heavy_crates() {
+        let mut analyzer = CodeAnalyzer::new().await.unwrap();
+        let config = ReviewConfig::check(r#"
+[dependency_policy]
+allow = ["serde", "tokio", "git2"]
+lean = ["tokio"]
+
+[[dependency_policy.deny]]
+name = "openssl"
+reason = "use rustls"
+
+[[dependency_policy.deny]]
+name = "git2"
+reason = "no forks"
+git_only = true
+"#).unwrap();
+        analyzer.set_dependency_policy(config.dependency_policy);
+        
+        let manifest = r#"[package]
+name = "demo"
+
+[dependencies]
+serde = { version = "1", default-features = false }
+tokio = "1"
+tls = { package = "openssl", version = "0.10" }
+git2 = "0.18"
+rand = "0.8"
+
+[dev-dependencies.git2]
+git = "https://example.com/fork/git2-rs"
+"#;
+        
+        let issues = analyzer.analyze_code(manifest, Path::new("Cargo.toml")).await.unwrap();
+        let found: Vec<(Option<usize>, &str)> = issues.iter().map(|i| (i.line, i.rule.as_str())).collect();
+        
+        assert_eq!(found, vec![
+            (Some(6), "cargo/default-features"),
+            (Some(7), "cargo/denied-dependency"),
+            (Some(9), "cargo/unlisted-dependency"),
+            (Some(11), "cargo/denied-dependency"),
+        ]);
+    }
+}
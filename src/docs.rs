@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use crate::code_analyzer::Severity;
+use crate::rust_attrs::is_test;
+
+/// Settings for the missing documentation check on public items.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MissingDocsConfig {
+    pub enabled: bool,
+    pub severity: Severity,
+}
+
+impl Default for MissingDocsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: Severity::Low,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Undocumented {
+    pub kind: &'static str,
+    pub name: String,
+    pub line: usize,
+}
+
+/// `pub` functions, methods, structs, enums and traits without a `///`
+/// (or `#[doc]`) comment. Test modules are skipped.
+pub fn undocumented_rust(ast: &syn::File) -> Vec<Undocumented> {
+    let mut found = Vec::new();
+    rust_items(&ast.items, &mut found);
+    found.sort_by_key(|item| item.line);
+    found
+}
+
+fn rust_items(items: &[syn::Item], found: &mut Vec<Undocumented>) {
+    for item in items {
+        match item {
+            syn::Item::Fn(item) => check(found, "function", &item.vis, &item.attrs, &item.sig.ident),
+            syn::Item::Struct(item) => check(found, "struct", &item.vis, &item.attrs, &item.ident),
+            syn::Item::Enum(item) => check(found, "enum", &item.vis, &item.attrs, &item.ident),
+            syn::Item::Trait(item) => check(found, "trait", &item.vis, &item.attrs, &item.ident),
+            // Trait impls are documented on the trait
+            syn::Item::Impl(block) if block.trait_.is_none() => {
+                for impl_item in &block.items {
+                    if let syn::ImplItem::Fn(method) = impl_item {
+                        check(found, "method", &method.vis, &method.attrs, &method.sig.ident);
+                    }
+                }
+            }
+            syn::Item::Mod(module) if !is_test(&module.attrs) => {
+                if let Some((_, items)) = &module.content {
+                    rust_items(items, found);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check(found: &mut Vec<Undocumented>, kind: &'static str, vis: &syn::Visibility, attrs: &[syn::Attribute], ident: &syn::Ident) {
+    if matches!(vis, syn::Visibility::Public(_)) && !has_docs(attrs) {
+        found.push(Undocumented {
+            kind,
+            name: ident.to_string(),
+            line: ident.span().start().line,
+        });
+    }
+}
+
+fn has_docs(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("doc"))
+}
+
+/// Public (no leading underscore) module-level functions, classes and
+/// methods without a docstring. Functions nested in functions are skipped.
+pub fn undocumented_python(content: &str) -> Vec<Undocumented> {
+    let lines: Vec<&str> = content.lines().collect();
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    // (indent, is_class) of enclosing blocks
+    let mut scopes: Vec<(usize, bool)> = Vec::new();
+    let mut found = Vec::new();
+    
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        
+        let level = indent(line);
+        while scopes.last().is_some_and(|(scope_indent, _)| *scope_indent >= level) {
+            scopes.pop();
+        }
+        
+        let (kind, rest) = if let Some(rest) = trimmed.strip_prefix("class ") {
+            ("class", rest)
+        } else if let Some(rest) = trimmed.strip_prefix("def ").or_else(|| trimmed.strip_prefix("async def ")) {
+            ("function", rest)
+        } else {
+            continue;
+        };
+        
+        let inside_function = scopes.iter().any(|(_, is_class)| !is_class);
+        scopes.push((level, kind == "class"));
+        
+        let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        if name.is_empty() || name.starts_with('_') || inside_function {
+            continue;
+        }
+        
+        if !has_docstring(&lines[i..]) {
+            let kind = if kind == "function" && scopes.len() > 1 { "method" } else { kind };
+            found.push(Undocumented { kind, name, line: i + 1 });
+        }
+    }
+    
+    found
+}
+
+// The header may span lines (long signatures); the docstring is the first
+// statement after the line that ends it with `:`.
+fn has_docstring(from_header: &[&str]) -> bool {
+    let Some(header_end) = from_header.iter().position(|line| line.split('#').next().unwrap_or("").trim_end().ends_with(':')) else {
+        return false;
+    };
+    
+    from_header[header_end + 1..]
+        .iter()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|first| {
+            let first = first.trim_start_matches(['r', 'R', 'u', 'U', 'b', 'B']);
+            first.starts_with('"') || first.starts_with('\'')
+        })
+}
@@ -0,0 +1,82 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize};
+
+/// Settings for the copy-pasted block check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DuplicateBlockConfig {
+    /// Report runs of at least this many non-blank lines that appear
+    /// again later in the file; lower finds more, smaller duplicates
+    pub window_lines: usize,
+}
+
+impl Default for DuplicateBlockConfig {
+    fn default() -> Self {
+        Self { window_lines: 6 }
+    }
+}
+
+/// A block of lines and the later places it was pasted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateBlock {
+    /// First and last line of the original, 1-based and inclusive
+    pub first: (usize, usize),
+    pub copies: Vec<(usize, usize)>,
+}
+
+impl DuplicateBlockConfig {
+    /// Blocks of `window_lines` or more non-blank lines that repeat, compared
+    /// with surrounding whitespace trimmed so re-indented copies still match.
+    /// Overlapping windows of one copy are merged into a single block.
+    pub fn find(&self, content: &str) -> Vec<DuplicateBlock> {
+        let window = self.window_lines.max(2);
+        let lines: Vec<(usize, &str)> = content.lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+        if lines.len() < window * 2 {
+            return Vec::new();
+        }
+        
+        // For each window, the earliest window with the same lines that
+        // ends before it starts
+        let mut first_seen: HashMap<Vec<&str>, Vec<usize>> = HashMap::new();
+        let mut earlier: Vec<Option<usize>> = Vec::with_capacity(lines.len() - window + 1);
+        for start in 0..=lines.len() - window {
+            let key: Vec<&str> = lines[start..start + window].iter().map(|(_, line)| *line).collect();
+            // Runs of braces and `else` alone aren't worth extracting
+            if key.iter().filter(|line| line.chars().any(char::is_alphanumeric)).count() * 2 < window {
+                earlier.push(None);
+                continue;
+            }
+            
+            let starts = first_seen.entry(key).or_default();
+            earlier.push(starts.iter().copied().find(|&seen| seen + window <= start));
+            starts.push(start);
+        }
+        
+        // Consecutive windows matching consecutive earlier ones are one block
+        let mut blocks: BTreeMap<(usize, usize), Vec<(usize, usize)>> = BTreeMap::new();
+        let mut start = 0;
+        while start < earlier.len() {
+            let Some(original) = earlier[start] else {
+                start += 1;
+                continue;
+            };
+            
+            let mut end = start;
+            while end + 1 < earlier.len() && earlier[end + 1] == Some(original + end + 1 - start) && original + end + 1 - start + window <= start {
+                end += 1;
+            }
+            
+            let span = |from: usize| (lines[from].0, lines[from + end - start + window - 1].0);
+            blocks.entry(span(original)).or_default().push(span(start));
+            start = end + 1;
+        }
+        
+        blocks.into_iter()
+            .map(|(first, copies)| DuplicateBlock { first, copies })
+            .collect()
+    }
+}
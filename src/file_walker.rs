@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use tracing::debug;
+
+/// Which files under the review path are considered.
+#[derive(Debug, Clone)]
+pub struct WalkOptions {
+    /// Skip what `.gitignore`, `.ignore` and git's exclude files ignore
+    pub respect_ignore: bool,
+    /// Globs relative to the walk root, matched like `.gitignore` lines;
+    /// applied even when `respect_ignore` is off
+    pub exclude: Vec<String>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self { respect_ignore: true, exclude: Vec::new() }
+    }
+}
+
+/// Every file under `root`, sorted, leaving out `.git` and whatever
+/// `options` excludes. Hidden files are still walked. Entries that can't
+/// be read are logged and skipped.
+pub fn walk(root: &Path, options: &WalkOptions) -> Result<Vec<PathBuf>> {
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.exclude {
+        overrides.add(&format!("!{}", glob))
+            .with_context(|| format!("Invalid --exclude glob `{}`", glob))?;
+    }
+    
+    let walker = WalkBuilder::new(root)
+        .standard_filters(options.respect_ignore)
+        .hidden(false)
+        // .gitignore applies even outside a git checkout
+        .require_git(false)
+        .overrides(overrides.build()?)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+    
+    let mut files = Vec::new();
+    for entry in walker {
+        match entry {
+            Ok(entry) if entry.file_type().is_some_and(|file_type| file_type.is_file()) => files.push(entry.into_path()),
+            Ok(_) => {}
+            Err(e) => debug!("Skipping unreadable entry: {}", e),
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn ignored_and_excluded_files_are_not_walked() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        for file in ["src/main.rs", "src/generated/api.rs", "target/debug/build.rs", "node_modules/pkg/index.js", ".git/config", ".github/ci.yml"] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, "").unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "target/\nnode_modules/\n").unwrap();
+        
+        let relative = |options: &WalkOptions| -> Vec<String> {
+            walk(root, options).unwrap().iter()
+                .map(|path| path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect()
+        };
+        
+        let options = WalkOptions { exclude: vec!["generated/".to_string()], ..WalkOptions::default() };
+        assert_eq!(relative(&options), [".github/ci.yml", ".gitignore", "src/main.rs"]);
+        
+        // --no-ignore brings back the ignored directories, not .git
+        let options = WalkOptions { respect_ignore: false, ..options };
+        assert_eq!(
+            relative(&options),
+            [".github/ci.yml", ".gitignore", "node_modules/pkg/index.js", "src/main.rs", "target/debug/build.rs"]
+        );
+        
+        assert!(walk(root, &WalkOptions { exclude: vec!["[".to_string()], ..WalkOptions::default() }).is_err());
+    }
+}
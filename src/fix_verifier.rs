@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::path::Path;
+use anyhow::{Context, Result};
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::code_analyzer::{CodeAnalyzer, Issue, Severity};
+
+/// What happened to a fix after it was written and re-analyzed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FixOutcome {
+    Applied,
+    RolledBack { reason: String },
+}
+
+/// Writes `fixed_content` over `file_path`, re-runs the analyzer and keeps
+/// the change only if the targeted issue is gone and nothing more severe
+/// appeared. Otherwise the original contents are restored.
+pub async fn apply_verified(
+    analyzer: &CodeAnalyzer,
+    file_path: &Path,
+    target: &Issue,
+    fixed_content: &str,
+) -> Result<FixOutcome> {
+    let original = fs::read_to_string(file_path).await
+        .with_context(|| format!("Failed to read {}", file_path.display()))?;
+    
+    let before = analyzer.analyze_code(&original, file_path).await?;
+    
+    fs::write(file_path, fixed_content).await
+        .with_context(|| format!("Failed to write {}", file_path.display()))?;
+    
+    let verdict = match analyzer.analyze_code(fixed_content, file_path).await {
+        Ok(after) => check_regression(target, &before, &after),
+        Err(e) => Some(format!("re-analysis failed: {}", e)),
+    };
+    
+    match verdict {
+        None => {
+            info!("Applied fix for '{}' in {}", target.rule, file_path.display());
+            Ok(FixOutcome::Applied)
+        }
+        Some(reason) => {
+            warn!("Rolling back fix in {}: {}", file_path.display(), reason);
+            fs::write(file_path, &original).await
+                .with_context(|| format!("Failed to restore {}", file_path.display()))?;
+            Ok(FixOutcome::RolledBack { reason })
+        }
+    }
+}
+
+// Issues are compared by (rule, severity) counts rather than line, since a
+// fix can shift every line below it, or message, which can name counts and
+// identifiers that a fix changes.
+fn check_regression(target: &Issue, before: &[Issue], after: &[Issue]) -> Option<String> {
+    let count = |issues: &[Issue]| issues.iter().filter(|i| i.rule == target.rule).count();
+    
+    if count(after) >= count(before) {
+        return Some(format!("targeted issue '{}' is still present", target.rule));
+    }
+    
+    let before_counts = counts_above(before, &target.severity);
+    let mut introduced: Vec<String> = counts_above(after, &target.severity)
+        .into_iter()
+        .filter(|(key, count)| *count > before_counts.get(key).copied().unwrap_or(0))
+        .map(|((rule, severity), _)| format!("{:?}: {}", severity, rule))
+        .collect();
+    
+    if introduced.is_empty() {
+        return None;
+    }
+    
+    introduced.sort();
+    Some(format!("introduced higher-severity issues: {}", introduced.join("; ")))
+}
+
+fn counts_above<'a>(issues: &'a [Issue], floor: &Severity) -> HashMap<(&'a str, Severity), usize> {
+    let mut counts = HashMap::new();
+    for issue in issues.iter().filter(|i| i.severity > *floor) {
+        *counts.entry((issue.rule.as_str(), issue.severity.clone())).or_default() += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_analyzer::IssueCategory;
+    
+    fn issue(rule: &str, severity: Severity, message: &str) -> Issue {
+        Issue {
+            rule: rule.to_string(),
+            severity,
+            message: message.to_string(),
+            line: Some(1),
+            code: None,
+            category: IssueCategory::Maintainability,
+        }
+    }
+    
+    #[test]
+    fn regressions_are_judged_by_rule_and_severity() {
+        let target = issue("rust/unwrap", Severity::Medium, "Avoid unwrap() on `config`");
+        let before = [issue("rust/unwrap", Severity::Medium, "Avoid unwrap() on `config`"), issue("all/todo", Severity::Low, "TODO found")];
+        
+        // The message names what was unwrapped; the rule is what counts
+        let removed = [issue("all/todo", Severity::Low, "TODO found")];
+        assert_eq!(check_regression(&target, &before, &removed), None);
+        
+        let still_there = [issue("rust/unwrap", Severity::Medium, "Avoid unwrap() on `settings`")];
+        assert_eq!(check_regression(&target, &before, &still_there), Some("targeted issue 'rust/unwrap' is still present".to_string()));
+        
+        let worse = [issue("rust/panic", Severity::High, "panic! in library code"), issue("all/todo", Severity::Low, "TODO found (2)")];
+        assert_eq!(check_regression(&target, &before, &worse), Some("introduced higher-severity issues: High: rust/panic".to_string()));
+    }
+    
+    #[tokio::test]
+    async fn rolled_back_fixes_restore_the_original_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        let original = "fn main() {\r\n    let port = std::env::var(\"PORT\").unwrap();\r\n}\r\n";
+        std::fs::write(&path, original).unwrap();
+        
+        let analyzer = CodeAnalyzer::new().await.unwrap();
+        let target = analyzer.analyze_code(original, &path).await.unwrap()
+            .into_iter()
+            .find(|issue| issue.rule == "rust/unwrap")
+            .unwrap();
+        
+        let unfixed = "fn main() {\n    let port = std::env::var(\"PORT\").unwrap(); // fixed\n}\n";
+        let outcome = apply_verified(&analyzer, &path, &target, unfixed).await.unwrap();
+        assert!(matches!(outcome, FixOutcome::RolledBack { .. }), "{:?}", outcome);
+        assert_eq!(std::fs::read(&path).unwrap(), original.as_bytes());
+        
+        let fixed = "fn main() {\n    let port = std::env::var(\"PORT\").unwrap_or_default();\n}\n";
+        assert_eq!(apply_verified(&analyzer, &path, &target, fixed).await.unwrap(), FixOutcome::Applied);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), fixed);
+    }
+}
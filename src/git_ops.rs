@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use tokio::process::Command;
+use tracing::{info, warn};
+
+/// Inclusive range of line numbers on the new side of a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl LineRange {
+    pub fn contains(&self, line: usize) -> bool {
+        line >= self.start && line <= self.end
+    }
+}
+
+/// A git command run in `dir` rather than the process working directory,
+/// which may be a different repo from the one under review.
+pub fn git(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(dir);
+    command
+}
+
+pub async fn repo_root(path: &Path) -> Result<PathBuf> {
+    let dir = if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(Path::new("."))
+    };
+    
+    let output = git(dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .await
+        .context("Failed to run git rev-parse")?;
+    
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "{} is not inside a git repository: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    
+    Ok(PathBuf::from(String::from_utf8(output.stdout)?.trim()))
+}
+
+/// Returns the added/modified line ranges per file (relative to the repo
+/// root) between `base` and the working tree, restricted to `path`.
+pub async fn changed_lines(path: &Path, base: &str) -> Result<HashMap<PathBuf, Vec<LineRange>>> {
+    let root = repo_root(path).await?;
+    let pathspec = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    
+    info!("Collecting changed lines against {} in {}", base, root.display());
+    
+    let output = git(&root)
+        .args(["diff", "--unified=0", "--no-color", "--no-ext-diff", base, "--"])
+        .arg(&pathspec)
+        .output()
+        .await
+        .context("Failed to run git diff")?;
+    
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git diff against {} failed: {}",
+            base,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    
+    Ok(parse_unified_diff(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Lists files under `path` as they exist at `commit`, relative to the
+/// repo root.
+pub async fn files_at(path: &Path, commit: &str) -> Result<Vec<PathBuf>> {
+    let root = repo_root(path).await?;
+    let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let pathspec = match absolute.strip_prefix(&root) {
+        // git rejects an empty pathspec for the root itself
+        Ok(relative) if relative.as_os_str().is_empty() => Path::new("."),
+        Ok(relative) => relative,
+        Err(_) => path,
+    };
+    
+    let output = git(&root)
+        .args(["ls-tree", "-r", "--name-only", commit, "--"])
+        .arg(pathspec)
+        .output()
+        .await
+        .context("Failed to run git ls-tree")?;
+    
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git ls-tree at {} failed: {}",
+            commit,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Reads a file's contents at `commit` without touching the working tree.
+/// `relative_path` is relative to the repo root.
+pub async fn show_file_at(root: &Path, commit: &str, relative_path: &Path) -> Result<String> {
+    let spec = format!("{}:{}", commit, relative_path.to_string_lossy().replace('\\', "/"));
+    
+    let output = git(root)
+        .args(["show", &spec])
+        .output()
+        .await
+        .context("Failed to run git show")?;
+    
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git show {} failed: {}",
+            spec,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    
+    String::from_utf8(output.stdout)
+        .with_context(|| format!("{} is not valid UTF-8", spec))
+}
+
+/// Stages everything under `path` and commits it in the repo containing
+/// `path`. Returns `false` when there was nothing to commit.
+pub async fn commit_all(path: &Path, message: &str) -> Result<bool> {
+    let root = repo_root(path).await?;
+    let pathspec = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    
+    let output = git(&root)
+        .args(["add", "--all", "--"])
+        .arg(&pathspec)
+        .output()
+        .await
+        .context("Failed to run git add")?;
+    
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git add in {} failed: {}",
+            root.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    
+    // Exits 0 when nothing is staged
+    let staged = git(&root)
+        .args(["diff", "--cached", "--quiet"])
+        .status()
+        .await
+        .context("Failed to run git diff")?;
+    if staged.success() {
+        return Ok(false);
+    }
+    
+    info!("Committing in {}", root.display());
+    let output = git(&root)
+        .args(["commit", "-m", message])
+        .output()
+        .await
+        .context("Failed to run git commit")?;
+    
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git commit in {} failed: {}",
+            root.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    
+    Ok(true)
+}
+
+pub fn parse_unified_diff(diff: &str) -> HashMap<PathBuf, Vec<LineRange>> {
+    let mut changes: HashMap<PathBuf, Vec<LineRange>> = HashMap::new();
+    let mut current: Option<PathBuf> = None;
+    
+    for line in diff.lines() {
+        if let Some(target) = line.strip_prefix("+++ ") {
+            // Deleted files show up as /dev/null on the new side
+            current = target.strip_prefix("b/").map(PathBuf::from);
+        } else if line.starts_with("@@") {
+            let Some(file) = &current else { continue };
+            
+            match parse_hunk_header(line) {
+                Some(range) => changes.entry(file.clone()).or_default().push(range),
+                None => continue,
+            }
+        }
+    }
+    
+    changes
+}
+
+// Parses the new-side range out of "@@ -a,b +c,d @@". Pure deletions
+// (d == 0) have no lines to comment on and are skipped.
+fn parse_hunk_header(header: &str) -> Option<LineRange> {
+    let new_side = header
+        .split_whitespace()
+        .find(|part| part.starts_with('+'))?
+        .trim_start_matches('+');
+    
+    let mut parts = new_side.splitn(2, ',');
+    let start: usize = parts.next()?.parse().ok()?;
+    let count: usize = match parts.next() {
+        Some(count) => count.parse().ok()?,
+        None => 1,
+    };
+    
+    if count == 0 {
+        return None;
+    }
+    
+    if start == 0 {
+        warn!("Unexpected hunk header: {}", header);
+        return None;
+    }
+    
+    Some(LineRange {
+        start,
+        end: start + count - 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn commits_land_in_the_reviewed_repo_not_the_working_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        let src = repo.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        let git = |args: &[&str]| std::process::Command::new("git").arg("-C").arg(repo).args(args).output().unwrap();
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "dev@example.com"]);
+        git(&["config", "user.name", "Dev"]);
+        std::fs::write(src.join("lib.rs"), "pub fn f() {}\n").unwrap();
+        std::fs::write(repo.join("notes.txt"), "outside the reviewed path\n").unwrap();
+        
+        assert_eq!(repo_root(&src).await.unwrap(), std::fs::canonicalize(repo).unwrap());
+        assert!(commit_all(&src, "Review fixes").await.unwrap());
+        assert!(!commit_all(&src, "Review fixes").await.unwrap());
+        
+        let committed = String::from_utf8(git(&["show", "--name-only", "--format=%s"]).stdout).unwrap();
+        assert_eq!(committed.split_whitespace().collect::<Vec<_>>(), vec!["Review", "fixes", "src/lib.rs"]);
+    }
+    
+    #[test]
+    fn hunk_headers_give_the_new_side_range() {
+        assert_eq!(parse_hunk_header("@@ -3 +4 @@"), Some(LineRange { start: 4, end: 4 }));
+        assert_eq!(parse_hunk_header("@@ -10,2 +12,3 @@ fn main() {"), Some(LineRange { start: 12, end: 14 }));
+        // Pure deletions leave nothing on the new side
+        assert_eq!(parse_hunk_header("@@ -5,2 +4,0 @@"), None);
+    }
+    
+    #[test]
+    fn unified_diffs_are_grouped_by_new_path() {
+        let diff = "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1 +1 @@
+-fn a() {}
++fn a() -> u8 { 0 }
+@@ -8,3 +8,0 @@
+-// gone
+-// gone
+-// gone
+@@ -20,2 +17,4 @@
+ fn b() {}
++fn c() {}
++fn d() {}
+ fn e() {}
+diff --git a/src/old_name.rs b/src/new_name.rs
+similarity index 90%
+rename from src/old_name.rs
+rename to src/new_name.rs
+--- a/src/old_name.rs
++++ b/src/new_name.rs
+@@ -2,2 +2,2 @@
+-let x = 1;
++let x = 2;
+ let y = 3;
+diff --git a/src/deleted.rs b/src/deleted.rs
+deleted file mode 100644
+--- a/src/deleted.rs
++++ /dev/null
+@@ -1,2 +0,0 @@
+-fn f() {}
+-fn g() {}
+";
+        
+        let changes = parse_unified_diff(diff);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[Path::new("src/a.rs")], vec![LineRange { start: 1, end: 1 }, LineRange { start: 17, end: 20 }]);
+        assert_eq!(changes[Path::new("src/new_name.rs")], vec![LineRange { start: 2, end: 3 }]);
+        assert!(!changes.contains_key(Path::new("src/old_name.rs")));
+    }
+}
@@ -18,6 +18,15 @@ pub struct GPUConfig {
     pub shared_memory_size: usize,
     pub enable_tensor_cores: bool,
     pub memory_pool_size: usize,
+    /// How long a metrics sample is reused before querying the driver again
+    #[serde(default = "GPUConfig::default_metrics_ttl_ms")]
+    pub metrics_ttl_ms: u64,
+}
+
+impl GPUConfig {
+    fn default_metrics_ttl_ms() -> u64 {
+        200
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,7 +44,7 @@ pub struct CodeGenerationResponse {
     pub compilation_time_ms: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GPUMetrics {
     pub gpu_utilization: f32,
     pub memory_used_mb: f32,
@@ -48,7 +57,14 @@ pub struct GPUAccelerator {
     #[cfg(feature = "gpu")]
     cuda_context: *mut cuda_runtime_sys::cudaContext_t,
     code_templates: Arc<Mutex<std::collections::HashMap<String, String>>>,
-    performance_cache: Arc<Mutex<std::collections::HashMap<String, GPUMetrics>>>,
+    // Std mutex: metrics are read from rayon workers, outside the runtime
+    performance_cache: Arc<std::sync::Mutex<std::collections::HashMap<String, CachedMetrics>>>,
+}
+
+struct CachedMetrics {
+    // Tokio's clock, so tests can step past the TTL without sleeping
+    sampled_at: tokio::time::Instant,
+    metrics: GPUMetrics,
 }
 
 impl GPUAccelerator {
@@ -66,17 +82,15 @@ impl GPUAccelerator {
             context
         };
         
-        #[cfg(not(feature = "gpu"))]
-        let cuda_context = std::ptr::null_mut();
-        
         // Pre-load common code templates for instant access
         let templates = Self::load_code_templates().await?;
         
         Ok(Self {
             config,
+            #[cfg(feature = "gpu")]
             cuda_context,
             code_templates: Arc::new(Mutex::new(templates)),
-            performance_cache: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            performance_cache: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
         })
     }
     
@@ -217,6 +231,29 @@ impl GPUAccelerator {
     }
     
     fn get_gpu_metrics(&self) -> GPUMetrics {
+        let key = format!("device:{}", self.config.device_id);
+        let ttl = std::time::Duration::from_millis(self.config.metrics_ttl_ms);
+        
+        // Holding the lock while sampling means concurrent callers wait for
+        // one driver query instead of all issuing their own.
+        let mut cache = self.performance_cache.lock().unwrap_or_else(|e| e.into_inner());
+        
+        if let Some(cached) = cache.get(&key) {
+            if cached.sampled_at.elapsed() < ttl {
+                return cached.metrics.clone();
+            }
+        }
+        
+        let metrics = self.sample_gpu_metrics();
+        cache.insert(key, CachedMetrics {
+            sampled_at: tokio::time::Instant::now(),
+            metrics: metrics.clone(),
+        });
+        
+        metrics
+    }
+    
+    fn sample_gpu_metrics(&self) -> GPUMetrics {
         #[cfg(feature = "gpu")]
         {
             unsafe {
@@ -303,4 +340,34 @@ impl GPUAccelerator {
         
         Ok(benchmark_code)
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    
+    #[tokio::test(start_paused = true)]
+    async fn metrics_are_sampled_again_once_the_ttl_expires() {
+        let accelerator = GPUAccelerator::new(GPUConfig {
+            device_id: 0,
+            max_threads_per_block: 1024,
+            shared_memory_size: 48 * 1024,
+            enable_tensor_cores: false,
+            memory_pool_size: 0,
+            metrics_ttl_ms: 200,
+        }).await.unwrap();
+        let sampled_at = || accelerator.performance_cache.lock().unwrap()["device:0"].sampled_at;
+        
+        accelerator.get_gpu_metrics();
+        let first = sampled_at();
+        
+        tokio::time::advance(Duration::from_millis(199)).await;
+        accelerator.get_gpu_metrics();
+        assert_eq!(sampled_at(), first);
+        
+        tokio::time::advance(Duration::from_millis(1)).await;
+        accelerator.get_gpu_metrics();
+        assert_eq!(sampled_at(), first + Duration::from_millis(200));
+    }
+}
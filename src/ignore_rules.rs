@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use glob::{MatchOptions, Pattern};
+use tokio::fs;
+use tracing::info;
+
+use crate::code_analyzer::Issue;
+use crate::config;
+
+pub const IGNORE_FILE: &str = ".devagentignore";
+
+/// Path-scoped rule suppressions read from `.devagentignore`.
+///
+/// Each line is a glob followed by the rules to suppress under it:
+///
+/// ```text
+/// examples/**  rust/println
+/// tests/       rust/unwrap rust/clone
+/// vendor/**
+/// ```
+///
+/// A glob with no rules suppresses everything. Rules may end in `/*` to
+/// cover a whole language (`python/*`). Globs are relative to the
+/// directory holding the ignore file; a trailing `/` means the directory
+/// and everything below it.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    root: Option<PathBuf>,
+    entries: Vec<IgnoreEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreEntry {
+    glob: Pattern,
+    rules: Vec<String>,
+}
+
+impl IgnoreRules {
+    /// Looks for `.devagentignore` next to the review path, then in the
+    /// working directory, like `.devagent.toml`. Missing files ignore nothing.
+    pub async fn load(review_path: &Path) -> Result<Self> {
+        for dir in [config::config_dir(review_path), Path::new(".")] {
+            let candidate = dir.join(IGNORE_FILE);
+            if !candidate.is_file() {
+                continue;
+            }
+            
+            info!("Loading rule ignores from {}", candidate.display());
+            
+            let content = fs::read_to_string(&candidate).await
+                .with_context(|| format!("Failed to read {}", candidate.display()))?;
+            let mut rules = Self::parse(&content)
+                .with_context(|| format!("Invalid {}", candidate.display()))?;
+            rules.root = Some(std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf()));
+            return Ok(rules);
+        }
+        
+        Ok(Self::default())
+    }
+    
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+        
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            
+            let mut fields = line.split_whitespace();
+            let Some(glob) = fields.next() else { continue };
+            let glob = match glob.strip_suffix('/') {
+                Some(dir) => format!("{}/**", dir),
+                None => glob.to_string(),
+            };
+            
+            entries.push(IgnoreEntry {
+                glob: Pattern::new(&glob).with_context(|| format!("line {}: bad glob `{}`", i + 1, glob))?,
+                rules: fields.map(str::to_string).collect(),
+            });
+        }
+        
+        Ok(Self { root: None, entries })
+    }
+    
+    /// Drops issues whose rule is suppressed for `file_path`. Paths outside
+    /// the ignore file's directory are matched as given.
+    pub fn retain_issues(&self, file_path: &Path, issues: &mut Vec<Issue>) {
+        let entries = self.matching_entries(file_path);
+        if !entries.is_empty() {
+            issues.retain(|issue| !entries.iter().any(|entry| entry.suppresses(&issue.rule)));
+        }
+    }
+    
+    fn matching_entries(&self, file_path: &Path) -> Vec<&IgnoreEntry> {
+        if self.entries.is_empty() {
+            return Vec::new();
+        }
+        
+        let relative = self.relative_path(file_path);
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::default()
+        };
+        
+        self.entries
+            .iter()
+            .filter(|entry| entry.glob.matches_with(&relative, options))
+            .collect()
+    }
+    
+    fn relative_path(&self, file_path: &Path) -> String {
+        let relative = self.root.as_ref().and_then(|root| {
+            let absolute = std::fs::canonicalize(file_path).ok()?;
+            absolute.strip_prefix(root).ok().map(Path::to_path_buf)
+        });
+        
+        let path = relative.unwrap_or_else(|| file_path.to_path_buf());
+        let path = path.to_string_lossy().replace('\\', "/");
+        path.strip_prefix("./").unwrap_or(&path).to_string()
+    }
+}
+
+impl IgnoreEntry {
+    fn suppresses(&self, rule: &str) -> bool {
+        self.rules.is_empty() || self.rules.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => rule.starts_with(prefix),
+            None => pattern == rule,
+        })
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{BinOp, Expr};
+
+use crate::code_analyzer::{Issue, IssueCategory, Severity};
+use crate::rust_attrs::is_test;
+
+/// Integer arithmetic in non-test Rust that can silently wrap or panic:
+/// `as` casts to a type that can't hold every value of the source type,
+/// `len() - n` that underflows on short collections, and indexing by a
+/// computed value. Source types come from a small per-function inference
+/// (typed parameters and `let`s, `len()`, suffixed literals, casts), so
+/// casts from values of unknown type aren't flagged.
+pub fn check(ast: &syn::File, lines: &[&str]) -> Vec<Issue> {
+    let mut collector = Collector::default();
+    collector.visit_file(ast);
+    
+    collector.findings.into_iter()
+        .map(|finding| Issue {
+            rule: finding.rule.to_string(),
+            severity: finding.severity,
+            message: finding.message,
+            line: Some(finding.line),
+            code: lines.get(finding.line.saturating_sub(1)).map(|line| line.to_string()),
+            category: IssueCategory::Security,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct IntType {
+    name: &'static str,
+    bits: u32,
+    signed: bool,
+}
+
+const INT_TYPES: [IntType; 12] = [
+    IntType { name: "u8", bits: 8, signed: false },
+    IntType { name: "u16", bits: 16, signed: false },
+    IntType { name: "u32", bits: 32, signed: false },
+    IntType { name: "u64", bits: 64, signed: false },
+    IntType { name: "u128", bits: 128, signed: false },
+    // Pointer-sized types are taken as 64-bit, the common target
+    IntType { name: "usize", bits: 64, signed: false },
+    IntType { name: "i8", bits: 8, signed: true },
+    IntType { name: "i16", bits: 16, signed: true },
+    IntType { name: "i32", bits: 32, signed: true },
+    IntType { name: "i64", bits: 64, signed: true },
+    IntType { name: "i128", bits: 128, signed: true },
+    IntType { name: "isize", bits: 64, signed: true },
+];
+
+impl IntType {
+    fn named(name: &str) -> Option<Self> {
+        INT_TYPES.into_iter().find(|int| int.name == name)
+    }
+    
+    fn of_type(ty: &syn::Type) -> Option<Self> {
+        match ty {
+            syn::Type::Path(path) if path.qself.is_none() => Self::named(&path.path.get_ident()?.to_string()),
+            syn::Type::Reference(reference) => Self::of_type(&reference.elem),
+            syn::Type::Paren(paren) => Self::of_type(&paren.elem),
+            _ => None,
+        }
+    }
+    
+    /// Whether every value of `self` fits in `target`.
+    fn fits_in(self, target: Self) -> bool {
+        match (self.signed, target.signed) {
+            (false, false) | (true, true) => self.bits <= target.bits,
+            (false, true) => self.bits < target.bits,
+            (true, false) => false,
+        }
+    }
+}
+
+struct Finding {
+    rule: &'static str,
+    severity: Severity,
+    message: String,
+    line: usize,
+}
+
+#[derive(Default)]
+struct Collector {
+    findings: Vec<Finding>,
+    // Integer-typed bindings in the current function
+    locals: HashMap<String, IntType>,
+    test_depth: usize,
+}
+
+impl Collector {
+    fn type_of(&self, expr: &Expr) -> Option<IntType> {
+        match expr {
+            Expr::Path(path) => self.locals.get(&path.path.get_ident()?.to_string()).copied(),
+            Expr::Cast(cast) => IntType::of_type(&cast.ty),
+            Expr::Paren(paren) => self.type_of(&paren.expr),
+            Expr::Reference(reference) => self.type_of(&reference.expr),
+            Expr::Unary(unary) => self.type_of(&unary.expr),
+            Expr::MethodCall(call) if is_len(call) => IntType::named("usize"),
+            Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(int), .. }) => IntType::named(int.suffix()),
+            Expr::Binary(binary) => self.type_of(&binary.left).or_else(|| self.type_of(&binary.right)),
+            _ => None,
+        }
+    }
+    
+    fn bind(&mut self, pat: &syn::Pat, ty: Option<IntType>) {
+        match (pat, ty) {
+            (syn::Pat::Ident(ident), Some(ty)) => {
+                self.locals.insert(ident.ident.to_string(), ty);
+            }
+            (syn::Pat::Ident(ident), None) => {
+                // Shadowed by a non-integer binding
+                self.locals.remove(&ident.ident.to_string());
+            }
+            (syn::Pat::Type(typed), _) => self.bind(&typed.pat, IntType::of_type(&typed.ty)),
+            _ => {}
+        }
+    }
+    
+    fn visit_fn(&mut self, attrs: &[syn::Attribute], sig: &syn::Signature, visit: impl FnOnce(&mut Self)) {
+        let is_test = is_test(attrs);
+        if is_test {
+            self.test_depth += 1;
+        }
+        let outer = std::mem::take(&mut self.locals);
+        
+        for input in &sig.inputs {
+            if let syn::FnArg::Typed(typed) = input {
+                self.bind(&typed.pat, IntType::of_type(&typed.ty));
+            }
+        }
+        visit(self);
+        
+        self.locals = outer;
+        if is_test {
+            self.test_depth -= 1;
+        }
+    }
+    
+    fn flag(&mut self, rule: &'static str, severity: Severity, message: String, line: usize) {
+        if self.test_depth == 0 {
+            self.findings.push(Finding { rule, severity, message, line });
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Collector {
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.visit_fn(&item.attrs, &item.sig, |this| visit::visit_item_fn(this, item));
+    }
+    
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.visit_fn(&item.attrs, &item.sig, |this| visit::visit_impl_item_fn(this, item));
+    }
+    
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        let is_test = is_test(&item.attrs);
+        if is_test {
+            self.test_depth += 1;
+        }
+        visit::visit_item_mod(self, item);
+        if is_test {
+            self.test_depth -= 1;
+        }
+    }
+    
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        // The initializer is visited first, since the binding isn't in
+        // scope there yet
+        visit::visit_local(self, local);
+        
+        let inferred = local.init.as_ref().and_then(|init| self.type_of(&init.expr));
+        self.bind(&local.pat, inferred);
+    }
+    
+    fn visit_expr_cast(&mut self, cast: &'ast syn::ExprCast) {
+        if let (Some(source), Some(target)) = (self.type_of(&cast.expr), IntType::of_type(&cast.ty)) {
+            if !source.fits_in(target) {
+                self.flag(
+                    "rust/truncating-cast",
+                    Severity::Medium,
+                    format!(
+                        "`as {}` silently truncates or wraps {} values that don't fit; use {}::try_from and handle the error",
+                        target.name, source.name, target.name,
+                    ),
+                    cast.as_token.span.start().line,
+                );
+            }
+        }
+        
+        visit::visit_expr_cast(self, cast);
+    }
+    
+    fn visit_expr_binary(&mut self, binary: &'ast syn::ExprBinary) {
+        let subtracts_from_len = matches!(binary.op, BinOp::Sub(_))
+            && matches!(&*binary.left, Expr::MethodCall(call) if is_len(call))
+            && !is_zero(&binary.right);
+        if subtracts_from_len {
+            self.flag(
+                "rust/len-underflow",
+                Severity::Medium,
+                "`len() - n` underflows when the collection is shorter than n; use checked_sub or saturating_sub".to_string(),
+                binary.op.span().start().line,
+            );
+        }
+        
+        visit::visit_expr_binary(self, binary);
+    }
+    
+    fn visit_expr_index(&mut self, index: &'ast syn::ExprIndex) {
+        // `v[v.len() - 1]` is already reported as a len underflow
+        let computed = matches!(
+            &*index.index,
+            Expr::Binary(binary) if matches!(binary.op, BinOp::Add(_) | BinOp::Sub(_) | BinOp::Mul(_))
+                && !matches!(&*binary.left, Expr::MethodCall(call) if is_len(call))
+        );
+        if computed {
+            self.flag(
+                "rust/computed-index",
+                Severity::Low,
+                "Index computed with arithmetic can overflow or fall out of bounds and panic; use .get() or check the bounds first".to_string(),
+                index.bracket_token.span.open().start().line,
+            );
+        }
+        
+        visit::visit_expr_index(self, index);
+    }
+}
+
+fn is_len(call: &syn::ExprMethodCall) -> bool {
+    call.method == "len" && call.args.is_empty()
+}
+
+fn is_zero(expr: &Expr) -> bool {
+    matches!(expr, Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(int), .. }) if int.base10_digits() == "0")
+}
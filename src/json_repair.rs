@@ -0,0 +1,154 @@
+use serde_json::Value;
+
+/// Parses the first JSON object in an LLM response, tolerating prose or
+/// code fences around it and a truncated tail (the model hit its token
+/// limit mid-object). A truncated object keeps every member that was
+/// complete; the cut-off member is dropped, except a string value cut
+/// mid-text, which is kept as far as it got.
+pub fn parse_object(text: &str) -> Option<Value> {
+    let text = &text[text.find('{')?..];
+    
+    match object_end(text) {
+        Some(end) => serde_json::from_str(&text[..end]).ok(),
+        None => close_truncated(text),
+    }
+}
+
+/// The string value of `key` read straight from the text, for responses
+/// too broken to parse at all. An unterminated value runs to the end.
+pub fn string_field(text: &str, key: &str) -> Option<String> {
+    let quoted_key = format!("\"{}\"", key);
+    let after_key = &text[text.find(&quoted_key)? + quoted_key.len()..];
+    let value = after_key.trim_start().strip_prefix(':')?.trim_start().strip_prefix('"')?;
+    
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(other) => result.push(other),
+                None => break,
+            },
+            _ => result.push(c),
+        }
+    }
+    
+    (!result.trim().is_empty()).then_some(result)
+}
+
+// Where the object opening `text` closes, or `None` if it never does
+fn object_end(text: &str) -> Option<usize> {
+    let mut scan = Scan::default();
+    for (i, c) in text.char_indices() {
+        scan.push(c);
+        if scan.open.is_empty() {
+            return Some(i + c.len_utf8());
+        }
+    }
+    None
+}
+
+fn close_truncated(text: &str) -> Option<Value> {
+    let mut cut = text.len();
+    
+    loop {
+        let prefix = &text[..cut];
+        let mut scan = Scan::default();
+        prefix.chars().for_each(|c| scan.push(c));
+        
+        let mut candidate = if scan.in_string {
+            // Drop a dangling escape so the closing quote isn't escaped
+            let mut closed = prefix.strip_suffix('\\').unwrap_or(prefix).to_string();
+            closed.push('"');
+            closed
+        } else {
+            prefix.trim_end().trim_end_matches(',').to_string()
+        };
+        for open in scan.open.iter().rev() {
+            candidate.push(if *open == '{' { '}' } else { ']' });
+        }
+        
+        if let Ok(value) = serde_json::from_str(&candidate) {
+            return Some(value);
+        }
+        
+        // Drop the last, incomplete member and try again
+        let separator = scan.last_separator?;
+        let next_cut = if prefix[separator..].starts_with(',') { separator } else { separator + 1 };
+        if next_cut >= cut {
+            return None;
+        }
+        cut = next_cut;
+    }
+}
+
+#[derive(Default)]
+struct Scan {
+    open: Vec<char>,
+    in_string: bool,
+    escaped: bool,
+    offset: usize,
+    // Offset of the last `,`, `{` or `[` outside a string
+    last_separator: Option<usize>,
+}
+
+impl Scan {
+    fn push(&mut self, c: char) {
+        let offset = self.offset;
+        self.offset += c.len_utf8();
+        
+        if self.in_string {
+            match (self.escaped, c) {
+                (true, _) => self.escaped = false,
+                (false, '\\') => self.escaped = true,
+                (false, '"') => self.in_string = false,
+                _ => {}
+            }
+            return;
+        }
+        
+        match c {
+            '"' => self.in_string = true,
+            '{' | '[' => {
+                self.open.push(c);
+                self.last_separator = Some(offset);
+            }
+            '}' | ']' => {
+                self.open.pop();
+            }
+            ',' => self.last_separator = Some(offset),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn truncated_and_wrapped_objects_are_recovered() {
+        let fenced = "Sure:\n```json\n{\"action\": \"git_operation\", \"details\": \"commit {all}\"}\n```\nDone.";
+        assert_eq!(parse_object(fenced).unwrap()["details"], "commit {all}");
+        
+        // Cut mid-string: the partial reasoning is kept
+        let cut_in_string = r#"{"action": "build_operation", "confidence": 0.9, "reasoning": "The user wants a rel"#;
+        let value = parse_object(cut_in_string).unwrap();
+        assert_eq!((value["action"].as_str(), value["reasoning"].as_str()), (Some("build_operation"), Some("The user wants a rel")));
+        
+        let cut_in_array = r#"{"action": "web_search", "tags": ["rust", "tok"#;
+        assert_eq!(parse_object(cut_in_array).unwrap(), serde_json::json!({"action": "web_search", "tags": ["rust", "tok"]}));
+        
+        // Cut mid-number or mid-key: the broken member is dropped
+        let cut_in_number = r#"{"action": "test_operation", "details": "cargo test", "confidence": 0."#;
+        assert_eq!(parse_object(cut_in_number).unwrap(), serde_json::json!({"action": "test_operation", "details": "cargo test"}));
+        assert_eq!(parse_object(r#"{"action": "web_search", "requires_cl"#).unwrap(), serde_json::json!({"action": "web_search"}));
+        
+        assert_eq!(parse_object("no json here"), None);
+        assert_eq!(string_field(r#"{"action": "git_operation", "details": "push to \"main"#, "details").as_deref(), Some("push to \"main"));
+        assert_eq!(string_field(r#"{"action": }"#, "action"), None);
+    }
+}
@@ -0,0 +1,136 @@
+/// Best guesses below this share of the total score count as unknown.
+pub const MIN_CONFIDENCE: f32 = 0.6;
+
+// A single weak token shouldn't decide the language of a file
+const MIN_SCORE: usize = 4;
+
+// Lines containing a feature count at most this many times, so one
+// repeated token can't outweigh everything else
+const MAX_HITS: usize = 3;
+
+/// Tokens typical of each language with their weight. Tokens shared by
+/// several languages carry little weight, distinctive ones a lot.
+const FEATURES: &[(&str, &[(&str, usize)])] = &[
+    ("rust", &[
+        ("fn ", 2), ("let mut ", 4), ("impl ", 3), ("pub fn ", 4), ("use std::", 5), ("&self", 3),
+        ("&mut ", 3), ("#[derive(", 5), ("println!(", 4), ("-> Result<", 4), ("Option<", 2),
+        ("Vec<", 2), ("match ", 1), ("unwrap()", 3), ("pub struct ", 4), ("mod ", 1),
+    ]),
+    ("python", &[
+        ("def ", 3), ("elif ", 5), ("self.", 1), ("__init__", 5), ("import ", 1), ("from ", 1),
+        ("print(", 1), ("None", 2), ("lambda ", 3), ("if __name__ ==", 6), ("pass", 4),
+        ("except ", 4), ("True", 1), ("False", 1),
+    ]),
+    ("javascript", &[
+        ("function ", 2), ("const ", 2), ("let ", 1), ("=> ", 2), ("console.log(", 5), ("require(", 4),
+        ("module.exports", 5), ("export ", 3), ("===", 4), ("!==", 4), ("undefined", 3),
+        ("document.", 3), (": string", 3), (": number", 3), ("interface ", 1), ("async ", 1),
+    ]),
+    ("java", &[
+        ("public class ", 6), ("public static void main", 8), ("private ", 2), ("System.out.println", 7),
+        ("import java.", 7), ("@Override", 6), ("new ", 1), ("extends ", 2), ("throws ", 4),
+    ]),
+    ("go", &[
+        ("package main", 7), ("func ", 3), (":= ", 4), ("fmt.", 5), ("import (", 5), ("go func", 6),
+        ("chan ", 3), ("defer ", 4), ("err != nil", 6),
+    ]),
+    ("cpp", &[
+        ("#include", 6), ("std::", 4), ("int main(", 3), ("cout <<", 5), ("nullptr", 5),
+        ("template<", 5), ("template <", 5), ("namespace ", 2), ("#define ", 4),
+    ]),
+    ("php", &[
+        ("<?php", 12), ("$this->", 6), ("echo ", 2), ("function ", 1), ("=> $", 4),
+    ]),
+    ("shell", &[
+        ("fi", 2), ("then", 2), ("esac", 5), ("echo ", 2), ("$(", 3), ("export ", 1), ("done", 2),
+    ]),
+];
+
+/// The best-scoring language for a file and how sure the guess is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    pub language: String,
+    /// Share of the total score won by `language`, from 0.0 to 1.0
+    pub confidence: f32,
+}
+
+/// Classifies source text by its shebang when it has one, otherwise by
+/// weighted token frequency across every known language. `None` when
+/// nothing scores, or the best match is under `MIN_CONFIDENCE`.
+pub fn classify(content: &str) -> Option<Classification> {
+    if let Some(language) = content.lines().next().and_then(shebang_language) {
+        return Some(Classification {
+            language: language.to_string(),
+            confidence: 1.0,
+        });
+    }
+    
+    let lines: Vec<&str> = content.lines().map(str::trim).collect();
+    let scores: Vec<(&str, usize)> = FEATURES.iter()
+        .map(|(language, features)| {
+            let score = features.iter()
+                .map(|(token, weight)| lines.iter().filter(|line| matches(line, token)).count().min(MAX_HITS) * weight)
+                .sum();
+            (*language, score)
+        })
+        .collect();
+    
+    let total: usize = scores.iter().map(|(_, score)| score).sum();
+    let (language, best) = scores.into_iter().max_by_key(|(_, score)| *score)?;
+    if best < MIN_SCORE {
+        return None;
+    }
+    
+    let confidence = best as f32 / total as f32;
+    (confidence >= MIN_CONFIDENCE).then(|| Classification {
+        language: language.to_string(),
+        confidence,
+    })
+}
+
+// Short keywords ("fi", "then", "pass") only count as whole lines, line
+// starts or after a `;`
+fn matches(line: &str, token: &str) -> bool {
+    if token.len() <= 4 && token.chars().all(|c| c.is_ascii_lowercase()) {
+        return line == token || line.starts_with(&format!("{} ", token)) || line.ends_with(&format!("; {}", token));
+    }
+    line.contains(token)
+}
+
+fn shebang_language(first_line: &str) -> Option<&'static str> {
+    let interpreter = first_line.strip_prefix("#!")?;
+    // `#!/usr/bin/env python3` names the interpreter as an argument
+    let name = interpreter.split_whitespace()
+        .map(|part| part.rsplit('/').next().unwrap_or(part))
+        .find(|name| *name != "env")?;
+    
+    match name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+        "python" => Some("python"),
+        "node" | "deno" | "bun" => Some("javascript"),
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => Some("shell"),
+        "php" => Some("php"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn content_classifier_detects_extensionless_files() {
+        let rust = "fn main() {\n    let mut total = 0;\n    for n in 1..10 { total += n; }\n    println!(\"{}\", total);\n}\n";
+        let typescript = "export function area(width: number, height: number): number {\n    return width * height;\n}\n";
+        let go = "package main\n\nfunc main() {\n    if err := run(); err != nil {\n        fmt.Println(err)\n    }\n}\n";
+        let script = "#!/usr/bin/env python3\nprint('hi')\n";
+        let shell = "if [ -f build.log ]; then\n    echo \"found\"\nfi\n";
+        
+        let guess = |content: &str| classify(content).map(|c| c.language);
+        assert_eq!(guess(rust).as_deref(), Some("rust"));
+        assert_eq!(guess(typescript).as_deref(), Some("javascript"));
+        assert_eq!(guess(go).as_deref(), Some("go"));
+        assert_eq!(classify(script).map(|c| (c.language, c.confidence)), Some(("python".to_string(), 1.0)));
+        assert_eq!(guess(shell).as_deref(), Some("shell"));
+        assert_eq!(classify("Release notes for the next version.\n"), None);
+    }
+}
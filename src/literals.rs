@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{Expr, Token};
+
+/// Settings for the repeated string literal check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DuplicateLiteralConfig {
+    /// Report literals that appear at least this many times in a file
+    pub threshold: usize,
+    /// Literals shorter than this (in characters) are ignored
+    pub min_length: usize,
+}
+
+impl Default for DuplicateLiteralConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 3,
+            min_length: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DuplicateLiteral {
+    pub value: String,
+    pub count: usize,
+    pub first_line: usize,
+}
+
+impl DuplicateLiteralConfig {
+    pub fn find(&self, language: &str, content: &str) -> Vec<DuplicateLiteral> {
+        let literals = if language == "rust" {
+            match crate::profiling::measure("syn parsing", || syn::parse_file(content)) {
+                Ok(ast) => {
+                    let mut collector = RustLiteralCollector::default();
+                    collector.visit_file(&ast);
+                    collector.literals
+                }
+                Err(_) => scan_string_literals(content, "//"),
+            }
+        } else if language == "python" {
+            scan_string_literals(content, "#")
+        } else {
+            scan_string_literals(content, "//")
+        };
+        
+        let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+        for (value, line) in literals {
+            if value.trim().chars().count() < self.min_length {
+                continue;
+            }
+            
+            let entry = counts.entry(value).or_insert((0, line));
+            entry.0 += 1;
+            entry.1 = entry.1.min(line);
+        }
+        
+        let mut duplicates: Vec<DuplicateLiteral> = counts
+            .into_iter()
+            .filter(|(_, (count, _))| *count >= self.threshold)
+            .map(|(value, (count, first_line))| DuplicateLiteral { value, count, first_line })
+            .collect();
+        
+        duplicates.sort_by(|a, b| a.first_line.cmp(&b.first_line).then_with(|| a.value.cmp(&b.value)));
+        duplicates
+    }
+}
+
+// Lightweight tokenizer for languages without a parser here: skips
+// comments and triple-quoted docstrings, and returns each quoted string
+// with the line it starts on.
+fn scan_string_literals(content: &str, line_comment: &str) -> Vec<(String, usize)> {
+    let chars: Vec<char> = content.chars().collect();
+    let comment: Vec<char> = line_comment.chars().collect();
+    let block_comments = line_comment == "//";
+    
+    let mut literals = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    
+    let starts_with = |i: usize, pattern: &[char]| chars[i..].starts_with(pattern);
+    
+    while i < chars.len() {
+        let c = chars[i];
+        
+        if starts_with(i, &comment) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        
+        if block_comments && starts_with(i, &['/', '*']) {
+            i += 2;
+            while i < chars.len() && !starts_with(i, &['*', '/']) {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            i += 2;
+            continue;
+        }
+        
+        if c == '"' || c == '\'' || c == '`' {
+            let triple = [c, c, c];
+            if c != '`' && starts_with(i, &triple) {
+                i += 3;
+                while i < chars.len() && !starts_with(i, &triple) {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                i += 3;
+                continue;
+            }
+            
+            let start_line = line;
+            let mut value = String::new();
+            i += 1;
+            
+            while i < chars.len() && chars[i] != c {
+                match chars[i] {
+                    '\\' if i + 1 < chars.len() => {
+                        value.push(chars[i]);
+                        value.push(chars[i + 1]);
+                        i += 2;
+                        continue;
+                    }
+                    // Only template literals span lines; anything else is unterminated
+                    '\n' if c != '`' => break,
+                    '\n' => line += 1,
+                    _ => {}
+                }
+                value.push(chars[i]);
+                i += 1;
+            }
+            
+            if i < chars.len() && chars[i] == c {
+                literals.push((value, start_line));
+            }
+            i += 1;
+            continue;
+        }
+        
+        if c == '\n' {
+            line += 1;
+        }
+        i += 1;
+    }
+    
+    literals
+}
+
+#[derive(Default)]
+struct RustLiteralCollector {
+    literals: Vec<(String, usize)>,
+}
+
+impl<'ast> Visit<'ast> for RustLiteralCollector {
+    fn visit_lit_str(&mut self, lit: &'ast syn::LitStr) {
+        self.literals.push((lit.value(), lit.span().start().line));
+    }
+    
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // Same trick as the call collector: look inside println!/format!
+        // style bodies, which syn otherwise leaves as raw tokens.
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+        
+        visit::visit_macro(self, mac);
+    }
+}
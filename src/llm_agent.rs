@@ -1,10 +1,28 @@
 use anyhow::{Context, Result};
+use futures::future;
+use futures::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::Path;
-use tracing::{info, warn, error};
+use std::time::Instant;
+use tracing::{debug, info, warn, error};
 use reqwest::Client;
 use tokio::fs;
 
+use crate::chunking::{self, Chunk};
+use crate::comments;
+use crate::llm_cache::LlmCache;
+use crate::profiling;
+use crate::redact;
+use crate::rust_complexity::{self, FunctionComplexity};
+use crate::llm_worker::{WorkerClient, WorkerConfig};
+
+/// Tokens reserved for the model's answer.
+const RESPONSE_TOKENS: usize = 500;
+
+/// System message for chat-style APIs; `suggest` keeps only bullet lines.
+const CHAT_SYSTEM_PROMPT: &str = "You are an experienced code reviewer. Answer with a list of suggestions, one per line, each starting with \"- \".";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LlmAnalysis {
     pub complexity_score: f32,
@@ -13,6 +31,13 @@ pub struct LlmAnalysis {
     pub ai_suggestions: Vec<String>,
     pub code_quality_metrics: CodeQualityMetrics,
     pub refactoring_suggestions: Vec<RefactoringSuggestion>,
+    /// The run deadline had passed, so only static suggestions were made
+    #[serde(default)]
+    pub deadline_fallback: bool,
+    /// Pieces the file was split into to fit the model's context; 0 when
+    /// it was sent whole or the LLM wasn't used
+    #[serde(default)]
+    pub chunks: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,6 +47,10 @@ pub struct CodeQualityMetrics {
     pub comment_ratio: f32,
     pub function_count: usize,
     pub average_function_length: f32,
+    /// Complexity of each function, most complex first; only for Rust
+    /// files that parse
+    #[serde(default)]
+    pub functions: Vec<FunctionComplexity>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,109 +62,694 @@ pub struct RefactoringSuggestion {
     pub impact: String,
 }
 
+/// Prompt settings for LLM analysis, read from the `[llm]` table of
+/// `.devagent.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmConfig {
+    /// Model name sent to the local LLM endpoint
+    pub model: String,
+    /// Supports `{language}`, `{content}` and `{max_suggestions}`;
+    /// `{content}` is required and any other `{name}` is an error.
+    pub prompt_template: String,
+    pub max_suggestions: usize,
+    pub backend: LlmBackend,
+    /// Used when `backend` is `subprocess`
+    pub worker: WorkerConfig,
+    /// The model's context window in tokens. Files whose prompt would not
+    /// fit are split into chunks analyzed one at a time.
+    pub context_tokens: usize,
+    /// Lines repeated at the start of each chunk from the end of the last
+    pub chunk_overlap_lines: usize,
+    /// Response time of the `mock` backend
+    pub mock_latency_ms: u64,
+    /// Tries per HTTP request; timeouts, connection errors, 429 and 5xx
+    /// are retried, other failures are not
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubling for each one after
+    pub retry_backoff_ms: u64,
+    /// Limit on each HTTP attempt, including reading the response
+    pub attempt_timeout_secs: u64,
+}
+
+/// Where generate requests go.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmBackend {
+    /// The HTTP endpoint at `LLM_ENDPOINT`
+    #[default]
+    Http,
+    /// A worker process running the model locally, restarted if it crashes
+    Subprocess,
+    /// A canned answer after `mock_latency_ms`, for benchmarks and tests
+    Mock,
+}
+
+/// The API behind the HTTP backend, picked with `LLM_PROVIDER`.
+#[derive(Clone, Default, PartialEq)]
+pub enum LlmProvider {
+    /// Ollama's `/api/generate` at `LLM_ENDPOINT`
+    #[default]
+    Ollama,
+    /// `/v1/chat/completions` on OpenAI or a compatible gateway
+    OpenAI { api_key: String, base_url: String },
+}
+
+// Written out so the API key never ends up in a log line
+impl std::fmt::Debug for LlmProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ollama => f.write_str("Ollama"),
+            Self::OpenAI { base_url, .. } => f.debug_struct("OpenAI")
+                .field("api_key", &redact::MASK)
+                .field("base_url", base_url)
+                .finish(),
+        }
+    }
+}
+
+impl LlmProvider {
+    pub const DEFAULT_OPENAI_BASE_URL: &'static str = "https://api.openai.com/v1";
+    
+    /// `LLM_PROVIDER` is `ollama` (the default) or `openai`, which also
+    /// needs `OPENAI_API_KEY` and takes `OPENAI_BASE_URL`.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+    
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let provider = var("LLM_PROVIDER").unwrap_or_default();
+        match provider.trim().to_lowercase().as_str() {
+            "" | "ollama" => Ok(Self::Ollama),
+            "openai" => Ok(Self::OpenAI {
+                api_key: var("OPENAI_API_KEY")
+                    .filter(|key| !key.trim().is_empty())
+                    .context("LLM_PROVIDER=openai needs OPENAI_API_KEY")?,
+                base_url: var("OPENAI_BASE_URL").unwrap_or_else(|| Self::DEFAULT_OPENAI_BASE_URL.to_string()),
+            }),
+            other => Err(anyhow::anyhow!("Unknown LLM_PROVIDER {:?}, expected ollama or openai", other)),
+        }
+    }
+    
+    /// `path` under the OpenAI API root, whether or not `base_url` already
+    /// ends in `/v1`.
+    fn openai_url(base_url: &str, path: &str) -> String {
+        let base_url = base_url.trim_end_matches('/');
+        let base_url = base_url.strip_suffix("/v1").unwrap_or(base_url);
+        format!("{}/v1/{}", base_url, path)
+    }
+    
+    /// The answer fragment in one line of a streamed response, and whether
+    /// the answer is complete: NDJSON objects from Ollama, server-sent
+    /// `data:` events from OpenAI.
+    fn parse_stream_event(&self, line: &str) -> Result<(Option<String>, bool)> {
+        let line = line.trim();
+        let json = match self {
+            Self::Ollama if line.is_empty() => return Ok((None, false)),
+            Self::Ollama => line,
+            Self::OpenAI { .. } => match line.strip_prefix("data:").map(str::trim) {
+                Some("[DONE]") => return Ok((None, true)),
+                Some(data) => data,
+                // Blank separators, comments and other fields
+                None => return Ok((None, false)),
+            },
+        };
+        
+        let event: serde_json::Value = serde_json::from_str(json)
+            .with_context(|| format!("Malformed LLM stream event: {}", self.scrub(json)))?;
+        if let Some(error) = event.get("error") {
+            return Err(anyhow::anyhow!("LLM reported an error: {}", self.scrub(&error.to_string())));
+        }
+        
+        let fragment = match self {
+            Self::Ollama => event["response"].as_str(),
+            Self::OpenAI { .. } => event["choices"][0]["delta"]["content"].as_str(),
+        };
+        let done = matches!(self, Self::Ollama) && event["done"].as_bool().unwrap_or(false);
+        Ok((fragment.filter(|fragment| !fragment.is_empty()).map(str::to_string), done))
+    }
+    
+    /// `text` with the API key masked, for error messages built from
+    /// responses that might echo it.
+    fn scrub(&self, text: &str) -> String {
+        match self {
+            Self::OpenAI { api_key, .. } => text.replace(api_key.as_str(), redact::MASK),
+            Self::Ollama => text.to_string(),
+        }
+    }
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            model: "phi-3-mini-instruct".to_string(),
+            prompt_template: "Analyze this {language} code and provide specific improvement suggestions:\n\n{content}\n\nProvide up to {max_suggestions} specific, actionable suggestions for improving code quality, performance, and maintainability.".to_string(),
+            max_suggestions: 5,
+            backend: LlmBackend::Http,
+            worker: WorkerConfig::default(),
+            context_tokens: 4096,
+            chunk_overlap_lines: 20,
+            mock_latency_ms: 250,
+            max_attempts: 3,
+            retry_backoff_ms: 500,
+            attempt_timeout_secs: 120,
+        }
+    }
+}
+
+impl LlmConfig {
+    pub const REQUIRED_PLACEHOLDERS: [&'static str; 1] = ["{content}"];
+    pub const PLACEHOLDERS: [&'static str; 3] = ["{language}", "{content}", "{max_suggestions}"];
+    
+    // Smallest chunk worth sending; a context too small for this is a typo
+    const MIN_CONTENT_TOKENS: usize = 256;
+    
+    pub fn validate(&self) -> Result<()> {
+        let missing: Vec<&str> = Self::REQUIRED_PLACEHOLDERS
+            .into_iter()
+            .filter(|placeholder| !self.prompt_template.contains(placeholder))
+            .collect();
+        
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!(
+                "LLM prompt template is missing required placeholders: {}",
+                missing.join(", ")
+            ));
+        }
+        
+        let unknown: Vec<&str> = placeholders(&self.prompt_template)
+            .filter(|placeholder| !Self::PLACEHOLDERS.contains(placeholder))
+            .collect();
+        
+        if !unknown.is_empty() {
+            return Err(anyhow::anyhow!(
+                "LLM prompt template has unknown placeholders: {} (supported: {})",
+                unknown.join(", "),
+                Self::PLACEHOLDERS.join(", ")
+            ));
+        }
+        
+        if self.model.trim().is_empty() {
+            return Err(anyhow::anyhow!("LLM model must not be empty"));
+        }
+        
+        if self.max_suggestions == 0 {
+            return Err(anyhow::anyhow!("LLM max_suggestions must be at least 1"));
+        }
+        
+        if self.content_tokens() < Self::MIN_CONTENT_TOKENS {
+            return Err(anyhow::anyhow!(
+                "LLM context_tokens {} leaves no room for code after the prompt and a {}-token response",
+                self.context_tokens,
+                RESPONSE_TOKENS
+            ));
+        }
+        
+        if self.worker.startup_timeout_secs == 0 || self.worker.request_timeout_secs == 0 {
+            return Err(anyhow::anyhow!("LLM worker timeouts must be at least 1 second"));
+        }
+        
+        if self.max_attempts == 0 {
+            return Err(anyhow::anyhow!("LLM max_attempts must be at least 1"));
+        }
+        
+        if self.attempt_timeout_secs == 0 {
+            return Err(anyhow::anyhow!("LLM attempt_timeout_secs must be at least 1"));
+        }
+        
+        Ok(())
+    }
+    
+    /// Wait before retrying after failed attempt number `attempt` (from 1).
+    fn retry_delay(&self, attempt: u32) -> std::time::Duration {
+        std::time::Duration::from_millis(self.retry_backoff_ms.saturating_mul(1 << (attempt - 1).min(16)))
+    }
+    
+    fn http_client(&self) -> Client {
+        Client::builder()
+            .timeout(std::time::Duration::from_secs(self.attempt_timeout_secs))
+            .build()
+            .unwrap_or_default()
+    }
+    
+    /// Tokens of code that fit in one request alongside the prompt text
+    /// and the response.
+    pub fn content_tokens(&self) -> usize {
+        let prompt = self.render_prompt("unknown", "");
+        self.context_tokens.saturating_sub(chunking::estimate_tokens(&prompt) + RESPONSE_TOKENS)
+    }
+    
+    pub fn render_prompt(&self, language: &str, content: &str) -> String {
+        // Content goes in last so placeholders inside the code are left alone
+        self.prompt_template
+            .replace("{language}", language)
+            .replace("{max_suggestions}", &self.max_suggestions.to_string())
+            .replace("{content}", content)
+    }
+}
+
+/// `{name}` spans of `template`. Other braces, such as a JSON example in
+/// the prompt, aren't placeholders.
+fn placeholders(template: &str) -> impl Iterator<Item = &str> {
+    template.match_indices('{').filter_map(|(start, _)| {
+        let end = start + template[start..].find('}')?;
+        let name = &template[start + 1..end];
+        let is_name = !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c == '_');
+        is_name.then(|| &template[start..=end])
+    })
+}
+
+/// Where the agent finds its model, read from the environment outside
+/// tests.
+#[derive(Debug, Clone)]
+pub struct LlmAgentConfig {
+    pub provider: LlmProvider,
+    /// Where Ollama listens; the OpenAI provider carries its own URL
+    pub endpoint: String,
+    /// Replaces `model` from the `[llm]` config when set
+    pub model: Option<String>,
+}
+
+impl LlmAgentConfig {
+    pub const DEFAULT_ENDPOINT: &'static str = "http://localhost:11434";
+    
+    /// The provider as in `LlmProvider::from_env`, plus `LLM_ENDPOINT` and
+    /// `LLM_MODEL`.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+    
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        Ok(Self {
+            provider: LlmProvider::from_vars(&var)?,
+            endpoint: var("LLM_ENDPOINT").unwrap_or_else(|| Self::DEFAULT_ENDPOINT.to_string()),
+            model: var("LLM_MODEL")
+                .map(|model| model.trim().to_string())
+                .filter(|model| !model.is_empty()),
+        })
+    }
+    
+    /// Ollama at `endpoint`, using the configured model.
+    pub fn ollama(endpoint: impl Into<String>) -> Self {
+        Self { provider: LlmProvider::Ollama, endpoint: endpoint.into(), model: None }
+    }
+}
+
 pub struct LlmAgent {
     client: Client,
+    provider: LlmProvider,
     model_endpoint: String,
+    /// Sent with every request: `LLM_MODEL` if set, else `[llm] model`
+    model: String,
+    model_override: Option<String>,
     local_model_available: bool,
+    temperature: f32,
+    seed: Option<u64>,
+    timeout: Option<std::time::Duration>,
+    config: LlmConfig,
+    worker: Option<WorkerClient>,
+    cache: Option<LlmCache>,
 }
 
 impl LlmAgent {
-    pub async fn new() -> Result<Self> {
-        info!("Initializing LLM Agent...");
+    pub async fn new(agent_config: LlmAgentConfig) -> Self {
+        let LlmAgentConfig { provider, endpoint: model_endpoint, model: model_override } = agent_config;
+        let config = LlmConfig::default();
+        let model = model_override.clone().unwrap_or_else(|| config.model.clone());
+        info!("Initializing LLM Agent with {:?} and model {}", provider, model);
         
-        let client = Client::new();
-        let model_endpoint = std::env::var("LLM_ENDPOINT")
-            .unwrap_or_else(|_| "http://localhost:11434".to_string());
+        let client = config.http_client();
         
         // Check if local model is available
-        let local_model_available = Self::check_local_model(&client, &model_endpoint).await;
+        let local_model_available = Self::check_local_model(&client, &provider, &model_endpoint).await;
         
-        Ok(Self {
+        Self {
             client,
+            provider,
             model_endpoint,
+            model,
+            model_override,
             local_model_available,
-        })
+            temperature: 0.3,
+            seed: None,
+            timeout: None,
+            config,
+            worker: None,
+            cache: None,
+        }
     }
     
-    async fn check_local_model(client: &Client, endpoint: &str) -> bool {
-        match client.get(&format!("{}/api/tags", endpoint)).send().await {
+    pub fn set_temperature(&mut self, temperature: f32) {
+        self.temperature = temperature;
+    }
+    
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+    
+    /// Bounds each generate request; a timed-out request falls back to
+    /// static suggestions like any other LLM failure.
+    pub fn set_timeout(&mut self, timeout: std::time::Duration) {
+        self.timeout = Some(timeout);
+    }
+    
+    pub fn set_config(&mut self, config: LlmConfig) {
+        self.worker = match config.backend {
+            LlmBackend::Subprocess => Some(WorkerClient::new(config.worker.clone())),
+            LlmBackend::Http | LlmBackend::Mock => None,
+        };
+        if config.attempt_timeout_secs != self.config.attempt_timeout_secs {
+            self.client = config.http_client();
+        }
+        self.model = self.model_override.clone().unwrap_or_else(|| config.model.clone());
+        self.config = config;
+    }
+    
+    /// Reuse analyses from `cache` for contents the current model has
+    /// seen before; `None` always asks the model.
+    pub fn set_cache(&mut self, cache: Option<LlmCache>) {
+        self.cache = cache;
+    }
+    
+    pub async fn clear_cache(&self) -> Result<()> {
+        match &self.cache {
+            Some(cache) => cache.clear().await,
+            None => Ok(()),
+        }
+    }
+    
+    /// Starts the subprocess worker if that backend is configured and
+    /// checks it responds. Without this the worker starts on first use.
+    pub async fn health_check(&self) -> Result<()> {
+        match &self.worker {
+            Some(worker) => worker.health_check().await,
+            None => Ok(()),
+        }
+    }
+    
+    fn llm_available(&self) -> bool {
+        self.config.backend == LlmBackend::Mock || self.worker.is_some() || self.local_model_available
+    }
+    
+    async fn check_local_model(client: &Client, provider: &LlmProvider, endpoint: &str) -> bool {
+        let request = match provider {
+            LlmProvider::Ollama => client.get(&format!("{}/api/tags", endpoint)),
+            LlmProvider::OpenAI { api_key, base_url } => client
+                .get(LlmProvider::openai_url(base_url, "models"))
+                .bearer_auth(api_key),
+        };
+        match request.send().await {
             Ok(response) => response.status().is_success(),
             Err(_) => false,
         }
     }
     
-    pub async fn analyze_code(&self, content: &str, file_path: &Path) -> Result<LlmAnalysis> {
+    /// Once `deadline` has passed, the LLM is skipped and the file gets
+    /// static suggestions only; a request still in flight at the deadline
+    /// is abandoned the same way. With a cache set, contents the current
+    /// model has analyzed before are answered from it instead.
+    pub async fn analyze_code(&self, content: &str, file_path: &Path, deadline: Option<Instant>) -> Result<LlmAnalysis> {
         info!("Analyzing code with LLM: {}", file_path.display());
         
+        // Only analyses the model made are cached, so there's nothing to
+        // find without one
+        let cache = self.cache.as_ref().filter(|_| self.llm_available());
+        if let Some(cache) = cache {
+            if let Some(analysis) = cache.get(&self.model, content).await {
+                debug!("Reusing the cached LLM analysis of {}", file_path.display());
+                return Ok(analysis);
+            }
+        }
+        
         // Static analysis first
-        let metrics = self.calculate_code_metrics(content);
+        let metrics = profiling::measure("metrics", || self.calculate_code_metrics(content, file_path));
+        
+        let past_deadline = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        let mut deadline_fallback = self.llm_available() && past_deadline;
         
         // Try local LLM first, fallback to static analysis
-        let ai_suggestions = if self.local_model_available {
-            self.get_ai_suggestions(content, file_path).await.unwrap_or_else(|_| {
-                warn!("Local LLM failed, using static analysis");
-                self.get_static_suggestions(content, file_path)
-            })
+        let (ai_suggestions, chunks, from_llm) = if self.llm_available() && !past_deadline {
+            let request = profiling::measure_async("llm wait", self.get_ai_suggestions(content, file_path));
+            let result = match deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline.into(), request).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        deadline_fallback = true;
+                        Err(anyhow::anyhow!("run deadline reached"))
+                    }
+                },
+                None => request.await,
+            };
+            
+            match result {
+                Ok((suggestions, chunks)) => (suggestions, chunks, true),
+                Err(e) => {
+                    warn!("Local LLM failed ({}), using static analysis", e);
+                    (self.get_static_suggestions(content, file_path), 0, false)
+                }
+            }
         } else {
-            self.get_static_suggestions(content, file_path)
+            (self.get_static_suggestions(content, file_path), 0, false)
         };
         
+        if deadline_fallback {
+            info!("Run deadline passed, {} gets static suggestions only", file_path.display());
+        }
+        
         let refactoring_suggestions = self.generate_refactoring_suggestions(content, &metrics);
         
         let complexity_score = self.calculate_complexity_score(&metrics);
         let maintainability_score = self.calculate_maintainability_score(&metrics);
         let security_score = self.calculate_security_score(content);
         
-        Ok(LlmAnalysis {
+        let analysis = LlmAnalysis {
             complexity_score,
             maintainability_score,
             security_score,
             ai_suggestions,
             code_quality_metrics: metrics,
             refactoring_suggestions,
-        })
+            deadline_fallback,
+            chunks,
+        };
+        if let Some(cache) = cache.filter(|_| from_llm) {
+            if let Err(e) = cache.put(&self.model, content, &analysis).await {
+                warn!("Failed to cache the LLM analysis of {}: {:#}", file_path.display(), e);
+            }
+        }
+        Ok(analysis)
     }
     
-    async fn get_ai_suggestions(&self, content: &str, file_path: &Path) -> Result<Vec<String>> {
-        let prompt = format!(
-            "Analyze this {} code and provide specific improvement suggestions:\n\n{}\n\nProvide 3-5 specific, actionable suggestions for improving code quality, performance, and maintainability.",
-            file_path.extension().and_then(|s| s.to_str()).unwrap_or("unknown"),
-            content
-        );
+    /// Suggestions and the number of chunks the file was split into, 0 if
+    /// it fit in one request.
+    async fn get_ai_suggestions(&self, content: &str, file_path: &Path) -> Result<(Vec<String>, usize)> {
+        let language = file_path.extension().and_then(|s| s.to_str()).unwrap_or("unknown");
         
-        let request_body = serde_json::json!({
-            "model": "phi-3-mini-instruct",
+        let content_tokens = self.config.content_tokens();
+        if chunking::estimate_tokens(content) <= content_tokens {
+            let suggestions = self.suggest(&self.config.render_prompt(language, content)).await?;
+            return Ok((suggestions, 0));
+        }
+        
+        let chunks = chunking::split(content, content_tokens, self.config.chunk_overlap_lines);
+        info!("{} exceeds the LLM context, analyzing it in {} chunks", file_path.display(), chunks.len());
+        
+        // A failed chunk costs its suggestions, not the whole file's
+        let mut per_chunk: Vec<(&Chunk, Vec<String>)> = Vec::new();
+        let mut last_error = None;
+        for chunk in &chunks {
+            match self.suggest(&self.config.render_prompt(language, &chunk.numbered)).await {
+                Ok(suggestions) => per_chunk.push((chunk, suggestions)),
+                Err(e) => {
+                    warn!("LLM failed on lines {}-{} of {}: {}", chunk.first_line, chunk.last_line, file_path.display(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        
+        if per_chunk.is_empty() {
+            return Err(last_error.unwrap_or_else(|| anyhow::anyhow!("no chunks to analyze")));
+        }
+        Ok((chunking::merge(per_chunk), chunks.len()))
+    }
+    
+    /// Suggestions for the file as the model writes them, one item each.
+    /// Files too big for the context go chunk by chunk, each suggestion
+    /// tagged with its chunk's line range and repeats dropped, like the
+    /// buffered path. A connection lost mid-response ends the stream with
+    /// an error item rather than just stopping.
+    pub async fn stream_ai_suggestions<'a>(&'a self, content: &str, file_path: &Path) -> impl Stream<Item = Result<String>> + 'a {
+        let language = file_path.extension().and_then(|s| s.to_str()).unwrap_or("unknown");
+        
+        let content_tokens = self.config.content_tokens();
+        let prompts: Vec<(String, String)> = if chunking::estimate_tokens(content) <= content_tokens {
+            vec![(String::new(), self.config.render_prompt(language, content))]
+        } else {
+            chunking::split(content, content_tokens, self.config.chunk_overlap_lines)
+                .into_iter()
+                .map(|chunk| {
+                    let tag = format!("Lines {}-{}: ", chunk.first_line, chunk.last_line);
+                    (tag, self.config.render_prompt(language, &chunk.numbered))
+                })
+                .collect()
+        };
+        
+        let mut seen = HashSet::new();
+        stream::iter(prompts)
+            .flat_map(move |(tag, prompt)| {
+                self.stream_suggestions(prompt)
+                    .map(move |suggestion| suggestion.map(|suggestion| (tag.clone(), suggestion)))
+            })
+            .filter_map(move |item| {
+                let item = match item {
+                    Ok((tag, suggestion)) => {
+                        let key = suggestion.to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ");
+                        seen.insert(key).then(|| Ok(format!("{}{}", tag, suggestion)))
+                    }
+                    Err(e) => Some(Err(e)),
+                };
+                future::ready(item)
+            })
+    }
+    
+    async fn suggest(&self, prompt: &str) -> Result<Vec<String>> {
+        self.stream_suggestions(prompt.to_string()).try_collect().await
+    }
+    
+    /// Suggestions in the answer to `prompt`, as each line completes.
+    fn stream_suggestions(&self, prompt: String) -> BoxStream<'_, Result<String>> {
+        let fragments = match (self.config.backend, &self.worker) {
+            (LlmBackend::Mock, _) => stream::once(async move { Ok(self.generate_mock().await) }).boxed(),
+            (_, Some(worker)) => stream::once(async move { worker.generate(&prompt).await }).boxed(),
+            (_, None) => stream::once(async move { self.generate_http(&prompt).await })
+                .try_flatten()
+                .boxed(),
+        };
+        
+        lines(fragments.map_ok(String::into_bytes))
+            .filter_map(|line| future::ready(match line {
+                Ok(line) => parse_suggestion(&line).map(Ok),
+                Err(e) => Some(Err(e)),
+            }))
+            .take(self.config.max_suggestions)
+            .boxed()
+    }
+    
+    async fn generate_mock(&self) -> String {
+        tokio::time::sleep(std::time::Duration::from_millis(self.config.mock_latency_ms)).await;
+        "- Consider extracting long functions into smaller helpers".to_string()
+    }
+    
+    /// Pieces of the answer to `prompt` as they arrive. Only getting the
+    /// response is retried; once it's streaming, a failure is final.
+    async fn generate_http(&self, prompt: &str) -> Result<BoxStream<'_, Result<String>>> {
+        let mut attempt = 1;
+        let response = loop {
+            let (error, retry) = match self.send_generate(prompt).await {
+                Ok(response) if response.status().is_success() => break response,
+                Ok(response) => {
+                    let status = response.status();
+                    let body = response.text().await.unwrap_or_default();
+                    // A model still loading answers 503
+                    let retry = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                    (anyhow::anyhow!("LLM request failed with {}: {}", status, self.provider.scrub(&body)), retry)
+                }
+                Err(e) => {
+                    let retry = e.is_timeout() || e.is_connect();
+                    (anyhow::Error::from(e), retry)
+                }
+            };
+            
+            if !retry || attempt >= self.config.max_attempts {
+                return Err(error);
+            }
+            let delay = self.config.retry_delay(attempt);
+            debug!("LLM attempt {}/{} failed ({}), retrying in {:?}", attempt, self.config.max_attempts, error, delay);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
+        
+        let body = response.bytes_stream()
+            .map(|bytes| bytes.map(|bytes| bytes.to_vec()).context("LLM connection lost mid-response"));
+        let provider = &self.provider;
+        // Each line of the body is one event; stop at the one that says
+        // the answer is complete, and treat the body ending first as an error
+        let fragments = stream::unfold((lines(body), false), move |(mut events, finished)| async move {
+            if finished {
+                return None;
+            }
+            loop {
+                let event = match events.next().await {
+                    Some(Ok(event)) => event,
+                    Some(Err(e)) => return Some((Err(e), (events, true))),
+                    None => return Some((Err(anyhow::anyhow!("LLM response ended before it was complete")), (events, true))),
+                };
+                match provider.parse_stream_event(&event) {
+                    Ok((Some(fragment), done)) => return Some((Ok(fragment), (events, done))),
+                    Ok((None, true)) => return None,
+                    Ok((None, false)) => continue,
+                    Err(e) => return Some((Err(e), (events, true))),
+                }
+            }
+        });
+        
+        Ok(fragments.boxed())
+    }
+    
+    async fn send_generate(&self, prompt: &str) -> reqwest::Result<reqwest::Response> {
+        let request = match &self.provider {
+            LlmProvider::Ollama => self.ollama_request(prompt),
+            LlmProvider::OpenAI { api_key, base_url } => self.openai_request(prompt, api_key, base_url),
+        };
+        let request = match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        };
+        request.send().await
+    }
+    
+    fn ollama_request(&self, prompt: &str) -> reqwest::RequestBuilder {
+        let mut request_body = serde_json::json!({
+            "model": self.model,
             "prompt": prompt,
-            "stream": false,
+            "stream": true,
             "options": {
-                "temperature": 0.3,
+                "temperature": self.temperature,
                 "top_p": 0.9,
-                "max_tokens": 500
+                "max_tokens": RESPONSE_TOKENS
             }
         });
         
-        let response = self.client
-            .post(&format!("{}/api/generate", self.model_endpoint))
+        if let Some(seed) = self.seed {
+            request_body["options"]["seed"] = serde_json::json!(seed);
+        }
+        
+        self.client
+            .post(format!("{}/api/generate", self.model_endpoint))
             .json(&request_body)
-            .send()
-            .await?;
+    }
+    
+    fn openai_request(&self, prompt: &str, api_key: &str, base_url: &str) -> reqwest::RequestBuilder {
+        let mut request_body = serde_json::json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": CHAT_SYSTEM_PROMPT },
+                { "role": "user", "content": prompt }
+            ],
+            "stream": true,
+            "temperature": self.temperature,
+            "top_p": 0.9,
+            "max_tokens": RESPONSE_TOKENS
+        });
         
-        if response.status().is_success() {
-            let response_json: serde_json::Value = response.json().await?;
-            let response_text = response_json["response"].as_str().unwrap_or("");
-            
-            // Parse suggestions from response
-            let suggestions: Vec<String> = response_text
-                .lines()
-                .filter(|line| line.trim().starts_with('-') || line.trim().starts_with('*'))
-                .map(|line| line.trim_start_matches('-').trim_start_matches('*').trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-            
-            Ok(suggestions)
-        } else {
-            Err(anyhow::anyhow!("LLM request failed"))
+        if let Some(seed) = self.seed {
+            request_body["seed"] = serde_json::json!(seed);
         }
+        
+        self.client
+            .post(LlmProvider::openai_url(base_url, "chat/completions"))
+            .bearer_auth(api_key)
+            .json(&request_body)
     }
     
     fn get_static_suggestions(&self, content: &str, file_path: &Path) -> Vec<String> {
@@ -187,13 +801,12 @@ impl LlmAgent {
         suggestions
     }
     
-    fn calculate_code_metrics(&self, content: &str) -> CodeQualityMetrics {
+    fn calculate_code_metrics(&self, content: &str, file_path: &Path) -> CodeQualityMetrics {
         let lines: Vec<&str> = content.lines().collect();
         let lines_of_code = lines.len();
         
-        let comment_lines = lines.iter()
-            .filter(|line| line.trim().starts_with("//") || line.trim().starts_with("/*") || line.trim().starts_with("*"))
-            .count();
+        let extension = file_path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        let comment_lines = comments::count_comment_lines(content, extension);
         
         let comment_ratio = if lines_of_code > 0 {
             comment_lines as f32 / lines_of_code as f32
@@ -201,7 +814,26 @@ impl LlmAgent {
             0.0
         };
         
-        let function_count = content.matches("fn ").count() + content.matches("def ").count() + content.matches("function ").count();
+        // Rust that parses is measured per function; anything else, or a
+        // partial file, falls back to counting keywords
+        let is_rust = file_path.extension().is_some_and(|ext| ext == "rs");
+        let (function_count, cyclomatic_complexity, mut functions) = match is_rust.then(|| rust_complexity::analyze(content)) {
+            Some(Ok(ast)) => (ast.functions.len(), ast.cyclomatic_complexity(), ast.functions),
+            _ => {
+                let function_count = content.matches("fn ").count() + content.matches("def ").count() + content.matches("function ").count();
+                
+                // Simple cyclomatic complexity estimation
+                let complexity_indicators = content.matches("if ").count() + 
+                                          content.matches("for ").count() + 
+                                          content.matches("while ").count() + 
+                                          content.matches("match ").count() + 
+                                          content.matches("&&").count() + 
+                                          content.matches("||").count();
+                
+                (function_count, 1.0 + complexity_indicators as f32, Vec::new())
+            }
+        };
+        functions.sort_by_key(|function| std::cmp::Reverse(function.complexity));
         
         let average_function_length = if function_count > 0 {
             lines_of_code as f32 / function_count as f32
@@ -209,27 +841,18 @@ impl LlmAgent {
             0.0
         };
         
-        // Simple cyclomatic complexity estimation
-        let complexity_indicators = content.matches("if ").count() + 
-                                  content.matches("for ").count() + 
-                                  content.matches("while ").count() + 
-                                  content.matches("match ").count() + 
-                                  content.matches("&&").count() + 
-                                  content.matches("||").count();
-        
-        let cyclomatic_complexity = 1.0 + complexity_indicators as f32;
-        
         CodeQualityMetrics {
             cyclomatic_complexity,
             lines_of_code,
             comment_ratio,
             function_count,
             average_function_length,
+            functions,
         }
     }
     
     fn calculate_complexity_score(&self, metrics: &CodeQualityMetrics) -> f32 {
-        let mut score = 1.0;
+        let mut score: f32 = 1.0;
         
         // Penalize high cyclomatic complexity
         if metrics.cyclomatic_complexity > 10.0 {
@@ -252,7 +875,7 @@ impl LlmAgent {
     }
     
     fn calculate_maintainability_score(&self, metrics: &CodeQualityMetrics) -> f32 {
-        let mut score = 1.0;
+        let mut score: f32 = 1.0;
         
         // Penalize very large files
         if metrics.lines_of_code > 500 {
@@ -275,7 +898,7 @@ impl LlmAgent {
     }
     
     fn calculate_security_score(&self, content: &str) -> f32 {
-        let mut score = 1.0;
+        let mut score: f32 = 1.0;
         
         // Security issues to check
         let security_patterns = [
@@ -332,4 +955,315 @@ impl LlmAgent {
         
         suggestions
     }
-} 
\ No newline at end of file
+} 
+
+/// A suggestion from one line of model output: a `-` or `*` bullet.
+fn parse_suggestion(line: &str) -> Option<String> {
+    let line = line.trim();
+    if !line.starts_with('-') && !line.starts_with('*') {
+        return None;
+    }
+    let suggestion = line.trim_start_matches('-').trim_start_matches('*').trim();
+    (!suggestion.is_empty()).then(|| redact::stored(suggestion).into_owned())
+}
+
+/// Regroups byte pieces into lines, wherever the pieces were cut. The
+/// last line needn't end in a newline; an error ends the stream, dropping
+/// any unfinished line.
+fn lines<'a>(pieces: impl Stream<Item = Result<Vec<u8>>> + Send + 'a) -> BoxStream<'a, Result<String>> {
+    stream::unfold((pieces.boxed(), Vec::new(), false), |(mut pieces, mut buffer, finished)| async move {
+        if finished {
+            return None;
+        }
+        loop {
+            if let Some(newline) = buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=newline).collect();
+                let line = String::from_utf8_lossy(&line[..newline]).into_owned();
+                return Some((Ok(line), (pieces, buffer, false)));
+            }
+            match pieces.next().await {
+                Some(Ok(piece)) => buffer.extend_from_slice(&piece),
+                Some(Err(e)) => return Some((Err(e), (pieces, buffer, true))),
+                None if buffer.is_empty() => return None,
+                None => {
+                    let line = String::from_utf8_lossy(&buffer).into_owned();
+                    return Some((Ok(line), (pieces, Vec::new(), true)));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn prompt_templates_fill_known_placeholders_and_reject_unknown_ones() {
+        let config = LlmConfig {
+            prompt_template: "Review {language} for {max_suggestions} fixes; reply as {\"fixes\": []}:\n{content}".to_string(),
+            max_suggestions: 2,
+            ..LlmConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        // Placeholders in the code itself are left alone
+        assert_eq!(
+            config.render_prompt("Rust", "let s = \"{language}\";"),
+            "Review Rust for 2 fixes; reply as {\"fixes\": []}:\nlet s = \"{language}\";"
+        );
+        
+        let config = LlmConfig { prompt_template: "Review {lang}:\n{content}\n{file_name}".to_string(), ..LlmConfig::default() };
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "LLM prompt template has unknown placeholders: {lang}, {file_name} (supported: {language}, {content}, {max_suggestions})"
+        );
+        
+        let config = LlmConfig { prompt_template: "Review {language}".to_string(), ..LlmConfig::default() };
+        assert!(config.validate().unwrap_err().to_string().contains("missing required placeholders: {content}"));
+    }
+    
+    #[tokio::test]
+    async fn llm_suggestions_come_from_ollama_or_openai() {
+        use wiremock::matchers::{bearer_token, body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let vars = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+        };
+        assert_eq!(LlmProvider::from_vars(vars(&[])).unwrap(), LlmProvider::Ollama);
+        assert!(LlmProvider::from_vars(vars(&[("LLM_PROVIDER", "openai")])).is_err());
+        let provider = LlmProvider::from_vars(vars(&[("LLM_PROVIDER", "OpenAI"), ("OPENAI_API_KEY", "sk-test-key")])).unwrap();
+        assert_eq!(provider, LlmProvider::OpenAI {
+            api_key: "sk-test-key".to_string(),
+            base_url: LlmProvider::DEFAULT_OPENAI_BASE_URL.to_string(),
+        });
+        assert!(!format!("{:?}", provider).contains("sk-test-key"));
+        
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "models": [] })))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/api/generate"))
+            .and(body_partial_json(serde_json::json!({ "options": { "temperature": 0.5, "max_tokens": RESPONSE_TOKENS } })))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"response\": \"- Rename x\", \"done\": true}\n"))
+            .mount(&server).await;
+        Mock::given(method("GET")).and(path("/v1/models")).and(bearer_token("sk-test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "data": [] })))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/v1/chat/completions")).and(bearer_token("sk-test-key"))
+            .and(body_partial_json(serde_json::json!({
+                "stream": true,
+                "temperature": 0.5,
+                "max_tokens": RESPONSE_TOKENS,
+                "messages": [{ "role": "system" }, { "role": "user" }],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(concat!(
+                "data: {\"choices\": [{\"delta\": {\"role\": \"assistant\"}}]}\n\n",
+                "data: {\"choices\": [{\"delta\": {\"content\": \"Sure:\\n- Add a doc comment\\n- Handle\"}}]}\n\n",
+                "data: {\"choices\": [{\"delta\": {\"content\": \" the error\"}}]}\n\n",
+                "data: [DONE]\n\n",
+            ), "text/event-stream"))
+            .mount(&server).await;
+        
+        // Sampling settings are sent with every request
+        let mut ollama = LlmAgent::new(LlmAgentConfig::ollama(server.uri())).await;
+        ollama.set_temperature(0.5);
+        let analysis = ollama.analyze_code("fn main() {}\n", Path::new("main.rs"), None).await.unwrap();
+        assert_eq!(analysis.ai_suggestions, vec!["Rename x"]);
+        
+        let openai = LlmProvider::OpenAI { api_key: "sk-test-key".to_string(), base_url: format!("{}/v1/", server.uri()) };
+        let mut openai = LlmAgent::new(LlmAgentConfig { provider: openai, endpoint: "http://unused".to_string(), model: None }).await;
+        openai.set_temperature(0.5);
+        let analysis = openai.analyze_code("fn main() {}\n", Path::new("main.rs"), None).await.unwrap();
+        assert_eq!(analysis.ai_suggestions, vec!["Add a doc comment", "Handle the error"]);
+    }
+    
+    #[tokio::test]
+    async fn llm_suggestions_stream_as_lines_complete() {
+        use futures::StreamExt;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server).await;
+        // Lines split across events, as tokens arrive
+        Mock::given(method("POST")).and(path("/api/generate")).and(body_partial_json(serde_json::json!({ "prompt": "fn a() {}\n" })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(concat!(
+                "{\"response\": \"- Add\", \"done\": false}\n",
+                "{\"response\": \" docs\\n- Use\", \"done\": false}\n",
+                "{\"response\": \" const\\n\", \"done\": false}\n",
+                "{\"response\": \"\", \"done\": true}\n",
+            )))
+            .mount(&server).await;
+        // Cut off before Ollama said it was done
+        Mock::given(method("POST")).and(path("/api/generate")).and(body_partial_json(serde_json::json!({ "prompt": "fn b() {}\n" })))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"response\": \"- Add docs\\n- Use\", \"done\": false}\n"))
+            .mount(&server).await;
+        
+        let mut agent = LlmAgent::new(LlmAgentConfig::ollama(server.uri())).await;
+        agent.set_config(LlmConfig { prompt_template: "{content}".to_string(), ..Default::default() });
+        
+        let suggestions: Vec<String> = agent.stream_ai_suggestions("fn a() {}\n", Path::new("a.rs")).await
+            .map(|suggestion| suggestion.unwrap())
+            .collect()
+            .await;
+        assert_eq!(suggestions, vec!["Add docs", "Use const"]);
+        
+        let items: Vec<_> = agent.stream_ai_suggestions("fn b() {}\n", Path::new("b.rs")).await.collect().await;
+        // The unfinished last line is dropped in favor of the error
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap(), "Add docs");
+        assert!(items[1].as_ref().unwrap_err().to_string().contains("ended before it was complete"));
+    }
+    
+    #[tokio::test]
+    async fn llm_requests_retry_while_the_model_loads() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/api/generate")).and(body_partial_json(serde_json::json!({ "prompt": "fn a() {}\n" })))
+            .respond_with(ResponseTemplate::new(503).set_body_string("model is loading"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/api/generate")).and(body_partial_json(serde_json::json!({ "prompt": "fn a() {}\n" })))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"response\": \"- Add docs\", \"done\": true}\n"))
+            .expect(1)
+            .mount(&server).await;
+        let mut agent = LlmAgent::new(LlmAgentConfig::ollama(server.uri())).await;
+        agent.set_config(LlmConfig { prompt_template: "{content}".to_string(), retry_backoff_ms: 1, ..LlmConfig::default() });
+        
+        let analysis = agent.analyze_code("fn a() {}\n", Path::new("a.rs"), None).await.unwrap();
+        assert_eq!(analysis.ai_suggestions, vec!["Add docs"]);
+        server.verify().await;
+    }
+    
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server).await;
+        let mut agent = LlmAgent::new(LlmAgentConfig::ollama(server.uri())).await;
+        agent.set_config(LlmConfig { prompt_template: "{content}".to_string(), retry_backoff_ms: 1, ..LlmConfig::default() });
+        
+        for status in [400, 401, 404] {
+            let generate = Mock::given(method("POST")).and(path("/api/generate"))
+                .respond_with(ResponseTemplate::new(status).set_body_string("rejected"))
+                .expect(1)
+                .mount_as_scoped(&server).await;
+            
+            // Falls back to static suggestions after the one attempt
+            agent.analyze_code("fn a() {}\n", Path::new("a.rs"), None).await.unwrap();
+            drop(generate);
+        }
+    }
+    
+    #[tokio::test]
+    async fn unchanged_files_are_answered_from_the_llm_cache() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server).await;
+        let generate = Mock::given(method("POST")).and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"response\": \"- Add docs\", \"done\": true}\n"));
+        
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join(crate::llm_cache::DIR_NAME);
+        let mut agent = LlmAgent::new(LlmAgentConfig::ollama(server.uri())).await;
+        agent.set_cache(Some(LlmCache::new(cache_dir.clone())));
+        
+        let first = {
+            let _guard = generate.expect(1).mount_as_scoped(&server).await;
+            agent.analyze_code("fn a() {}\n", Path::new("a.rs"), None).await.unwrap()
+        };
+        
+        // No HTTP calls for the same contents, even under another path
+        let guard = Mock::given(method("POST")).and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount_as_scoped(&server).await;
+        let second = agent.analyze_code("fn a() {}\n", Path::new("b.rs"), None).await.unwrap();
+        assert_eq!(second.ai_suggestions, first.ai_suggestions);
+        assert_eq!(second.ai_suggestions, vec!["Add docs"]);
+        drop(guard);
+        
+        // Another model doesn't reuse the first one's analysis
+        agent.set_config(LlmConfig { model: "other".to_string(), ..LlmConfig::default() });
+        let generate = Mock::given(method("POST")).and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"response\": \"- Add tests\", \"done\": true}\n"))
+            .expect(1)
+            .mount_as_scoped(&server).await;
+        let other = agent.analyze_code("fn a() {}\n", Path::new("a.rs"), None).await.unwrap();
+        assert_eq!(other.ai_suggestions, vec!["Add tests"]);
+        drop(generate);
+        
+        agent.clear_cache().await.unwrap();
+        assert!(!cache_dir.exists());
+    }
+    
+    #[tokio::test]
+    async fn llm_requests_carry_the_configured_model() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server).await;
+        for model in ["qwen2.5-coder", "llama3.1"] {
+            Mock::given(method("POST")).and(path("/api/generate")).and(body_partial_json(serde_json::json!({ "model": model })))
+                .respond_with(ResponseTemplate::new(200).set_body_string(format!("{{\"response\": \"- Ask {}\", \"done\": true}}\n", model)))
+                .expect(1)
+                .mount(&server).await;
+        }
+        let llama = LlmConfig { model: "llama3.1".to_string(), ..LlmConfig::default() };
+        
+        // LLM_MODEL wins over the model in .devagent.toml
+        let uri = server.uri();
+        let vars = |name: &str| match name {
+            "LLM_ENDPOINT" => Some(uri.clone()),
+            "LLM_MODEL" => Some(" qwen2.5-coder ".to_string()),
+            _ => None,
+        };
+        let mut agent = LlmAgent::new(LlmAgentConfig::from_vars(vars).unwrap()).await;
+        agent.set_config(llama.clone());
+        let analysis = agent.analyze_code("fn a() {}\n", Path::new("a.rs"), None).await.unwrap();
+        assert_eq!(analysis.ai_suggestions, vec!["Ask qwen2.5-coder"]);
+        
+        let mut agent = LlmAgent::new(LlmAgentConfig::ollama(server.uri())).await;
+        agent.set_config(llama);
+        let analysis = agent.analyze_code("fn a() {}\n", Path::new("a.rs"), None).await.unwrap();
+        assert_eq!(analysis.ai_suggestions, vec!["Ask llama3.1"]);
+        server.verify().await;
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn mock_llm_requests_run_concurrently() {
+        let mut agent = LlmAgent::new(LlmAgentConfig::ollama(LlmAgentConfig::DEFAULT_ENDPOINT)).await;
+        agent.set_config(LlmConfig { backend: LlmBackend::Mock, mock_latency_ms: 100, ..LlmConfig::default() });
+        
+        let started = tokio::time::Instant::now();
+        let analyses = futures::future::join_all((0..4).map(|_| agent.analyze_code("fn main() {}\n", Path::new("main.rs"), None))).await;
+        let elapsed = started.elapsed();
+        
+        for analysis in analyses {
+            assert_eq!(analysis.unwrap().ai_suggestions, vec!["Consider extracting long functions into smaller helpers"]);
+        }
+        // In virtual time; four sequential requests would take 400ms
+        assert_eq!(elapsed, std::time::Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::llm_agent::LlmAnalysis;
+
+/// Kept under the report root between runs.
+pub const DIR_NAME: &str = ".devagent-cache";
+
+/// One cached analysis, stored as `<sha256 of content>.json`.
+#[derive(Serialize, Deserialize)]
+struct Entry<A> {
+    model: String,
+    analysis: A,
+}
+
+/// LLM analyses of file contents seen before, so re-reviewing unchanged
+/// files doesn't query the model again. An entry made with a different
+/// model is a miss and gets replaced.
+#[derive(Debug, Clone)]
+pub struct LlmCache {
+    dir: PathBuf,
+}
+
+impl LlmCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+    
+    fn entry_path(&self, content: &str) -> PathBuf {
+        self.dir.join(format!("{:x}.json", Sha256::digest(content.as_bytes())))
+    }
+    
+    /// The analysis `model` made of `content`, if there is one. Unreadable
+    /// entries count as misses.
+    pub async fn get(&self, model: &str, content: &str) -> Option<LlmAnalysis> {
+        let json = fs::read(self.entry_path(content)).await.ok()?;
+        serde_json::from_slice::<Entry<LlmAnalysis>>(&json).ok()
+            .filter(|entry| entry.model == model)
+            .map(|entry| entry.analysis)
+    }
+    
+    pub async fn put(&self, model: &str, content: &str, analysis: &LlmAnalysis) -> Result<()> {
+        let entry = Entry { model: model.to_string(), analysis };
+        fs::create_dir_all(&self.dir).await
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        
+        // Written aside and renamed, so a concurrent reader never sees half an entry
+        let path = self.entry_path(content);
+        let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&partial, serde_json::to_vec(&entry)?).await
+            .with_context(|| format!("Failed to write {}", partial.display()))?;
+        fs::rename(&partial, &path).await
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+    
+    /// Removes every entry.
+    pub async fn clear(&self) -> Result<()> {
+        match fs::remove_dir_all(&self.dir).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("Failed to clear {}", self.dir.display())),
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use tokio::time::error::Elapsed;
+use tracing::{info, warn};
+
+/// Hidden flag that starts this binary as an LLM worker.
+pub const WORKER_FLAG: &str = "--llm-worker";
+
+/// Settings for `LlmBackend::Subprocess`, the `[llm.worker]` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkerConfig {
+    /// Local model weights; the model's default source when unset
+    pub model_path: Option<PathBuf>,
+    /// Address space cap for the worker process (Unix only)
+    pub memory_limit_mb: Option<u64>,
+    /// Restarts allowed after crashes before the worker is given up on
+    pub max_restarts: u32,
+    /// How long a fresh worker may take to answer its first health check,
+    /// model loading included
+    pub startup_timeout_secs: u64,
+    /// How long one generate request may take
+    pub request_timeout_secs: u64,
+    /// Program and arguments to run instead of this binary in worker mode,
+    /// for workers written against the same stdio protocol
+    pub command: Vec<String>,
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            model_path: None,
+            memory_limit_mb: None,
+            max_restarts: 3,
+            startup_timeout_secs: 120,
+            request_timeout_secs: 60,
+            command: Vec::new(),
+        }
+    }
+}
+
+// The protocol is one JSON object per line: requests on the worker's
+// stdin, replies on its stdout. Logs go to stderr.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerRequest {
+    Ping,
+    Generate { prompt: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerReply {
+    Pong,
+    Text { text: String },
+    Error { message: String },
+}
+
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Worker {
+    async fn call(&mut self, request: &WorkerRequest, timeout: Duration) -> Result<WorkerReply> {
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        
+        let exchange = async {
+            self.stdin.write_all(line.as_bytes()).await?;
+            self.stdin.flush().await?;
+            
+            let mut reply = String::new();
+            if self.stdout.read_line(&mut reply).await? == 0 {
+                anyhow::bail!("LLM worker closed its output");
+            }
+            serde_json::from_str(&reply).context("LLM worker sent an invalid reply")
+        };
+        
+        tokio::time::timeout(timeout, exchange).await
+            .context("LLM worker did not answer in time")?
+    }
+}
+
+/// Runs LLM generation in a child process so a model crash or memory
+/// blow-up doesn't take the reviewer down. The worker is started on first
+/// use and restarted after it dies, up to `max_restarts` times.
+pub struct WorkerClient {
+    config: WorkerConfig,
+    worker: Mutex<Option<Worker>>,
+    starts: Mutex<u32>,
+}
+
+impl WorkerClient {
+    pub fn new(config: WorkerConfig) -> Self {
+        Self {
+            config,
+            worker: Mutex::new(None),
+            starts: Mutex::new(0),
+        }
+    }
+    
+    pub async fn generate(&self, prompt: &str) -> Result<String> {
+        let request = WorkerRequest::Generate { prompt: prompt.to_string() };
+        match self.call(&request, Duration::from_secs(self.config.request_timeout_secs)).await? {
+            WorkerReply::Text { text } => Ok(text),
+            WorkerReply::Error { message } => Err(anyhow::anyhow!("LLM worker error: {}", message)),
+            WorkerReply::Pong => Err(anyhow::anyhow!("LLM worker answered a generate request with pong")),
+        }
+    }
+    
+    /// Starts the worker if needed and checks that it answers a ping.
+    pub async fn health_check(&self) -> Result<()> {
+        match self.call(&WorkerRequest::Ping, Duration::from_secs(self.config.startup_timeout_secs)).await? {
+            WorkerReply::Pong => Ok(()),
+            other => Err(anyhow::anyhow!("LLM worker answered a ping with {:?}", other)),
+        }
+    }
+    
+    async fn call(&self, request: &WorkerRequest, timeout: Duration) -> Result<WorkerReply> {
+        let mut slot = self.worker.lock().await;
+        let reused = slot.is_some();
+        
+        match self.call_once(&mut slot, request, timeout).await {
+            // A worker that died since the last call may only show it once
+            // written to, so try again on a fresh one. A request that timed
+            // out would likely time out again and isn't retried.
+            Err(e) if reused && e.downcast_ref::<Elapsed>().is_none() => {
+                warn!("LLM worker failed ({:#}), restarting it", e);
+                self.call_once(&mut slot, request, timeout).await
+            }
+            result => result,
+        }
+    }
+    
+    async fn call_once(&self, slot: &mut Option<Worker>, request: &WorkerRequest, timeout: Duration) -> Result<WorkerReply> {
+        let worker = self.ensure_running(slot).await?;
+        
+        match worker.call(request, timeout).await {
+            Ok(reply) => Ok(reply),
+            Err(e) => {
+                // Dead or stuck; the next call starts a fresh worker
+                if let Some(mut worker) = slot.take() {
+                    let _ = worker.child.kill().await;
+                }
+                Err(e)
+            }
+        }
+    }
+    
+    async fn ensure_running<'a>(&self, slot: &'a mut Option<Worker>) -> Result<&'a mut Worker> {
+        if let Some(worker) = slot.as_mut() {
+            if let Some(status) = worker.child.try_wait()? {
+                warn!("LLM worker exited ({})", status);
+                *slot = None;
+            }
+        }
+        
+        if slot.is_none() {
+            let mut worker = self.spawn().await?;
+            
+            let startup = Duration::from_secs(self.config.startup_timeout_secs);
+            match worker.call(&WorkerRequest::Ping, startup).await {
+                Ok(WorkerReply::Pong) => {}
+                Ok(other) => anyhow::bail!("LLM worker answered its health check with {:?}", other),
+                Err(e) => {
+                    let _ = worker.child.kill().await;
+                    return Err(e.context("LLM worker failed its startup health check"));
+                }
+            }
+            
+            *slot = Some(worker);
+        }
+        
+        Ok(slot.as_mut().expect("worker was just started"))
+    }
+    
+    async fn spawn(&self) -> Result<Worker> {
+        let mut started = self.starts.lock().await;
+        // The first start isn't a restart
+        if *started > self.config.max_restarts {
+            anyhow::bail!("LLM worker crashed {} times; not restarting it", self.config.max_restarts);
+        }
+        *started += 1;
+        
+        let mut command = match self.config.command.split_first() {
+            Some((program, args)) => {
+                let mut command = Command::new(program);
+                command.args(args);
+                command
+            }
+            None => {
+                let mut command = Command::new(std::env::current_exe().context("Failed to locate the devagent binary")?);
+                command.arg(WORKER_FLAG).arg(serde_json::to_string(&self.config)?);
+                command
+            }
+        };
+        
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start the LLM worker")?;
+        
+        info!("Started LLM worker (pid {:?})", child.id());
+        
+        Ok(Worker {
+            stdin: child.stdin.take().context("LLM worker has no stdin")?,
+            stdout: BufReader::new(child.stdout.take().context("LLM worker has no stdout")?),
+            child,
+        })
+    }
+}
+
+/// Worker side of the protocol: answers requests from stdin with
+/// `generate` until stdin closes.
+pub async fn serve<G, F>(generate: G) -> Result<()>
+where
+    G: Fn(String) -> F,
+    F: Future<Output = Result<String>>,
+{
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    
+    while let Some(line) = lines.next_line().await? {
+        let reply = match serde_json::from_str::<WorkerRequest>(&line) {
+            Ok(WorkerRequest::Ping) => WorkerReply::Pong,
+            Ok(WorkerRequest::Generate { prompt }) => match generate(prompt).await {
+                Ok(text) => WorkerReply::Text { text },
+                Err(e) => WorkerReply::Error { message: format!("{:#}", e) },
+            },
+            Err(e) => WorkerReply::Error { message: format!("invalid request: {}", e) },
+        };
+        
+        let mut line = serde_json::to_string(&reply)?;
+        line.push('\n');
+        stdout.write_all(line.as_bytes()).await?;
+        stdout.flush().await?;
+    }
+    
+    Ok(())
+}
+
+/// Caps this process's address space, so a runaway model fails its own
+/// allocation instead of pushing the host into swap.
+#[cfg(unix)]
+pub fn limit_memory(megabytes: u64) -> Result<()> {
+    let bytes = megabytes.saturating_mul(1024 * 1024) as libc::rlim_t;
+    let limit = libc::rlimit { rlim_cur: bytes, rlim_max: bytes };
+    // SAFETY: setrlimit only reads the struct passed by reference
+    if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error()).context("Failed to set the LLM worker memory limit");
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn limit_memory(_megabytes: u64) -> Result<()> {
+    warn!("memory_limit_mb is only enforced on Unix");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn llm_worker_is_restarted_after_it_crashes() {
+        // Answers health checks, then serves one generate request and exits
+        let script = r#"while read -r line; do
+            case "$line" in
+                *'"type":"ping"'*) echo '{"type":"pong"}' ;;
+                *) echo '{"type":"text","text":"- Handle the error"}'; exit 0 ;;
+            esac
+        done"#;
+        let worker = WorkerClient::new(WorkerConfig {
+            max_restarts: 1,
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            ..WorkerConfig::default()
+        });
+        
+        worker.health_check().await.unwrap();
+        assert_eq!(worker.generate("fn main() {}").await.unwrap(), "- Handle the error");
+        // The first worker exited; this one is its single allowed restart
+        assert_eq!(worker.generate("fn main() {}").await.unwrap(), "- Handle the error");
+        
+        let err = worker.generate("fn main() {}").await.unwrap_err();
+        assert!(format!("{:#}", err).contains("not restarting"), "{:#}", err);
+    }
+}
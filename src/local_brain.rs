@@ -5,6 +5,14 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error};
 
+use crate::git_ops;
+use crate::chunking;
+use crate::json_repair;
+use crate::redact;
+use crate::safety::{SafetyPolicy, Verdict};
+use crate::shell::{self, CommandOutput};
+use crate::llm_worker::{self, WorkerConfig};
+
 // Local LLM integration
 use kalosm::language::*;
 use kalosm::*;
@@ -15,13 +23,71 @@ use agentai::mcp::*;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LocalBrainConfig {
     pub model_path: PathBuf,
+    /// Most tokens generated per decision. Responses cut off here arrive
+    /// as truncated JSON.
     pub max_tokens: usize,
+    /// Budget for earlier commands quoted in the prompt, newest first, so
+    /// long commands can't crowd out the Phi-3 context window
+    pub context_tokens: usize,
+    /// Sampling temperature, 0.0 to 2.0. Set 0.0 for a command router
+    /// that picks the same action for the same command every time.
     pub temperature: f32,
     pub gpu_enabled: bool,
     pub mcp_servers: Vec<String>,
+    /// Git operations run in the repo containing this path.
+    pub workspace: PathBuf,
+    /// Which shell commands from the model run without confirmation
+    pub safety: SafetyPolicy,
+    /// Program and arguments local commands are appended to, e.g.
+    /// `["bash", "-lc"]`; `sh -c` or `cmd /C` when unset
+    pub shell: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Default for LocalBrainConfig {
+    fn default() -> Self {
+        Self {
+            model_path: PathBuf::from("models/phi-3-mini-instruct"),
+            max_tokens: 512,
+            context_tokens: 256,
+            temperature: 0.7,
+            gpu_enabled: false,
+            mcp_servers: Vec::new(),
+            workspace: PathBuf::from("."),
+            safety: SafetyPolicy::default(),
+            shell: None,
+        }
+    }
+}
+
+impl LocalBrainConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=2.0).contains(&self.temperature) {
+            return Err(anyhow::anyhow!("Local brain temperature must be between 0.0 and 2.0, got {}", self.temperature));
+        }
+        
+        if self.max_tokens == 0 {
+            return Err(anyhow::anyhow!("Local brain max_tokens must be at least 1"));
+        }
+        
+        Ok(())
+    }
+    
+    fn sampler(&self) -> GenerationParameters {
+        let sampler = GenerationParameters::default()
+            .with_temperature(self.temperature)
+            .with_max_length(self.max_tokens as u32);
+        
+        // Ties between equally likely tokens are still broken by the RNG,
+        // so a fixed seed is what makes temperature 0 repeatable
+        if self.temperature == 0.0 {
+            sampler.with_seed(0)
+        } else {
+            sampler
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceCommand {
     pub text: String,
     pub confidence: f32,
@@ -29,15 +95,18 @@ pub struct VoiceCommand {
     pub context: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrainResponse {
     pub action: BrainAction,
     pub confidence: f32,
     pub reasoning: String,
     pub requires_cloud: bool,
+    /// What a local command printed; set once it has run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_output: Option<CommandOutput>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BrainAction {
     LocalExecution(String),
     CloudDelegation(String),
@@ -51,29 +120,53 @@ pub enum BrainAction {
     NoAction,
 }
 
+/// Entry point for `--llm-worker`: loads Phi-3 once, then answers
+/// generate requests on stdio until the parent closes the pipe.
+pub async fn serve_llm_worker(config: WorkerConfig) -> Result<()> {
+    // Before loading, so the model itself counts against the limit
+    if let Some(limit) = config.memory_limit_mb {
+        llm_worker::limit_memory(limit)?;
+    }
+    
+    let mut builder = Phi3MiniInstruct::builder();
+    if let Some(model_path) = config.model_path {
+        builder = builder.with_source(Phi3MiniInstructSource::Local(model_path));
+    }
+    let model = builder.build().await.context("LLM worker failed to load its model")?;
+    info!("LLM worker ready");
+    
+    let model = &model;
+    llm_worker::serve(move |prompt| async move {
+        Ok(model.generate_text(&prompt).await?)
+    }).await
+}
+
 pub struct LocalBrain {
     config: LocalBrainConfig,
     phi_model: Arc<Mutex<Option<Phi3MiniInstruct>>>,
     mcp_client: Arc<Mutex<MCPClient>>,
     command_history: Arc<Mutex<Vec<VoiceCommand>>>,
+    /// Action held back by the safety policy until the user confirms it
+    pending_action: Mutex<Option<BrainAction>>,
 }
 
 impl LocalBrain {
     pub async fn new(config: LocalBrainConfig) -> Result<Self> {
         info!("Initializing Local Brain with Phi-3-mini-instruct");
+        config.validate()?;
         
         // Initialize Phi-3-mini-instruct model
         let phi_model = if config.gpu_enabled {
             info!("Loading Phi-3-mini-instruct with GPU acceleration");
             let model = Phi3MiniInstruct::builder()
-                .with_source(Phi3MiniInstructSource::Local(config.model_path))
+                .with_source(Phi3MiniInstructSource::Local(config.model_path.clone()))
                 .build()
                 .await?;
             Arc::new(Mutex::new(Some(model)))
         } else {
             info!("Loading Phi-3-mini-instruct with CPU");
             let model = Phi3MiniInstruct::builder()
-                .with_source(Phi3MiniInstructSource::Local(config.model_path))
+                .with_source(Phi3MiniInstructSource::Local(config.model_path.clone()))
                 .build()
                 .await?;
             Arc::new(Mutex::new(Some(model)))
@@ -87,6 +180,7 @@ impl LocalBrain {
             phi_model,
             mcp_client,
             command_history: Arc::new(Mutex::new(Vec::new())),
+            pending_action: Mutex::new(None),
         })
     }
     
@@ -102,30 +196,46 @@ impl LocalBrain {
             }
         }
         
+        let pending = self.pending_action.lock().await.take();
+        if let Some(pending) = pending {
+            if SafetyPolicy::is_confirmation(&command.text) {
+                let mut response = BrainResponse {
+                    action: pending,
+                    confidence: 1.0,
+                    reasoning: "Confirmed by the user".to_string(),
+                    requires_cloud: false,
+                    command_output: None,
+                };
+                self.run_action(&mut response).await?;
+                return Ok(response);
+            }
+        }
+        
         // Analyze command with local brain
-        let response = self.analyze_command(&command).await?;
+        let mut response = self.analyze_command(&command).await?;
         
         // Execute action based on response
-        self.execute_action(&response).await?;
+        self.execute_action(&mut response).await?;
         
         Ok(response)
     }
     
     async fn analyze_command(&self, command: &VoiceCommand) -> Result<BrainResponse> {
-        let prompt = self.build_analysis_prompt(command);
+        let context = self.get_recent_context().await;
+        let prompt = Self::build_analysis_prompt(command, &context);
         
         let model_guard = self.phi_model.lock().await;
         if let Some(model) = &*model_guard {
-            let response = model.generate_text(&prompt).await?;
-            self.parse_brain_response(&response)
+            let response = model.generate_text(&prompt)
+                .with_sampler(self.config.sampler())
+                .await?;
+            Self::parse_brain_response(&response)
         } else {
             Err(anyhow::anyhow!("Phi-3 model not loaded"))
         }
     }
     
-    fn build_analysis_prompt(&self, command: &VoiceCommand) -> String {
-        let context = self.get_recent_context().await;
-        
+    fn build_analysis_prompt(command: &VoiceCommand, context: &str) -> String {
         format!(
             r#"You are a local AI brain that processes voice commands for a developer environment.
 
@@ -152,64 +262,104 @@ Respond in JSON format:
     
     async fn get_recent_context(&self) -> String {
         let history = self.command_history.lock().await;
-        let recent: Vec<String> = history
-            .iter()
-            .rev()
-            .take(5)
-            .map(|cmd| cmd.text.clone())
-            .collect();
-        recent.join("; ")
+        recent_context(&history, self.config.context_tokens)
     }
     
-    fn parse_brain_response(&self, response: &str) -> Result<BrainResponse> {
+    fn parse_brain_response(response: &str) -> Result<BrainResponse> {
         // Try to parse JSON response
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(response) {
+        let json = match serde_json::from_str::<serde_json::Value>(response) {
+            Ok(json) => Some(json),
+            Err(e) => {
+                // Often a truncated object: raise max_tokens if this recurs
+                warn!("Brain response is not valid JSON ({}), attempting repair. Raw response: {:?}", e, redact::log(response));
+                json_repair::parse_object(response)
+            }
+        };
+        
+        if let Some(json) = json {
             let action_str = json["action"].as_str().unwrap_or("no_action");
-            let confidence = json["confidence"].as_f64().unwrap_or(0.5) as f32;
-            let reasoning = json["reasoning"].as_str().unwrap_or("").to_string();
-            let requires_cloud = json["requires_cloud"].as_bool().unwrap_or(false);
+            let details = json["details"].as_str().unwrap_or("");
             
-            let action = match action_str {
-                "local_execution" => BrainAction::LocalExecution(json["details"].as_str().unwrap_or("").to_string()),
-                "cloud_delegation" => BrainAction::CloudDelegation(json["details"].as_str().unwrap_or("").to_string()),
-                "voice_response" => BrainAction::VoiceResponse(json["details"].as_str().unwrap_or("").to_string()),
-                "file_operation" => BrainAction::FileOperation(json["details"].as_str().unwrap_or("").to_string()),
-                "git_operation" => BrainAction::GitOperation(json["details"].as_str().unwrap_or("").to_string()),
-                "build_operation" => BrainAction::BuildOperation(json["details"].as_str().unwrap_or("").to_string()),
-                "test_operation" => BrainAction::TestOperation(json["details"].as_str().unwrap_or("").to_string()),
-                "web_search" => BrainAction::WebSearch(json["details"].as_str().unwrap_or("").to_string()),
-                "code_analysis" => BrainAction::CodeAnalysis(json["details"].as_str().unwrap_or("").to_string()),
-                _ => BrainAction::NoAction,
-            };
+            return Ok(BrainResponse {
+                action: Self::brain_action(action_str, details),
+                confidence: json["confidence"].as_f64().unwrap_or(0.5) as f32,
+                reasoning: json["reasoning"].as_str().unwrap_or("").to_string(),
+                requires_cloud: json["requires_cloud"].as_bool().unwrap_or(false),
+                command_output: None,
+            });
+        }
+        
+        // Too broken to parse, but the action may still be stated
+        if let Some(action_str) = json_repair::string_field(response, "action") {
+            let details = json_repair::string_field(response, "details").unwrap_or_default();
+            let action = Self::brain_action(&action_str, &details);
+            let requires_cloud = matches!(action, BrainAction::CloudDelegation(_));
             
-            Ok(BrainResponse {
+            return Ok(BrainResponse {
                 action,
-                confidence,
-                reasoning,
+                confidence: 0.5,
+                reasoning: json_repair::string_field(response, "reasoning").unwrap_or_else(|| "Recovered from malformed JSON".to_string()),
                 requires_cloud,
-            })
+                command_output: None,
+            });
+        }
+        
+        warn!("Brain response has no recoverable action, using keyword fallback");
+        
+        // Fallback parsing for non-JSON responses
+        let requires_cloud = response.to_lowercase().contains("cloud");
+        let action = if requires_cloud {
+            BrainAction::CloudDelegation(response.to_string())
         } else {
-            // Fallback parsing for non-JSON responses
-            let action = if response.to_lowercase().contains("cloud") {
-                BrainAction::CloudDelegation(response.to_string())
-            } else {
-                BrainAction::LocalExecution(response.to_string())
-            };
-            
-            Ok(BrainResponse {
-                action,
-                confidence: 0.7,
-                reasoning: "Fallback parsing".to_string(),
-                requires_cloud: matches!(action, BrainAction::CloudDelegation(_)),
-            })
+            BrainAction::LocalExecution(response.to_string())
+        };
+        
+        Ok(BrainResponse {
+            action,
+            confidence: 0.7,
+            reasoning: "Fallback parsing".to_string(),
+            requires_cloud,
+            command_output: None,
+        })
+    }
+    
+    fn brain_action(action: &str, details: &str) -> BrainAction {
+        let details = details.to_string();
+        match action {
+            "local_execution" => BrainAction::LocalExecution(details),
+            "cloud_delegation" => BrainAction::CloudDelegation(details),
+            "voice_response" => BrainAction::VoiceResponse(details),
+            "file_operation" => BrainAction::FileOperation(details),
+            "git_operation" => BrainAction::GitOperation(details),
+            "build_operation" => BrainAction::BuildOperation(details),
+            "test_operation" => BrainAction::TestOperation(details),
+            "web_search" => BrainAction::WebSearch(details),
+            "code_analysis" => BrainAction::CodeAnalysis(details),
+            _ => BrainAction::NoAction,
         }
     }
     
-    async fn execute_action(&self, response: &BrainResponse) -> Result<()> {
+    /// Carries out the chosen action. A command held back by the safety
+    /// policy turns the action into a request for confirmation.
+    async fn execute_action(&self, response: &mut BrainResponse) -> Result<()> {
+        if let Some(command) = command_line(&response.action) {
+            if let Verdict::Confirm(question) = self.config.safety.review(&command) {
+                warn!("Holding back command: {}", redact::log(&command));
+                *self.pending_action.lock().await = Some(response.action.clone());
+                response.action = BrainAction::VoiceResponse(question);
+                return Ok(());
+            }
+        }
+        self.run_action(response).await
+    }
+    
+    /// Carries out `response.action` without review. A local command's
+    /// output is added to `response`.
+    async fn run_action(&self, response: &mut BrainResponse) -> Result<()> {
         match &response.action {
-            BrainAction::LocalExecution(details) => {
-                info!("Executing locally: {}", details);
-                self.execute_local_command(details).await?;
+            BrainAction::LocalExecution(command) => {
+                info!("Executing locally: {}", redact::log(command));
+                response.command_output = self.run_shell_command(command).await?;
             }
             BrainAction::CloudDelegation(details) => {
                 info!("Delegating to cloud: {}", details);
@@ -224,11 +374,11 @@ Respond in JSON format:
                 self.execute_file_operation(operation).await?;
             }
             BrainAction::GitOperation(operation) => {
-                info!("Git operation: {}", operation);
+                info!("Git operation: {}", redact::log(operation));
                 self.execute_git_operation(operation).await?;
             }
             BrainAction::BuildOperation(operation) => {
-                info!("Build operation: {}", operation);
+                info!("Build operation: {}", redact::log(operation));
                 self.execute_build_operation(operation).await?;
             }
             BrainAction::TestOperation(operation) => {
@@ -251,20 +401,21 @@ Respond in JSON format:
         Ok(())
     }
     
-    async fn execute_local_command(&self, command: &str) -> Result<()> {
-        // Execute local system commands
-        let output = tokio::process::Command::new("cmd")
-            .args(&["/C", command])
-            .output()
-            .await?;
+    /// Output of `command`, or `None` in a dry run.
+    async fn run_shell_command(&self, command: &str) -> Result<Option<CommandOutput>> {
+        if self.config.safety.dry_run {
+            info!("Dry run, would execute: {}", redact::log(command));
+            return Ok(None);
+        }
         
-        if output.status.success() {
+        let output = shell::run(command, self.config.shell.as_deref()).await?;
+        if output.success {
             info!("Local command executed successfully");
         } else {
-            warn!("Local command failed: {}", String::from_utf8_lossy(&output.stderr));
+            warn!("Local command failed: {}", redact::log(&output.stderr));
         }
         
-        Ok(())
+        Ok(Some(output))
     }
     
     async fn delegate_to_cloud(&self, details: &str) -> Result<()> {
@@ -277,7 +428,7 @@ Respond in JSON format:
                 
                 // Send command to cloud LLM via MCP
                 let response = client.send_message(details).await?;
-                info!("Cloud response: {}", response);
+                info!("Cloud response: {}", redact::log(&response));
                 break;
             }
         }
@@ -299,8 +450,13 @@ Respond in JSON format:
     }
     
     async fn execute_git_operation(&self, operation: &str) -> Result<()> {
-        // Execute git commands
-        let output = tokio::process::Command::new("git")
+        if self.config.safety.dry_run {
+            info!("Dry run, would execute: git {}", redact::log(operation));
+            return Ok(());
+        }
+        
+        let root = git_ops::repo_root(&self.config.workspace).await?;
+        let output = git_ops::git(&root)
             .args(operation.split_whitespace().collect::<Vec<_>>())
             .output()
             .await?;
@@ -308,13 +464,18 @@ Respond in JSON format:
         if output.status.success() {
             info!("Git operation completed");
         } else {
-            warn!("Git operation failed: {}", String::from_utf8_lossy(&output.stderr));
+            warn!("Git operation failed: {}", redact::log(&String::from_utf8_lossy(&output.stderr)));
         }
         
         Ok(())
     }
     
     async fn execute_build_operation(&self, operation: &str) -> Result<()> {
+        if self.config.safety.dry_run {
+            info!("Dry run, would execute: cargo {}", redact::log(operation));
+            return Ok(());
+        }
+        
         // Execute build commands
         let output = tokio::process::Command::new("cargo")
             .args(operation.split_whitespace().collect::<Vec<_>>())
@@ -324,7 +485,7 @@ Respond in JSON format:
         if output.status.success() {
             info!("Build operation completed");
         } else {
-            warn!("Build operation failed: {}", String::from_utf8_lossy(&output.stderr));
+            warn!("Build operation failed: {}", redact::log(&String::from_utf8_lossy(&output.stderr)));
         }
         
         Ok(())
@@ -357,4 +518,90 @@ Respond in JSON format:
         info!("Code analysis for: {}", path);
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+/// Texts of the latest commands, newest first, as many as fit in `budget`
+/// tokens.
+fn recent_context(history: &[VoiceCommand], budget: usize) -> String {
+    let mut used = 0;
+    let recent: Vec<&str> = history
+        .iter()
+        .rev()
+        .map(|cmd| cmd.text.as_str())
+        .take_while(|text| {
+            used += chunking::estimate_tokens(text) + 1;
+            used <= budget
+        })
+        .collect();
+    recent.join("; ")
+}
+
+/// The command line `action` runs, as the safety policy reviews it;
+/// `None` for actions that run no model-chosen command.
+fn command_line(action: &BrainAction) -> Option<String> {
+    match action {
+        BrainAction::LocalExecution(command) => Some(command.clone()),
+        BrainAction::GitOperation(operation) => Some(format!("git {}", operation)),
+        BrainAction::BuildOperation(operation) => Some(format!("cargo {}", operation)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn command(text: &str) -> VoiceCommand {
+        VoiceCommand {
+            text: text.to_string(),
+            confidence: 1.0,
+            timestamp: chrono::Utc::now(),
+            context: None,
+        }
+    }
+    
+    #[test]
+    fn prompt_context_keeps_the_newest_commands_within_budget() {
+        let history = vec![
+            command(&"refactor everything ".repeat(200)),
+            command("run the tests"),
+            command("open main.rs"),
+        ];
+        
+        let context = recent_context(&history, 64);
+        assert_eq!(context, "open main.rs; run the tests");
+        
+        let prompt = LocalBrain::build_analysis_prompt(&command("build it"), &context);
+        assert!(prompt.contains("Recent context: open main.rs; run the tests"));
+        assert!(prompt.contains("Current command: \"build it\""));
+    }
+    
+    #[test]
+    fn plain_text_cloud_responses_require_the_cloud() {
+        let response = LocalBrain::parse_brain_response("I would delegate to cloud for this refactor").unwrap();
+        assert!(response.requires_cloud);
+        assert!(matches!(response.action, BrainAction::CloudDelegation(ref details) if details.contains("refactor")));
+        
+        let response = LocalBrain::parse_brain_response("just run cargo build").unwrap();
+        assert!(!response.requires_cloud);
+        assert!(matches!(response.action, BrainAction::LocalExecution(_)));
+    }
+    
+    #[test]
+    fn git_and_build_operations_are_reviewed_like_shell_commands() {
+        let policy = SafetyPolicy::default();
+        let review = |action: BrainAction| command_line(&action).map(|command| policy.review(&command));
+        
+        assert_eq!(review(BrainAction::GitOperation("status".to_string())), Some(Verdict::Run));
+        assert_eq!(review(BrainAction::BuildOperation("build --release".to_string())), Some(Verdict::Run));
+        for action in [
+            BrainAction::GitOperation("push --force".to_string()),
+            BrainAction::GitOperation("reset --hard".to_string()),
+            BrainAction::GitOperation("clean -fdx".to_string()),
+            BrainAction::BuildOperation("install --path .".to_string()),
+        ] {
+            assert!(matches!(review(action.clone()), Some(Verdict::Confirm(_))), "{:?} should need confirmation", action);
+        }
+        assert_eq!(review(BrainAction::TestOperation("all".to_string())), None);
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+use crate::code_analyzer::{Issue, IssueCategory, Severity};
+use crate::rust_attrs::is_test;
+
+// Standard library macros are as plain to read as function calls
+const STD_MACROS: [&str; 32] = [
+    "assert", "assert_eq", "assert_ne", "cfg", "column", "compile_error", "concat", "dbg", "debug_assert",
+    "debug_assert_eq", "debug_assert_ne", "env", "eprint", "eprintln", "file", "format", "format_args",
+    "include", "include_bytes", "include_str", "line", "matches", "panic", "print", "println", "stringify",
+    "todo", "unimplemented", "unreachable", "vec", "write", "writeln",
+];
+
+/// Settings for the Rust macro complexity check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MacroUsageConfig {
+    /// Flag files with more macro invocations than this per 100 lines
+    pub max_density: f32,
+    /// Files with fewer invocations than this are never flagged
+    pub min_invocations: usize,
+    /// Flag `macro_rules!` definitions longer than this many lines
+    pub max_macro_rules_lines: usize,
+    /// Macros besides the standard library's that don't count, e.g.
+    /// logging macros
+    pub ignore: Vec<String>,
+}
+
+impl Default for MacroUsageConfig {
+    fn default() -> Self {
+        Self {
+            max_density: 10.0,
+            min_invocations: 20,
+            max_macro_rules_lines: 40,
+            ignore: ["trace", "debug", "info", "warn", "error"].map(String::from).to_vec(),
+        }
+    }
+}
+
+impl MacroUsageConfig {
+    /// Macro-dense non-test code and long `macro_rules!` definitions.
+    /// Invocations are `name!(...)` in any position; attribute and derive
+    /// macros aren't counted.
+    pub fn check(&self, ast: &syn::File, lines: &[&str]) -> Vec<Issue> {
+        let mut collector = Collector::default();
+        collector.visit_file(ast);
+        let mut issues = Vec::new();
+        
+        let counted = collector.invocations.iter()
+            .filter(|name| !STD_MACROS.contains(&name.as_str()) && !self.ignore.contains(name))
+            .count();
+        let code_lines = lines.iter().filter(|line| !line.trim().is_empty()).count().max(1);
+        let density = counted as f32 * 100.0 / code_lines as f32;
+        if counted >= self.min_invocations && density > self.max_density {
+            issues.push(Issue {
+                rule: "rust/macro-density".to_string(),
+                severity: Severity::Low,
+                message: format!(
+                    "{} macro invocations in {} lines ({:.1} per 100 lines, limit {:.1}) and {} macro_rules! definitions; macro-heavy code is hard to read and debug",
+                    counted, code_lines, density, self.max_density, collector.definitions.len()
+                ),
+                line: None,
+                code: None,
+                category: IssueCategory::Maintainability,
+            });
+        }
+        
+        for definition in collector.definitions.iter().filter(|definition| definition.lines > self.max_macro_rules_lines) {
+            issues.push(Issue {
+                rule: "rust/long-macro-rules".to_string(),
+                severity: Severity::Low,
+                message: format!(
+                    "macro_rules! {} is {} lines (limit {}); consider a function, generics or a smaller macro",
+                    definition.name, definition.lines, self.max_macro_rules_lines
+                ),
+                line: Some(definition.line),
+                code: lines.get(definition.line.saturating_sub(1)).map(|line| line.to_string()),
+                category: IssueCategory::Maintainability,
+            });
+        }
+        
+        issues
+    }
+}
+
+struct Definition {
+    name: String,
+    line: usize,
+    lines: usize,
+}
+
+#[derive(Default)]
+struct Collector {
+    /// Names of macros invoked outside test code
+    invocations: Vec<String>,
+    definitions: Vec<Definition>,
+    test_depth: usize,
+}
+
+impl<'ast> Visit<'ast> for Collector {
+    fn visit_item_macro(&mut self, item: &'ast syn::ItemMacro) {
+        // `macro_rules! name { ... }` defines rather than invokes
+        match &item.ident {
+            Some(name) if item.mac.path.is_ident("macro_rules") => {
+                let span = item.span();
+                self.definitions.push(Definition {
+                    name: name.to_string(),
+                    line: span.start().line,
+                    lines: span.end().line - span.start().line + 1,
+                });
+            }
+            _ => visit::visit_item_macro(self, item),
+        }
+    }
+    
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if self.test_depth == 0 {
+            if let Some(name) = mac.path.segments.last() {
+                self.invocations.push(name.ident.to_string());
+            }
+        }
+        visit::visit_macro(self, mac);
+    }
+    
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        let is_test = is_test(&item.attrs);
+        self.test_depth += usize::from(is_test);
+        visit::visit_item_fn(self, item);
+        self.test_depth -= usize::from(is_test);
+    }
+    
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        let is_test = is_test(&item.attrs);
+        self.test_depth += usize::from(is_test);
+        visit::visit_item_mod(self, item);
+        self.test_depth -= usize::from(is_test);
+    }
+}
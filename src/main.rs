@@ -1,41 +1,114 @@
 use std::path::PathBuf;
-use std::process::Command;
 use anyhow::{Context, Result};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 use tracing::{info, warn, error};
-use walkdir::WalkDir;
 use wasmtime::{Engine, Instance, Module, Store};
 use wasmtime_wasi::{WasiCtx, WasiCtxBuilder};
-use axum::{
-    routing::{get, post},
-    http::StatusCode,
-    Json, Router,
-};
 use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use futures::{stream, FutureExt, StreamExt};
+use std::io::Write;
+use std::panic::AssertUnwindSafe;
 
 mod wasm_agent;
 mod llm_agent;
+mod llm_cache;
 mod memory_system;
 mod code_analyzer;
 mod voice_agent;
 mod local_brain;
 mod orchestrator;
 mod gpu_accelerator;
+mod git_ops;
+mod pr_review;
+mod daemon;
+mod archive_source;
+mod reporters;
+mod config;
+mod naming;
+mod literals;
+mod metrics;
+mod fix_verifier;
+mod ignore_rules;
+mod file_walker;
+mod patches;
+mod telemetry;
+mod packages;
+mod size_limits;
+mod profiling;
+mod coverage_hints;
+mod review_events;
+mod dependency_policy;
+mod public_api;
+mod docs;
+mod skipped;
+mod agent_config;
+mod report_meta;
+mod markdown;
+mod resource_leaks;
+mod language_detect;
+mod llm_worker;
+mod config_cascade;
+mod integer_overflow;
+mod report_diff;
+mod json_repair;
+mod redact;
+mod chunking;
+mod bench;
+mod macro_usage;
+mod config_files;
+mod scoring;
+mod checkpoint;
+mod rust_complexity;
+mod suppression;
+mod comments;
+mod duplication;
+mod worker_channel;
+mod web;
+mod semantic_search;
+mod qdrant_store;
+mod audio_file;
+mod vad;
+mod wake_word;
+mod safety;
+mod shell;
+mod supervisor;
+mod subprocess;
+mod whisper;
+mod cli;
+mod rust_attrs;
+#[cfg(feature = "sqlite")]
+mod sqlite_memory;
 
 use wasm_agent::WasmAgent;
-use llm_agent::LlmAgent;
+use llm_agent::{LlmAgent, LlmAgentConfig};
+use llm_cache::LlmCache;
 use memory_system::MemorySystem;
-use code_analyzer::CodeAnalyzer;
+use code_analyzer::{CodeAnalyzer, Issue, Severity, Suggestion};
 use voice_agent::{VoiceAgent, VoiceConfig};
 use local_brain::{LocalBrain, LocalBrainConfig};
 use orchestrator::{Orchestrator, OrchestratorConfig};
 use gpu_accelerator::{GPUAccelerator, GPUConfig};
+use reporters::{Reporter, ReporterRegistry};
+use config::ReviewConfig;
+use ignore_rules::IgnoreRules;
+use packages::PackageResolver;
+use coverage_hints::CoverageHints;
+use review_events::{ReviewEvent, ReviewEvents};
+use metrics::ReviewMetrics;
+use skipped::{FileTooLarge, SkippedFile};
+use agent_config::DevAgentConfig;
+use report_meta::ReportMeta;
+use config_cascade::ConfigCascade;
+use checkpoint::Checkpoint;
+use fix_verifier::FixOutcome;
 
-#[derive(Parser, Debug)]
+const CODE_EXTENSIONS: [&str; 15] = ["rs", "js", "ts", "tsx", "py", "java", "cpp", "c", "go", "php", "wasm", "yaml", "yml", "json", "toml"];
+
+#[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     /// Path to the codebase to review
@@ -59,54 +132,261 @@ struct Args {
     web: bool,
     
     /// Port for web server
-    #[arg(short, long, default_value = "8080")]
+    #[arg(long, default_value = "8080")]
     port: u16,
     
     /// Enable voice control
-    #[arg(short, long)]
+    #[arg(long)]
     voice: bool,
     
     /// Voice clone audio files
-    #[arg(short, long)]
+    #[arg(long)]
     voice_files: Vec<PathBuf>,
     
     /// Voice clone name
-    #[arg(short, long)]
+    #[arg(long)]
     voice_name: Option<String>,
     
     /// Enable GPU acceleration
     #[arg(short, long)]
     gpu: bool,
+    
+    /// Run as a daemon: watch the path, re-review changed files and serve the web API
+    #[arg(long)]
+    daemon: bool,
+    
+    /// Seconds between daemon re-review passes
+    #[arg(long, default_value = "5")]
+    review_interval: u64,
+    
+    /// Output format for review results (json, sarif, html, codeclimate)
+    #[arg(long, env = "DEVAGENT_FORMAT", default_value = "json")]
+    format: String,
+    
+    /// Seed passed to the LLM so sampled suggestions are repeatable
+    #[arg(long)]
+    seed: Option<u64>,
+    
+    /// Produce byte-identical reports for identical inputs: fixed seed,
+    /// temperature 0, sorted output and fixed timestamps
+    #[arg(long)]
+    deterministic: bool,
+    
+    /// Directory that report paths are relative to (defaults to the git
+    /// repo root, or the review path outside a repo)
+    #[arg(long)]
+    root: Option<PathBuf>,
+    
+    /// Write absolute file paths in reports instead of root-relative ones
+    #[arg(long)]
+    absolute_paths: bool,
+    
+    /// Review files as they were at this commit instead of the working tree
+    #[arg(long, value_name = "COMMIT")]
+    at: Option<String>,
+    
+    /// Export pipeline tracing spans to this OTLP (gRPC) endpoint,
+    /// e.g. http://localhost:4317
+    #[arg(long, value_name = "URL")]
+    otlp_endpoint: Option<String>,
+    
+    /// Fail the run when a file's score (or the average, see --score-gate)
+    /// is below this value
+    #[arg(long, value_name = "0.0-1.0", value_parser = parse_score)]
+    min_score: Option<f32>,
+    
+    /// What --min-score applies to
+    #[arg(long, value_enum, default_value = "file")]
+    score_gate: ScoreGate,
+    
+    /// Fail the run when any issue is at or above this severity
+    /// (overrides DEVAGENT_FAIL_ON and `fail_on` in .devagent.toml)
+    #[arg(long, value_name = "SEVERITY")]
+    fail_on: Option<Severity>,
+    
+    /// Print time spent per analyzer stage, summed over the whole run
+    #[arg(long)]
+    profile_cpu: bool,
+    
+    /// Suggest tests for Rust public functions no test appears to call
+    /// (name-based heuristic, not real coverage)
+    #[arg(long)]
+    coverage_hints: bool,
+    
+    /// Rust: review only the public API surface, checking docs and panics
+    /// on public items and skipping non-security issues in private code
+    #[arg(long)]
+    public_only: bool,
+    
+    /// Time budget for the whole run in seconds. Once spent, remaining
+    /// files skip LLM analysis and get static suggestions only
+    #[arg(long, value_name = "SECONDS")]
+    run_timeout: Option<u64>,
+    
+    /// Also review fenced code blocks in Markdown (.md) files
+    #[arg(long)]
+    check_markdown: bool,
+    
+    /// Mask secrets in logs, the memory file and report snippets, and log
+    /// only a short, hashed preview of file contents
+    #[arg(long)]
+    redact: bool,
+    
+    /// Review files that `.gitignore` and `.ignore` files exclude
+    #[arg(long)]
+    no_ignore: bool,
+    
+    /// Leave out files matching this glob, relative to --path and matched
+    /// like a .gitignore line (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+    
+    /// Skip files larger than this, listing them as too large
+    #[arg(long, value_name = "BYTES", default_value_t = DevAgentConfig::default().max_file_size)]
+    max_file_size: u64,
+    
+    /// Directory that suggestion patches are written to
+    #[arg(long, value_name = "DIR", default_value = "./patches")]
+    patch_dir: PathBuf,
+    
+    /// Apply patchable suggestions to the reviewed files, keeping each one
+    /// only if a re-review shows its issue gone and nothing more severe
+    #[arg(long)]
+    apply_fixes: bool,
+    
+    /// Files reviewed at once (defaults to the number of CPUs)
+    #[arg(long, value_name = "N", default_value_t = agent_config::default_jobs())]
+    jobs: usize,
+    
+    /// Most wasm-pack/wasm-opt processes running at once, shared by the
+    /// web server and batch review
+    #[arg(long, env = "DEVAGENT_WASM_COMPILE_JOBS", value_name = "N", default_value_t = wasm_agent::DEFAULT_MAX_COMPILATIONS)]
+    wasm_compile_jobs: usize,
+    
+    /// Continue an interrupted review from its `.devagent-progress`
+    /// checkpoint, skipping files that are done and unchanged
+    #[arg(long)]
+    resume: bool,
+    
+    /// Build Rust files with wasm-pack to report their real WASM size and
+    /// exports, estimating when a file doesn't build or the toolchain is
+    /// missing
+    #[arg(long)]
+    compile_wasm: bool,
+    
+    /// Ask the LLM about every file, even ones whose analysis is cached
+    /// in `.devagent-cache` from an earlier run
+    #[arg(long)]
+    no_cache: bool,
+    
+    /// Empty the LLM analysis cache before reviewing
+    #[arg(long)]
+    clear_cache: bool,
+    
+    /// Internal: run as the LLM worker process with this JSON config
+    #[arg(long = "llm-worker", value_name = "CONFIG", hide = true)]
+    llm_worker: Option<String>,
+    
+    /// Run a single command instead of the flag-driven review
+    #[command(subcommand)]
+    command: Option<cli::Commands>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ScoreGate {
+    /// Every file must meet the minimum
+    File,
+    /// Only the average over all files must meet it
+    Average,
+}
+
+fn parse_score(value: &str) -> Result<f32, String> {
+    let score: f32 = value.parse().map_err(|_| format!("`{}` is not a number", value))?;
+    if (0.0..=1.0).contains(&score) {
+        Ok(score)
+    } else {
+        Err(format!("{} is outside 0.0-1.0", score))
+    }
+}
+
+impl Args {
+    /// Defaults for every other flag, for callers that build their own
+    /// arguments instead of parsing the command line.
+    fn for_review(path: PathBuf, output: Option<PathBuf>, verbose: bool) -> Self {
+        let mut args = Self::parse_from(["devagent"]);
+        args.path = path;
+        args.output = output;
+        args.verbose = verbose;
+        args
+    }
+    
+    // Deterministic runs always need a seed; 0 unless one was given.
+    fn effective_seed(&self) -> Option<u64> {
+        self.seed.or(self.deterministic.then_some(0))
+    }
+    
+    /// `--seed`, and temperature 0 with `--deterministic`, for every
+    /// request `llm_agent` sends.
+    fn apply_sampling(&self, llm_agent: &mut LlmAgent) {
+        llm_agent.set_seed(self.effective_seed());
+        if self.deterministic {
+            llm_agent.set_temperature(0.0);
+        }
+    }
+    
+    /// `--jobs` and `--max-file-size` over the `DevAgentConfig` defaults.
+    fn agent_config(&self) -> DevAgentConfig {
+        DevAgentConfig {
+            max_concurrency: self.jobs,
+            max_file_size: self.max_file_size,
+            ..DevAgentConfig::default()
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct CodeReview {
     id: String,
     file_path: String,
+    /// Crate or package owning the file (see `PackageResolver`)
+    #[serde(default)]
+    package: String,
     issues: Vec<Issue>,
     suggestions: Vec<Suggestion>,
+    /// Issue penalties weighted by confidence, so heuristic findings move
+    /// it less; what `--min-score` checks
     score: f32,
+    /// The same with every issue at full penalty
+    #[serde(default)]
+    raw_score: f32,
+    /// Issues silenced by inline `devagent:ignore` comments
+    #[serde(default)]
+    suppressed: usize,
     timestamp: DateTime<Utc>,
     wasm_analysis: Option<WasmAnalysis>,
     llm_analysis: Option<LlmAnalysis>,
 }
 
+/// Everything a run produced: the finished reviews plus the files that
+/// couldn't be reviewed and why, under a header naming the rule set used.
 #[derive(Debug, Serialize, Deserialize)]
-struct Issue {
-    severity: Severity,
-    message: String,
-    line: Option<usize>,
-    code: Option<String>,
-    wasm_context: Option<String>,
+struct ReviewReport {
+    /// See `report_meta::FORMAT_VERSION`
+    format_version: u32,
+    meta: ReportMeta,
+    reviews: Vec<CodeReview>,
+    skipped: Vec<SkippedFile>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Suggestion {
-    title: String,
-    description: String,
-    code: Option<String>,
-    impact: Impact,
-    wasm_optimization: Option<String>,
+impl ReviewReport {
+    fn new(meta: ReportMeta) -> Self {
+        Self {
+            format_version: report_meta::FORMAT_VERSION,
+            meta,
+            reviews: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -115,6 +395,9 @@ struct WasmAnalysis {
     binary_size: usize,
     optimization_suggestions: Vec<String>,
     performance_score: f32,
+    /// The size is a guess from the source, not a compiled module's
+    #[serde(default)]
+    estimated: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -123,243 +406,705 @@ struct LlmAnalysis {
     maintainability_score: f32,
     security_score: f32,
     ai_suggestions: Vec<String>,
+    /// Skipped the LLM because `--run-timeout` had run out
+    #[serde(default)]
+    deadline_fallback: bool,
+    /// Chunks the file was split into to fit the LLM context, 0 if none
+    #[serde(default)]
+    chunks: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-enum Severity {
-    Low,
-    Medium,
-    High,
-    Critical,
+impl From<wasm_agent::WasmAnalysis> for WasmAnalysis {
+    fn from(analysis: wasm_agent::WasmAnalysis) -> Self {
+        Self {
+            compile_time: analysis.compile_time,
+            binary_size: analysis.binary_size,
+            optimization_suggestions: analysis.optimization_suggestions,
+            performance_score: analysis.performance_score,
+            estimated: analysis.estimated,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-enum Impact {
-    Low,
-    Medium,
-    High,
+impl From<llm_agent::LlmAnalysis> for LlmAnalysis {
+    fn from(analysis: llm_agent::LlmAnalysis) -> Self {
+        Self {
+            complexity_score: analysis.complexity_score,
+            maintainability_score: analysis.maintainability_score,
+            security_score: analysis.security_score,
+            ai_suggestions: analysis.ai_suggestions,
+            deadline_fallback: analysis.deadline_fallback,
+            chunks: analysis.chunks,
+        }
+    }
 }
 
 struct DevAgent {
     args: Args,
+    config: DevAgentConfig,
     wasm_agent: WasmAgent,
     llm_agent: LlmAgent,
-    memory_system: MemorySystem,
+    /// Locked per store, since files are reviewed concurrently
+    memory_system: tokio::sync::Mutex<MemorySystem>,
     code_analyzer: CodeAnalyzer,
     voice_agent: Option<VoiceAgent>,
     local_brain: Option<LocalBrain>,
     orchestrator: Option<Orchestrator>,
+    reporters: ReporterRegistry,
+    report_root: PathBuf,
+    packages: PackageResolver,
+    metrics: ReviewMetrics,
+    fail_on: Option<Severity>,
+    coverage_hints: Option<CoverageHints>,
+    events: ReviewEvents,
+    report_meta: ReportMeta,
+    /// Shared LLM cutoff from `--run-timeout`, counted from startup
+    run_deadline: Option<std::time::Instant>,
+    config_cascade: ConfigCascade,
+    /// Analyzers for subdirectories with their own `.devagent.toml`
+    directory_analyzers: tokio::sync::Mutex<HashMap<PathBuf, std::sync::Arc<CodeAnalyzer>>>,
+    /// Finished reviews of a batch run, for `--resume`
+    checkpoint: Option<Checkpoint>,
 }
 
 impl DevAgent {
-    async fn new(args: Args) -> Result<Self> {
+    async fn new(args: Args, config: DevAgentConfig) -> Result<Self> {
         info!("Initializing DevAgent with WASM and LLM support...");
         
-        let wasm_agent = WasmAgent::new().await?;
-        let llm_agent = LlmAgent::new().await?;
-        let memory_system = MemorySystem::new().await?;
-        let code_analyzer = CodeAnalyzer::new().await?;
+        config.validate()?;
+        let mut wasm_agent = WasmAgent::new().await?;
+        wasm_agent.set_compile(args.compile_wasm);
+        let review_config = ReviewConfig::load(&args.path).await?;
+        
+        let mut llm_agent = LlmAgent::new(LlmAgentConfig::from_env()?).await;
+        llm_agent.set_config(review_config.llm.clone());
+        args.apply_sampling(&mut llm_agent);
+        llm_agent.set_timeout(config.llm_timeout);
+        let report_root = Self::resolve_report_root(&args).await;
+        llm_agent.set_cache(Some(LlmCache::new(report_root.join(llm_cache::DIR_NAME))));
+        if args.clear_cache {
+            llm_agent.clear_cache().await?;
+        }
+        if args.no_cache {
+            llm_agent.set_cache(None);
+        }
+        if let Err(e) = llm_agent.health_check().await {
+            warn!("LLM worker is unhealthy ({:#}), files will get static suggestions until it recovers", e);
+        }
+        
+        let mut memory_system = MemorySystem::new().await?;
+        memory_system.set_max_cache_bytes(config.max_cache_bytes);
+        match qdrant_store::connect(&review_config.semantic_search).await {
+            Ok(Some(index)) => memory_system.set_semantic_index(index),
+            Ok(None) => {}
+            Err(e) => warn!("Semantic search is unavailable: {:#}", e),
+        }
+        let mut code_analyzer = CodeAnalyzer::new().await?;
+        code_analyzer.apply_config(&review_config);
+        code_analyzer.set_public_only(args.public_only);
+        code_analyzer.set_ignore_rules(IgnoreRules::load(&args.path).await?);
+        let reporters = ReporterRegistry::with_builtins(args.output.clone());
+        let packages = PackageResolver::new(report_root.clone());
+        let metrics = ReviewMetrics::new()?;
+        let fail_on = args.fail_on.clone().or(review_config.fail_on.clone());
+        let coverage_hints = args.coverage_hints.then(CoverageHints::new);
+        let run_deadline = args.run_timeout
+            .map(|seconds| std::time::Instant::now() + std::time::Duration::from_secs(seconds));
+        let report_meta = ReportMeta::new(&review_config, code_analyzer.enabled_rules());
+        let config_cascade = ConfigCascade::new(&args.path, &review_config)?;
         
         Ok(Self {
             args,
+            config,
             wasm_agent,
             llm_agent,
-            memory_system,
+            memory_system: tokio::sync::Mutex::new(memory_system),
             code_analyzer,
+            voice_agent: None,
+            local_brain: None,
+            orchestrator: None,
+            reporters,
+            report_root,
+            packages,
+            metrics,
+            fail_on,
+            coverage_hints,
+            events: ReviewEvents::default(),
+            report_meta,
+            run_deadline,
+            config_cascade,
+            directory_analyzers: tokio::sync::Mutex::new(HashMap::new()),
+            checkpoint: None,
         })
     }
     
-    async fn review_codebase(&self) -> Result<Vec<CodeReview>> {
+    async fn resolve_report_root(args: &Args) -> PathBuf {
+        let root = match &args.root {
+            Some(root) => root.clone(),
+            None => match git_ops::repo_root(&args.path).await {
+                Ok(root) => root,
+                Err(_) if args.path.is_dir() => args.path.clone(),
+                Err(_) => args.path.parent().unwrap_or(std::path::Path::new(".")).to_path_buf(),
+            },
+        };
+        
+        std::fs::canonicalize(&root).unwrap_or(root)
+    }
+    
+    /// Path as written to reports: relative to the report root with `/`
+    /// separators, or absolute with `--absolute-paths`.
+    fn report_path(&self, file_path: &std::path::Path) -> String {
+        let absolute = std::fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+        
+        if self.args.absolute_paths {
+            return absolute.to_string_lossy().to_string();
+        }
+        
+        absolute
+            .strip_prefix(&self.report_root)
+            .unwrap_or(file_path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+    
+    fn new_report(&self) -> ReviewReport {
+        ReviewReport::new(self.report_meta.clone())
+    }
+    
+    async fn review_codebase(&self) -> Result<ReviewReport> {
+        let mut report = self.new_report();
+        self.collect_reviews(&mut report).await?;
+        Ok(report)
+    }
+    
+    /// Pushes each review into `report` as soon as it is done, so callers
+    /// keep the partial results if the run fails or panics midway.
+    async fn collect_reviews(&self, report: &mut ReviewReport) -> Result<()> {
         info!("Starting comprehensive codebase review with WASM and LLM analysis");
         
-        let mut reviews = Vec::new();
+        if let Some(commit) = &self.args.at {
+            return self.review_commit(commit, report).await;
+        }
+        
+        if archive_source::is_archive(&self.args.path) {
+            return self.review_archive(report).await;
+        }
         
-        // Walk through the codebase
-        for entry in WalkDir::new(&self.args.path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-        {
-            let file_path = entry.path();
-            
-            if !self.is_code_file(file_path) {
-                continue;
+        let files = self.code_files()?;
+        
+        // Results arrive as files finish; `order_reviews` puts them back in
+        // a fixed order
+        let mut results = stream::iter(&files)
+            .map(|file_path| async move {
+                info!("Reviewing file: {}", file_path.display());
+                (file_path, self.review_file(file_path).await)
+            })
+            .buffer_unordered(self.config.max_concurrency);
+        
+        while let Some((file_path, result)) = results.next().await {
+            self.record_result(report, &file_path.display().to_string(), result);
+        }
+        
+        self.order_reviews(&mut report.reviews);
+        
+        info!("Completed codebase review. Found {} files to review.", report.reviews.len());
+        Ok(())
+    }
+    
+    /// Keeps a finished review and tells any event subscriber about it.
+    /// Failed files are logged and listed in the report as skipped.
+    fn record_result(&self, report: &mut ReviewReport, file_path: &str, result: Result<CodeReview>) {
+        match result {
+            Ok(review) => {
+                self.events.emit(ReviewEvent::FileReviewed {
+                    file_path: review.file_path.clone(),
+                    score: review.score,
+                    issues: review.issues.len(),
+                });
+                report.reviews.push(review);
             }
-            
-            info!("Reviewing file: {}", file_path.display());
-            
-            match self.review_file(file_path).await {
-                Ok(review) => reviews.push(review),
-                Err(e) => {
-                    error!("Failed to review {}: {}", file_path.display(), e);
-                }
+            Err(e) => {
+                error!("Failed to review {}: {}", file_path, e);
+                self.events.emit(ReviewEvent::FileFailed {
+                    file_path: file_path.to_string(),
+                    error: format!("{:#}", e),
+                });
+                report.skipped.push(SkippedFile::new(file_path, &e));
             }
         }
-        
-        info!("Completed codebase review. Found {} files to review.", reviews.len());
-        Ok(reviews)
+    }
+    
+    /// Groups reviews by package, sorted by path within each one, so the
+    /// report doesn't depend on which files finished first.
+    fn order_reviews(&self, reviews: &mut [CodeReview]) {
+        reviews.sort_by(|a, b| (&a.package, &a.file_path).cmp(&(&b.package, &b.file_path)));
+    }
+    
+    /// Code files under the review path, sorted, minus what `.gitignore`
+    /// (unless `--no-ignore`) and `--exclude` leave out.
+    fn code_files(&self) -> Result<Vec<PathBuf>> {
+        let options = file_walker::WalkOptions {
+            respect_ignore: !self.args.no_ignore,
+            exclude: self.args.exclude.clone(),
+        };
+        let mut files = file_walker::walk(&self.args.path, &options)?;
+        files.retain(|path| self.is_code_file(path));
+        Ok(files)
     }
     
     fn is_code_file(&self, path: &std::path::Path) -> bool {
-        let extensions = ["rs", "js", "ts", "py", "java", "cpp", "c", "go", "php", "wasm"];
+        // Cached LLM analyses are JSON, and reviewing them would cache more
+        if path.components().any(|component| component.as_os_str() == llm_cache::DIR_NAME) {
+            return false;
+        }
+        
+        // Reviewed for the dependency policy
+        if path.file_name().is_some_and(|name| name == "Cargo.toml") {
+            return true;
+        }
+        
+        if self.args.check_markdown && code_analyzer::is_markdown(path) {
+            return true;
+        }
+        
         path.extension()
             .and_then(|ext| ext.to_str())
-            .map(|ext| extensions.contains(&ext))
+            .map(|ext| CODE_EXTENSIONS.contains(&ext))
             .unwrap_or(false)
     }
     
+    async fn review_archive(&self, report: &mut ReviewReport) -> Result<()> {
+        let archive = self.args.path.clone();
+        let archive_name = archive.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| archive.to_string_lossy().to_string());
+        
+        let entries = tokio::task::spawn_blocking(move || {
+            archive_source::read_code_entries(&archive, |path| {
+                // Compiled .wasm entries can't be read as source text
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(|ext| ext != "wasm" && CODE_EXTENSIONS.contains(&ext))
+                    .unwrap_or(false)
+            })
+        })
+        .await??;
+        
+        let archive_source = std::fs::canonicalize(&self.args.path).unwrap_or_else(|_| self.args.path.clone());
+        
+        for entry in entries {
+            let display_path = entry.display_path(&archive_name);
+            let source_path = entry.display_path(&archive_source.to_string_lossy());
+            info!("Reviewing archive entry: {}", display_path);
+            
+            let result = self.review_content(&display_path, &source_path, std::path::Path::new(&entry.path), &entry.content).await;
+            self.record_result(report, &display_path, result);
+        }
+        
+        self.order_reviews(&mut report.reviews);
+        
+        info!("Completed archive review. Reviewed {} files.", report.reviews.len());
+        Ok(())
+    }
+    
+    async fn review_commit(&self, commit: &str, report: &mut ReviewReport) -> Result<()> {
+        let root = git_ops::repo_root(&self.args.path).await
+            .context("--at needs the review path to be inside a git repository")?;
+        let files = git_ops::files_at(&self.args.path, commit).await?;
+        
+        info!("Reviewing {} files at {}", files.len(), commit);
+        
+        for relative_path in files {
+            let file_path = root.join(&relative_path);
+            
+            if !self.is_code_file(&file_path) {
+                continue;
+            }
+            
+            let result = match git_ops::show_file_at(&root, commit, &relative_path).await {
+                Ok(content) => {
+                    info!("Reviewing file at {}: {}", commit, relative_path.display());
+                    self.review_content(&self.report_path(&file_path), &file_path.to_string_lossy(), &file_path, &content).await
+                }
+                Err(e) => Err(e.context(format!("Failed to read file at {}", commit))),
+            };
+            self.record_result(report, &relative_path.display().to_string(), result);
+        }
+        
+        self.order_reviews(&mut report.reviews);
+        
+        info!("Completed review at {}. Reviewed {} files.", commit, report.reviews.len());
+        Ok(())
+    }
+    
     async fn review_file(&self, file_path: &std::path::Path) -> Result<CodeReview> {
+        // Checked before reading so huge files never get loaded
+        let size = fs::metadata(file_path).await
+            .context("Failed to read file")?
+            .len();
+        self.check_file_size(size)?;
+        
         let content = fs::read_to_string(file_path).await
             .context("Failed to read file")?;
         
-        let file_id = Uuid::new_v4().to_string();
+        let source_path = std::fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+        let report_path = self.report_path(file_path);
+        
+        let Some(checkpoint) = &self.checkpoint else {
+            return self.review_content(&report_path, &source_path.to_string_lossy(), file_path, &content).await;
+        };
+        
+        if let Some(review) = checkpoint.completed(&report_path, &content) {
+            match serde_json::from_value(review.clone()) {
+                Ok(review) => {
+                    info!("Resumed {} from checkpoint", report_path);
+                    return Ok(review);
+                }
+                Err(e) => warn!("Ignoring checkpoint entry for {}: {}", report_path, e),
+            }
+        }
+        
+        let review = self.review_content(&report_path, &source_path.to_string_lossy(), file_path, &content).await?;
+        if let Err(e) = checkpoint.record(&report_path, &content, &review) {
+            warn!("Failed to checkpoint {}: {:#}", report_path, e);
+        }
+        Ok(review)
+    }
+    
+    /// Records finished files as the walk goes, so an interrupted run can
+    /// be continued with `--resume`.
+    fn start_checkpoint(&mut self) -> Result<()> {
+        let checkpoint = Checkpoint::open(&self.report_root, &self.report_meta.config_hash, self.args.resume)?;
+        if self.args.resume {
+            info!("Resuming: {} files already reviewed", checkpoint.resumed());
+        }
+        self.checkpoint = Some(checkpoint);
+        Ok(())
+    }
+    
+    /// Drops the checkpoint once the run's results are saved.
+    fn finish_checkpoint(&mut self) {
+        if let Some(checkpoint) = self.checkpoint.take() {
+            if let Err(e) = checkpoint.clear() {
+                warn!("{:#}", e);
+            }
+        }
+    }
+    
+    // `display_path` is what ends up in the report and `source_path` is where
+    // the memory store can find the file again; `file_path` only drives
+    // language detection, so archive entries can use their internal path.
+    async fn review_content(&self, display_path: &str, source_path: &str, file_path: &std::path::Path, content: &str) -> Result<CodeReview> {
+        self.check_file_size(content.len() as u64)?;
+        
+        let review = telemetry::timed(
+            telemetry::review_span(display_path),
+            self.review_stages(display_path, source_path, file_path, content),
+        );
+        
+        // Boxed, as the analysis future is too large to keep moving on the stack
+        let review = Box::pin(async {
+            tokio::time::timeout(self.config.file_timeout, review).await
+                .with_context(|| format!("Review timed out after {:?}", self.config.file_timeout))?
+        });
+        // A panic while analyzing one file fails that file, not the run
+        catch_panic(review).await
+    }
+    
+    /// The analyzer for a subdirectory with its own `.devagent.toml`, or
+    /// `None` when the root analyzer applies.
+    async fn directory_analyzer(&self, file_path: &std::path::Path) -> Result<Option<std::sync::Arc<CodeAnalyzer>>> {
+        let Some((dir, config)) = self.config_cascade.resolve(file_path).await? else {
+            return Ok(None);
+        };
+        
+        let mut analyzers = self.directory_analyzers.lock().await;
+        if let Some(analyzer) = analyzers.get(&dir) {
+            return Ok(Some(analyzer.clone()));
+        }
+        
+        let analyzer = std::sync::Arc::new(self.code_analyzer.for_config(&config).await?);
+        analyzers.insert(dir, analyzer.clone());
+        Ok(Some(analyzer))
+    }
+    
+    // Code snippets are copied from the source, so they can carry secrets
+    fn redact_snippets(mut issues: Vec<Issue>, mut suggestions: Vec<Suggestion>) -> (Vec<Issue>, Vec<Suggestion>) {
+        for issue in &mut issues {
+            issue.code = issue.code.as_deref().map(redact::mask_secrets);
+        }
+        for suggestion in &mut suggestions {
+            suggestion.code = suggestion.code.as_deref().map(redact::mask_secrets);
+        }
+        (issues, suggestions)
+    }
+    
+    fn check_file_size(&self, size: u64) -> Result<()> {
+        let limit = self.config.max_file_size;
+        if size > limit {
+            return Err(FileTooLarge { size, limit }.into());
+        }
+        Ok(())
+    }
+    
+    async fn review_stages(&self, display_path: &str, source_path: &str, file_path: &std::path::Path, content: &str) -> Result<CodeReview> {
+        let file_id = Self::review_id(std::path::Path::new(display_path));
         
         // Store in memory system
-        self.memory_system.store_file(&file_id, &content).await?;
+        // Only the in-memory update holds the lock; files reviewed at once
+        // write the memory file one after another
+        let staged = self.memory_system.lock().await.stage_file(&file_id, source_path, content)?;
+        telemetry::stage("memory", staged.persist()).await?;
         
         // Static analysis
-        let issues = self.code_analyzer.analyze_code(&content, file_path).await?;
-        let suggestions = self.code_analyzer.generate_suggestions(&content, file_path).await?;
-        let score = self.code_analyzer.calculate_score(&content);
+        let directory_analyzer = self.directory_analyzer(file_path).await?;
+        let code_analyzer = directory_analyzer.as_deref().unwrap_or(&self.code_analyzer);
+        let (issues, suppressed, suggestions) = telemetry::stage("static", profiling::measure_async("static analysis", async {
+            let (issues, suppressed) = code_analyzer.analyze_code_with_suppressed(content, file_path).await?;
+            let mut suggestions = code_analyzer.generate_suggestions(content, file_path).await?;
+            if let Some(hints) = &self.coverage_hints {
+                suggestions.extend(hints.suggestions(file_path, content));
+            }
+            anyhow::Ok((issues, suppressed, suggestions))
+        })).await?;
+        let score = profiling::measure("metrics", || code_analyzer.calculate_score(content, &issues));
+        let (issues, suggestions) = if redact::is_enabled() {
+            Self::redact_snippets(issues, suggestions)
+        } else {
+            (issues, suggestions)
+        };
         
         // WASM analysis for Rust files
         let wasm_analysis = if file_path.extension().map_or(false, |ext| ext == "rs") {
-            Some(self.wasm_agent.analyze_rust_file(&content).await?)
+            Some(telemetry::stage("wasm", profiling::measure_async("wasm analysis", self.wasm_agent.analyze_rust_file(content))).await?)
         } else {
             None
         };
         
         // LLM analysis
-        let llm_analysis = Some(self.llm_agent.analyze_code(&content, file_path).await?);
+        let llm_started = std::time::Instant::now();
+        let llm_analysis = Some(telemetry::stage("llm", self.llm_agent.analyze_code(content, file_path, self.run_deadline)).await?);
+        self.metrics.observe_llm_latency(llm_started.elapsed().as_secs_f64());
+        
+        // Pin the timestamp to the epoch so identical inputs give identical reports
+        let timestamp = if self.args.deterministic {
+            DateTime::<Utc>::default()
+        } else {
+            Utc::now()
+        };
         
-        Ok(CodeReview {
+        let review = CodeReview {
             id: file_id,
-            file_path: file_path.to_string_lossy().to_string(),
+            file_path: display_path.to_string(),
+            package: self.packages.resolve(file_path),
             issues,
             suggestions,
-            score,
-            timestamp: Utc::now(),
-            wasm_analysis,
-            llm_analysis,
-        })
+            score: score.weighted,
+            raw_score: score.raw,
+            suppressed,
+            timestamp,
+            wasm_analysis: wasm_analysis.map(Into::into),
+            llm_analysis: llm_analysis.map(Into::into),
+        };
+        
+        self.metrics.record_review(&review);
+        Ok(review)
     }
     
-    async fn save_reviews(&self, reviews: &[CodeReview]) -> Result<()> {
-        let output_path = self.args.output.clone()
-            .unwrap_or_else(|| PathBuf::from("code_review_results.json"));
-        
-        let json = serde_json::to_string_pretty(reviews)
-            .context("Failed to serialize reviews")?;
-        
-        fs::write(&output_path, json).await
-            .context("Failed to write review results")?;
+    /// Refreshes queue gauges that are sampled rather than pushed.
+    async fn refresh_queue_metrics(&self) {
+        if let Some(orchestrator) = &self.orchestrator {
+            for (queue, depth) in orchestrator.queue_depths() {
+                self.metrics.set_queue_depth(queue, depth);
+            }
+        }
+    }
+    
+    /// Stable review id derived from the normalized file path, so the same
+    /// file maps to the same id across runs.
+    fn review_id(file_path: &std::path::Path) -> String {
+        let normalized = file_path
+            .strip_prefix(".")
+            .unwrap_or(file_path)
+            .to_string_lossy()
+            .replace('\\', "/");
         
-        info!("Review results saved to: {}", output_path.display());
-        Ok(())
+        Uuid::new_v5(&Uuid::NAMESPACE_URL, format!("devagent:{}", normalized).as_bytes()).to_string()
     }
     
+    /// Errors when any issue reaches the `--fail-on` severity.
+    fn check_fail_on(&self, reviews: &[CodeReview]) -> Result<()> {
+        match &self.fail_on {
+            Some(threshold) => fail_on_gate(reviews, threshold),
+            None => Ok(()),
+        }
+    }
+    
+    /// Errors listing the offending files when `--min-score` is not met.
+    fn check_min_score(&self, reviews: &[CodeReview]) -> Result<()> {
+        match self.args.min_score {
+            Some(min_score) => min_score_gate(reviews, min_score, self.args.score_gate),
+            None => Ok(()),
+        }
+    }
+    
+    async fn save_reviews(&self, report: &ReviewReport) -> Result<()> {
+        self.reporters.get(&self.args.format)?.report(report).await
+    }
+    
+    fn register_reporter(&mut self, name: &str, reporter: impl Reporter + 'static) {
+        self.reporters.register(name, reporter);
+    }
+    
+    /// Writes a unified diff under `--patch-dir` for each suggestion that
+    /// says how to change its line, relative to the report root so
+    /// `git apply` takes it from there. Reviews of files that aren't on
+    /// disk (archives, `--at`) get none.
     async fn generate_patches(&self, reviews: &[CodeReview]) -> Result<()> {
         info!("Generating patches with WASM optimizations...");
         
+        let patch_dir = &self.args.patch_dir;
+        let mut written = 0;
         for review in reviews {
+            let editable = review.suggestions.iter().any(|suggestion| suggestion.line.is_some() && suggestion.code.is_some());
+            if !editable {
+                continue;
+            }
+            
+            let source = self.report_root.join(&review.file_path);
+            let original = match fs::read_to_string(&source).await {
+                Ok(original) => original,
+                Err(e) => {
+                    warn!("No patches for {}: {}", review.file_path, e);
+                    continue;
+                }
+            };
+            let diff_path = source.strip_prefix(&self.report_root).unwrap_or(&source).to_string_lossy().replace('\\', "/");
+            
             for suggestion in &review.suggestions {
-                if let Some(code) = &suggestion.code {
-                    let patch_name = format!("{}_{}.patch", 
-                        review.file_path.replace('/', "_").replace('\\', "_"),
-                        suggestion.title.replace(' ', "_")
-                    );
-                    
-                    let patch_content = format!(
-                        "--- {}\n+++ {}\n@@ -1,1 +1,1 @@\n{}\n",
-                        review.file_path, review.file_path, code
-                    );
-                    
-                    fs::write(&patch_name, patch_content).await
-                        .context("Failed to write patch file")?;
-                    
-                    info!("Generated patch: {}", patch_name);
+                let (Some(line), Some(code)) = (suggestion.line, &suggestion.code) else {
+                    continue;
+                };
+                let Some(patch) = patches::replace_line(&original, line, code)
+                    .and_then(|modified| patches::unified_diff(&diff_path, &original, &modified)) else {
+                    continue;
+                };
+                
+                if written == 0 {
+                    fs::create_dir_all(patch_dir).await
+                        .with_context(|| format!("Failed to create {}", patch_dir.display()))?;
                 }
+                let patch_file = patch_dir.join(patches::file_name(&diff_path, line, &suggestion.title));
+                fs::write(&patch_file, patch).await
+                    .with_context(|| format!("Failed to write {}", patch_file.display()))?;
+                written += 1;
+                
+                info!("Generated patch: {}", patch_file.display());
             }
         }
         
+        if written > 0 {
+            info!("Wrote {} patches to {}", written, patch_dir.display());
+        }
         Ok(())
     }
     
-    async fn commit_changes(&self) -> Result<()> {
-        info!("Committing changes to git...");
-        
-        let status = Command::new("git")
-            .args(["add", "."])
-            .status()
-            .context("Failed to git add")?;
-        
-        if !status.success() {
-            warn!("Git add failed");
+    /// With `--apply-fixes`, writes each patchable suggestion that targets
+    /// an issue on its line into the file, bottom-up so earlier line
+    /// numbers still hold. Fixes that leave the issue or introduce a worse
+    /// one are rolled back by `fix_verifier`.
+    async fn apply_fixes(&self, reviews: &[CodeReview]) -> Result<()> {
+        if !self.args.apply_fixes {
             return Ok(());
         }
         
-        let status = Command::new("git")
-            .args(["commit", "-m", "Auto-generated code improvements from DevAgent with WASM optimizations"])
-            .status()
-            .context("Failed to git commit")?;
+        // Masked snippets and reviews of another revision don't belong in
+        // the working tree
+        if redact::is_enabled() || self.args.at.is_some() {
+            warn!("Not applying fixes with --redact or --at");
+            return Ok(());
+        }
         
-        if status.success() {
-            info!("Changes committed successfully");
-        } else {
-            warn!("Git commit failed - no changes to commit");
+        let (mut applied, mut rolled_back) = (0, 0);
+        for review in reviews {
+            let mut fixes: Vec<(usize, &String, &Issue)> = review.suggestions.iter()
+                .filter_map(|suggestion| {
+                    let (line, code) = (suggestion.line?, suggestion.code.as_ref()?);
+                    let target = review.issues.iter().find(|issue| issue.line == Some(line))?;
+                    Some((line, code, target))
+                })
+                .collect();
+            fixes.sort_by_key(|fix| std::cmp::Reverse(fix.0));
+            fixes.dedup_by_key(|fix| fix.0);
+            if fixes.is_empty() {
+                continue;
+            }
+            
+            let source = self.report_root.join(&review.file_path);
+            let directory_analyzer = self.directory_analyzer(&source).await?;
+            let analyzer = directory_analyzer.as_deref().unwrap_or(&self.code_analyzer);
+            
+            for (line, code, target) in fixes {
+                let original = match fs::read_to_string(&source).await {
+                    Ok(original) => original,
+                    Err(e) => {
+                        warn!("Not applying fixes to {}: {}", review.file_path, e);
+                        break;
+                    }
+                };
+                let Some(modified) = patches::replace_line(&original, line, code).filter(|modified| *modified != original) else {
+                    continue;
+                };
+                
+                match fix_verifier::apply_verified(analyzer, &source, target, &modified).await? {
+                    FixOutcome::Applied => applied += 1,
+                    FixOutcome::RolledBack { .. } => rolled_back += 1,
+                }
+            }
         }
         
+        if applied + rolled_back > 0 {
+            info!("Applied {} fixes, rolled back {}", applied, rolled_back);
+        }
         Ok(())
     }
     
-    async fn start_web_server(&self) -> Result<()> {
-        info!("Starting web server for WASM hosting on port {}", self.args.port);
-        
-        let app = Router::new()
-            .route("/", get(self.health_check))
-            .route("/review", post(self.review_endpoint))
-            .route("/wasm/analyze", post(self.wasm_analyze_endpoint))
-            .route("/llm/analyze", post(self.llm_analyze_endpoint));
-        
-        let addr = format!("0.0.0.0:{}", self.args.port);
-        info!("Web server starting on {}", addr);
+    async fn commit_changes(&self) -> Result<()> {
+        info!("Committing changes to git...");
         
-        axum::Server::bind(&addr.parse()?)
-            .serve(app.into_make_service())
-            .await?;
+        // Commit in the reviewed repo, not wherever DevAgent was started
+        let message = "Auto-generated code improvements from DevAgent with WASM optimizations";
+        match git_ops::commit_all(&self.args.path, message).await {
+            Ok(true) => info!("Changes committed successfully"),
+            Ok(false) => warn!("Git commit skipped - no changes to commit"),
+            Err(e) => warn!("Git commit failed: {:#}", e),
+        }
         
         Ok(())
     }
     
-    async fn health_check(&self) -> StatusCode {
-        StatusCode::OK
-    }
-    
-    async fn review_endpoint(&self, Json(payload): Json<serde_json::Value>) -> Json<serde_json::Value> {
-        // Handle review requests via web API
-        Json(serde_json::json!({
-            "status": "success",
-            "message": "Review endpoint ready"
-        }))
-    }
-    
-    async fn wasm_analyze_endpoint(&self, Json(payload): Json<serde_json::Value>) -> Json<serde_json::Value> {
-        // Handle WASM analysis requests
-        Json(serde_json::json!({
-            "status": "success",
-            "wasm_analysis": "ready"
-        }))
-    }
-    
-    async fn llm_analyze_endpoint(&self, Json(payload): Json<serde_json::Value>) -> Json<serde_json::Value> {
-        // Handle LLM analysis requests
-        Json(serde_json::json!({
-            "status": "success",
-            "llm_analysis": "ready"
-        }))
+    /// Runs a review, printing each file's result as it finishes.
+    async fn review_with_progress(&self) -> Result<ReviewReport> {
+        let mut events = self.events.subscribe();
+        let mut report = self.new_report();
+        
+        let review = async {
+            let result = self.collect_reviews(&mut report).await;
+            self.events.close();
+            result
+        };
+        let progress = async {
+            while let Some(event) = events.recv().await {
+                println!("{}", event);
+            }
+        };
+        
+        let (result, ()) = tokio::join!(review, progress);
+        result?;
+        Ok(report)
     }
     
-    async fn run_interactive_mode(&self) -> Result<()> {
+    async fn run_interactive_mode(self) -> Result<()> {
         info!("Starting interactive mode with WASM and LLM capabilities...");
         
         loop {
@@ -371,15 +1116,21 @@ impl DevAgent {
             println!("5. Start web server");
             println!("6. Exit");
             print!("Choose an option: ");
+            // print! doesn't flush, so the prompt would otherwise show up
+            // only after read_line returns
+            std::io::stdout().flush()?;
             
             let mut input = String::new();
             std::io::stdin().read_line(&mut input)?;
             
             match input.trim() {
                 "1" => {
-                    let reviews = self.review_codebase().await?;
-                    self.save_reviews(&reviews).await?;
-                    println!("Code review completed!");
+                    let report = self.review_with_progress().await?;
+                    self.save_reviews(&report).await?;
+                    println!("Code review completed: {} files reviewed.", report.reviews.len());
+                    if let Some(skipped) = skipped::summary(&report.skipped) {
+                        println!("{}", skipped);
+                    }
                 }
                 "2" => {
                     println!("WASM analysis mode - analyzing Rust files for WASM compilation...");
@@ -395,7 +1146,7 @@ impl DevAgent {
                 }
                 "5" => {
                     println!("Starting web server...");
-                    self.start_web_server().await?;
+                    return web::serve(self).await;
                 }
                 "6" => break,
                 _ => println!("Invalid option"),
@@ -406,41 +1157,156 @@ impl DevAgent {
     }
 }
 
+/// Errors listing the files below `min_score` when the `gate` fails. An
+/// empty review has nothing to score and always passes.
+fn min_score_gate(reviews: &[CodeReview], min_score: f32, gate: ScoreGate) -> Result<()> {
+    if reviews.is_empty() {
+        return Ok(());
+    }
+    
+    let below: Vec<&CodeReview> = reviews.iter().filter(|review| review.score < min_score).collect();
+    let average = reviews.iter().map(|review| review.score).sum::<f32>() / reviews.len() as f32;
+    
+    let failed = match gate {
+        ScoreGate::File => !below.is_empty(),
+        ScoreGate::Average => average < min_score,
+    };
+    
+    if !failed {
+        return Ok(());
+    }
+    
+    let files: Vec<String> = below.iter()
+        .map(|review| format!("  {} ({:.2})", review.file_path, review.score))
+        .collect();
+    
+    match gate {
+        ScoreGate::File => anyhow::bail!(
+            "{} file(s) scored below --min-score {:.2}:\n{}", below.len(), min_score, files.join("\n")
+        ),
+        ScoreGate::Average => anyhow::bail!(
+            "Average score {:.2} is below --min-score {:.2}; files below it:\n{}", average, min_score, files.join("\n")
+        ),
+    }
+}
+
+/// Errors listing every issue at or above `threshold`, counted per
+/// severity, so `--fail-on` exits non-zero.
+fn fail_on_gate(reviews: &[CodeReview], threshold: &Severity) -> Result<()> {
+    let Some(summary) = code_analyzer::severity_summary(reviews.iter().flat_map(|review| &review.issues), threshold) else {
+        return Ok(());
+    };
+    
+    let failing: Vec<String> = reviews.iter()
+        .flat_map(|review| review.issues.iter().map(move |issue| (review, issue)))
+        .filter(|(_, issue)| issue.severity >= *threshold)
+        .map(|(review, issue)| match issue.line {
+            Some(line) => format!("  {}:{} {:?}: {}", review.file_path, line, issue.severity, issue.message),
+            None => format!("  {} {:?}: {}", review.file_path, issue.severity, issue.message),
+        })
+        .collect();
+    
+    anyhow::bail!("{} issue(s) at or above {:?} ({}):\n{}", failing.len(), threshold, summary, failing.join("\n"))
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
     
-    // Initialize logging
-    if args.verbose {
-        tracing_subscriber::fmt()
-            .with_env_filter("debug")
-            .init();
-    } else {
-        tracing_subscriber::fmt()
-            .with_env_filter("info")
-            .init();
+    // Initialize logging, and span export when an OTLP endpoint is given.
+    // Interactive mode only logs warnings so the menu isn't buried.
+    let verbose = args.verbose || args.command.as_ref().is_some_and(cli::Commands::verbose);
+    let log_level = match (verbose, args.interactive) {
+        (true, _) => "debug",
+        (false, true) => "warn",
+        (false, false) => "info",
+    };
+    telemetry::init(log_level, args.otlp_endpoint.as_deref())?;
+    
+    // Logs go to stderr, leaving stdout to the worker protocol
+    if let Some(worker_config) = &args.llm_worker {
+        let worker_config = serde_json::from_str(worker_config).context("Invalid LLM worker config")?;
+        return local_brain::serve_llm_worker(worker_config).await;
+    }
+    
+    if args.profile_cpu {
+        profiling::enable();
+    }
+    if args.redact {
+        redact::enable();
+    }
+    wasm_agent::set_max_compilations(args.wasm_compile_jobs)?;
+    
+    let result = match args.command.take() {
+        Some(command) => cli::run_cli(command).await,
+        None => run(args).await,
+    };
+    telemetry::shutdown();
+    
+    if profiling::is_enabled() {
+        eprintln!("\n{}", profiling::report());
     }
     
+    result
+}
+
+async fn run(args: Args) -> Result<()> {
     info!("Starting DevAgent Pipeline v0.1.0 (Rust + WASM + LLM)");
     
-    let agent = DevAgent::new(args.clone()).await?;
+    let mut agent = DevAgent::new(args.clone(), args.agent_config()).await?;
     
-    if args.web {
-        agent.start_web_server().await?;
+    if args.daemon {
+        let config = daemon::DaemonConfig {
+            port: args.port,
+            review_interval: std::time::Duration::from_secs(args.review_interval.max(1)),
+        };
+        daemon::run(agent, config).await?;
+    } else if args.web {
+        web::serve(agent).await?;
     } else if args.interactive {
         agent.run_interactive_mode().await?;
     } else {
-        // Run automated review
-        let reviews = agent.review_codebase().await?;
+        // Run automated review, keeping whatever finished if it aborts
+        agent.start_checkpoint()?;
+        let mut report = agent.new_report();
+        let outcome = AssertUnwindSafe(telemetry::stage("walk", agent.collect_reviews(&mut report)))
+            .catch_unwind()
+            .await;
+        
+        let failure = match outcome {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e),
+            Err(panic) => Some(anyhow::anyhow!("Review panicked: {}", panic_message(&*panic))),
+        };
+        
+        if let Some(e) = failure {
+            error!("Review aborted after {} files: {:#}", report.reviews.len(), e);
+            
+            if !report.reviews.is_empty() {
+                match agent.save_reviews(&report).await {
+                    Ok(()) => warn!("Saved partial results for {} files", report.reviews.len()),
+                    Err(save_error) => error!("Failed to save partial results: {:#}", save_error),
+                }
+            }
+            
+            return Err(e);
+        }
         
         // Save results
-        agent.save_reviews(&reviews).await?;
+        telemetry::stage("save", agent.save_reviews(&report)).await?;
+        agent.finish_checkpoint();
+        let reviews = &report.reviews;
         
         // Generate patches
-        agent.generate_patches(&reviews).await?;
+        agent.generate_patches(reviews).await?;
+        agent.apply_fixes(reviews).await?;
+        
+        // Don't commit on top of a failing gate
+        let score_gate = agent.check_min_score(reviews);
+        let severity_gate = agent.check_fail_on(reviews);
         
         // Optionally commit changes
-        if !reviews.is_empty() {
+        if !reviews.is_empty() && score_gate.is_ok() && severity_gate.is_ok() {
             agent.commit_changes().await?;
         }
         
@@ -455,12 +1321,323 @@ async fn main() -> Result<()> {
             .sum();
         
         println!("\n=== Review Summary ===");
+        println!("Profile: {} (config {})", report.meta.profile, report.meta.config_hash);
         println!("Files reviewed: {}", reviews.len());
         println!("Total issues found: {}", total_issues);
         println!("Total suggestions: {}", total_suggestions);
-        println!("Average score: {:.2}", 
-            reviews.iter().map(|r| r.score).sum::<f32>() / reviews.len() as f32);
+        let suppressed: usize = reviews.iter().map(|r| r.suppressed).sum();
+        if suppressed > 0 {
+            println!("Suppressed by devagent:ignore comments: {}", suppressed);
+        }
+        // No average without a reviewed file
+        if !reviews.is_empty() {
+            println!("Average score: {:.2} (unweighted {:.2})", 
+                reviews.iter().map(|r| r.score).sum::<f32>() / reviews.len() as f32,
+                reviews.iter().map(|r| r.raw_score).sum::<f32>() / reviews.len() as f32);
+        }
+        if let Some(skipped) = skipped::summary(&report.skipped) {
+            println!("{}", skipped);
+        }
+        
+        let deadline_fallbacks = reviews.iter()
+            .filter(|r| r.llm_analysis.as_ref().is_some_and(|llm| llm.deadline_fallback))
+            .count();
+        if deadline_fallbacks > 0 {
+            println!("Static fallback after --run-timeout: {} files skipped LLM analysis", deadline_fallbacks);
+        }
+        let chunked = reviews.iter()
+            .filter(|r| r.llm_analysis.as_ref().is_some_and(|llm| llm.chunks > 0))
+            .count();
+        if chunked > 0 {
+            println!("Chunked for the LLM context: {} files too large to analyze in one request", chunked);
+        }
+        
+        let packages = packages::summarize(reviews);
+        if packages.len() > 1 {
+            println!("\n=== By Package ===");
+            for package in &packages {
+                println!("{}: {} files, avg score {:.2}, {} issues {:?}",
+                    package.package, package.files, package.average_score, package.total_issues(), package.issues);
+            }
+        }
+        
+        severity_gate?;
+        score_gate?;
     }
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+async fn catch_panic<T>(future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+    AssertUnwindSafe(future).catch_unwind().await
+        .unwrap_or_else(|panic| Err(anyhow::anyhow!("Review panicked: {}", panic_message(&*panic))))
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic.downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_analyzer::IssueCategory;
+    use llm_agent::{LlmBackend, LlmConfig};
+    
+    pub(crate) fn review(file_path: &str, score: f32, severities: &[Severity]) -> CodeReview {
+        let issues = severities.iter().map(|severity| Issue {
+            rule: "rust/unsafe".to_string(),
+            severity: severity.clone(),
+            message: "Unsafe block".to_string(),
+            line: Some(1),
+            code: None,
+            category: IssueCategory::Security,
+        }).collect();
+        CodeReview {
+            id: file_path.to_string(),
+            file_path: file_path.to_string(),
+            package: String::new(),
+            issues,
+            suggestions: Vec::new(),
+            score,
+            raw_score: score,
+            suppressed: 0,
+            timestamp: DateTime::UNIX_EPOCH,
+            wasm_analysis: None,
+            llm_analysis: None,
+        }
+    }
+    
+    /// Agent for `args` answering from the mock LLM backend without a
+    /// cache, with its memory file under `scratch` instead of the working
+    /// directory.
+    pub(crate) async fn mock_agent(args: Args, scratch: &std::path::Path) -> DevAgent {
+        let mut agent = DevAgent::new(args, DevAgentConfig::default()).await.unwrap();
+        agent.llm_agent.set_config(LlmConfig { backend: LlmBackend::Mock, ..LlmConfig::default() });
+        agent.llm_agent.set_cache(None);
+        agent.memory_system.get_mut().set_memory_file(scratch.join("memory.json").to_string_lossy());
+        agent
+    }
+    
+    #[test]
+    fn critical_issues_fail_a_high_threshold() {
+        let reviews = [review("src/lib.rs", 0.5, &[Severity::Critical, Severity::Low]), review("src/main.rs", 0.9, &[Severity::High])];
+        let error = fail_on_gate(&reviews, &Severity::High).unwrap_err().to_string();
+        assert!(error.starts_with("2 issue(s) at or above High (1 Critical, 1 High):"), "{}", error);
+        assert!(error.contains("  src/lib.rs:1 Critical: Unsafe block"), "{}", error);
+        
+        let reviews = [review("src/main.rs", 0.9, &[Severity::High, Severity::Medium])];
+        assert!(fail_on_gate(&reviews, &Severity::Critical).is_ok());
+        assert!(fail_on_gate(&[], &Severity::Low).is_ok());
+    }
+    
+    #[test]
+    fn min_score_gates_each_file_or_the_average() {
+        let reviews = [review("src/lib.rs", 0.6, &[]), review("src/main.rs", 1.0, &[])];
+        
+        let error = min_score_gate(&reviews, 0.7, ScoreGate::File).unwrap_err().to_string();
+        assert_eq!(error, "1 file(s) scored below --min-score 0.70:\n  src/lib.rs (0.60)");
+        assert!(min_score_gate(&reviews, 0.7, ScoreGate::Average).is_ok());
+        
+        let error = min_score_gate(&reviews, 0.9, ScoreGate::Average).unwrap_err().to_string();
+        assert_eq!(error, "Average score 0.80 is below --min-score 0.90; files below it:\n  src/lib.rs (0.60)");
+        assert!(min_score_gate(&reviews, 0.6, ScoreGate::File).is_ok());
+        
+        // Nothing reviewed, nothing to fail
+        assert!(min_score_gate(&[], 1.0, ScoreGate::File).is_ok());
+        assert!(min_score_gate(&[], 1.0, ScoreGate::Average).is_ok());
+    }
+    
+    #[test]
+    fn command_line_limits_override_the_agent_config_defaults() {
+        let config = Args::parse_from(["devagent", "--jobs", "3", "--max-file-size", "2048"]).agent_config();
+        assert_eq!((config.max_concurrency, config.max_file_size), (3, 2048));
+        assert_eq!(config.file_timeout, DevAgentConfig::default().file_timeout);
+        
+        let config = Args::parse_from(["devagent"]).agent_config();
+        assert_eq!(config.max_concurrency, agent_config::default_jobs());
+        assert_eq!(config.max_file_size, DevAgentConfig::default().max_file_size);
+        
+        // Flags go through the same validation as embedder settings
+        assert!(Args::parse_from(["devagent", "--jobs", "0"]).agent_config().validate().is_err());
+    }
+    
+    #[tokio::test]
+    async fn deterministic_runs_send_a_seed_and_zero_temperature() {
+        use llm_agent::LlmAgentConfig;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server).await;
+        let generate = |options: serde_json::Value| Mock::given(method("POST")).and(path("/api/generate"))
+            .and(body_partial_json(serde_json::json!({ "options": options })))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"response\": \"- Add docs\", \"done\": true}\n"))
+            .expect(1);
+        
+        for (flags, options) in [
+            (&["--deterministic"][..], serde_json::json!({ "seed": 0, "temperature": 0.0 })),
+            (&["--deterministic", "--seed", "42"][..], serde_json::json!({ "seed": 42, "temperature": 0.0 })),
+        ] {
+            let args = Args::parse_from(["devagent"].iter().chain(flags));
+            let mut llm_agent = LlmAgent::new(LlmAgentConfig::ollama(server.uri())).await;
+            args.apply_sampling(&mut llm_agent);
+            
+            let _guard = generate(options).mount_as_scoped(&server).await;
+            let analysis = llm_agent.analyze_code("fn a() {}\n", std::path::Path::new("a.rs"), None).await.unwrap();
+            assert_eq!(analysis.ai_suggestions, vec!["Add docs"], "{:?}", flags);
+        }
+        
+        // Without the flags nothing is pinned
+        let args = Args::parse_from(["devagent"]);
+        assert_eq!((args.effective_seed(), args.deterministic), (None, false));
+        assert_eq!(Args::parse_from(["devagent", "--seed", "7"]).effective_seed(), Some(7));
+    }
+    
+    #[tokio::test]
+    async fn report_paths_are_relative_to_the_report_root() {
+        use std::io::Write;
+        
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "pub fn f() {}\n").unwrap();
+        std::fs::write(dir.path().join("src/nested/mod.rs"), "pub fn g() {}\n").unwrap();
+        let archive = scratch.path().join("release.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        zip.start_file("src/main.rs", zip::write::FileOptions::default()).unwrap();
+        zip.write_all(b"fn main() {}\n").unwrap();
+        zip.finish().unwrap();
+        
+        let reviewed = |path: PathBuf, root: Option<PathBuf>| {
+            let scratch = scratch.path().to_path_buf();
+            async move {
+                let mut args = Args::for_review(path, None, false);
+                args.root = root;
+                let report = mock_agent(args, &scratch).await.review_codebase().await.unwrap();
+                report.reviews.into_iter().map(|review| review.file_path).collect::<Vec<_>>()
+            }
+        };
+        
+        assert_eq!(reviewed(dir.path().to_path_buf(), None).await, ["src/lib.rs", "src/nested/mod.rs"]);
+        // A single file is reported relative to its directory, or to --root
+        let file = dir.path().join("src/nested/mod.rs");
+        assert_eq!(reviewed(file.clone(), None).await, ["mod.rs"]);
+        assert_eq!(reviewed(file, Some(dir.path().to_path_buf())).await, ["src/nested/mod.rs"]);
+        assert_eq!(reviewed(archive, None).await, ["release.zip!src/main.rs"]);
+    }
+    
+    #[tokio::test]
+    async fn a_panicking_file_is_skipped_and_the_others_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            std::fs::write(dir.path().join(name), "pub fn f() {}\n").unwrap();
+        }
+        let agent = mock_agent(Args::for_review(dir.path().to_path_buf(), None, false), scratch.path()).await;
+        
+        let mut report = agent.new_report();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            let path = dir.path().join(name);
+            let result = catch_panic(async {
+                if name == "b.rs" {
+                    panic!("analyzer bug in {}", name);
+                }
+                agent.review_file(&path).await
+            }).await;
+            agent.record_result(&mut report, name, result);
+        }
+        
+        let reviewed: Vec<&str> = report.reviews.iter().map(|review| review.file_path.as_str()).collect();
+        assert_eq!(reviewed, ["a.rs", "c.rs"]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].path, "b.rs");
+        assert_eq!(report.skipped[0].error, "Review panicked: analyzer bug in b.rs");
+    }
+    
+    #[tokio::test]
+    async fn at_reviews_files_as_they_were_at_the_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git").arg("-C").arg(repo).args(args).output().unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "dev@example.com"]);
+        git(&["config", "user.name", "Dev"]);
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::write(repo.join("src/lib.rs"), "pub fn f(x: Option<u8>) -> u8 { x.unwrap() }\n").unwrap();
+        std::fs::write(repo.join("src/old.rs"), "pub fn g() {}\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-qm", "First"]);
+        let first = git(&["rev-parse", "HEAD"]);
+        
+        // The working tree moves on: unwrap fixed, a file swapped for another
+        std::fs::write(repo.join("src/lib.rs"), "pub fn f(x: Option<u8>) -> u8 { x.unwrap_or(0) }\n").unwrap();
+        std::fs::remove_file(repo.join("src/old.rs")).unwrap();
+        std::fs::write(repo.join("src/new.rs"), "pub fn h() {}\n").unwrap();
+        
+        let mut args = Args::for_review(repo.to_path_buf(), None, false);
+        args.at = Some(first);
+        let report = mock_agent(args.clone(), scratch.path()).await.review_codebase().await.unwrap();
+        let reviewed: Vec<&str> = report.reviews.iter().map(|review| review.file_path.as_str()).collect();
+        assert_eq!(reviewed, ["src/lib.rs", "src/old.rs"]);
+        assert!(report.reviews[0].issues.iter().any(|issue| issue.rule == "rust/unwrap"));
+        
+        args.at = Some("no-such-commit".to_string());
+        let error = mock_agent(args, scratch.path()).await.review_codebase().await.unwrap_err();
+        assert!(error.to_string().contains("no-such-commit"), "{:#}", error);
+    }
+    
+    #[tokio::test]
+    async fn an_expired_run_timeout_still_writes_every_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        for name in ["a.rs", "b.rs"] {
+            std::fs::write(dir.path().join(name), "pub fn f() {}\n").unwrap();
+        }
+        let output = scratch.path().join("report.json");
+        let mut args = Args::for_review(dir.path().to_path_buf(), Some(output.clone()), false);
+        args.run_timeout = Some(1);
+        
+        // The model would answer long after the budget runs out
+        let mut agent = mock_agent(args, scratch.path()).await;
+        agent.llm_agent.set_config(LlmConfig { backend: LlmBackend::Mock, mock_latency_ms: 60_000, ..LlmConfig::default() });
+        let started = std::time::Instant::now();
+        let report = agent.review_codebase().await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(30));
+        agent.save_reviews(&report).await.unwrap();
+        
+        let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        let reviews = saved["reviews"].as_array().unwrap();
+        assert_eq!(reviews.iter().map(|review| review["file_path"].as_str().unwrap()).collect::<Vec<_>>(), ["a.rs", "b.rs"]);
+        assert!(reviews.iter().all(|review| review["llm_analysis"]["deadline_fallback"] == true), "{}", saved);
+    }
+    
+    #[tokio::test]
+    async fn review_ids_are_stable_across_runs_and_written_to_the_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "pub fn f() {}\n").unwrap();
+        let args = Args::for_review(dir.path().to_path_buf(), None, false);
+        
+        let first = mock_agent(args.clone(), scratch.path()).await.review_codebase().await.unwrap();
+        let second = mock_agent(args, scratch.path()).await.review_codebase().await.unwrap();
+        let id = DevAgent::review_id(std::path::Path::new("src/lib.rs"));
+        assert_eq!(first.reviews[0].id, id);
+        assert_eq!(second.reviews[0].id, id);
+        assert_ne!(DevAgent::review_id(std::path::Path::new("src/main.rs")), id);
+        
+        let json = serde_json::to_value(&first).unwrap();
+        assert_eq!(json["reviews"][0]["id"], id.as_str());
+        assert_eq!(json["meta"]["config_hash"], first.meta.config_hash.as_str());
+    }
+}
@@ -0,0 +1,93 @@
+/// A fenced code block from a Markdown file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    /// First word of the info string, e.g. `rust` for ```` ```rust,no_run ````
+    pub language: String,
+    pub content: String,
+    /// 1-based line of the opening fence; block line `n` is at
+    /// `line_offset + n` in the Markdown file
+    pub line_offset: usize,
+}
+
+/// Fenced (``` or ~~~) code blocks that have a language tag. Indented
+/// blocks and untagged fences are skipped since their language is unknown.
+/// An unclosed fence runs to the end of the file, as in CommonMark.
+pub fn code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut lines = markdown.lines().enumerate();
+    
+    while let Some((index, line)) = lines.next() {
+        let Some(fence) = Fence::open(line) else {
+            continue;
+        };
+        
+        let mut content = Vec::new();
+        for (_, line) in lines.by_ref() {
+            if fence.closes(line) {
+                break;
+            }
+            content.push(fence.strip_indent(line));
+        }
+        
+        if !fence.language.is_empty() {
+            blocks.push(CodeBlock {
+                language: fence.language,
+                content: content.join("\n"),
+                line_offset: index + 1,
+            });
+        }
+    }
+    
+    blocks
+}
+
+struct Fence {
+    marker: char,
+    length: usize,
+    indent: usize,
+    language: String,
+}
+
+impl Fence {
+    fn open(line: &str) -> Option<Self> {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        // Four spaces of indentation make an indented code block instead
+        if indent > 3 {
+            return None;
+        }
+        
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+        let length = trimmed.chars().take_while(|c| *c == marker).count();
+        if length < 3 {
+            return None;
+        }
+        
+        let info = &trimmed[length..];
+        // Backtick fences can't have backticks in their info string
+        if marker == '`' && info.contains('`') {
+            return None;
+        }
+        
+        let language = info
+            .trim()
+            .split(|c: char| c.is_whitespace() || c == ',' || c == '{' || c == '}')
+            .find(|word| !word.is_empty())
+            .unwrap_or_default()
+            .to_lowercase();
+        
+        Some(Self { marker, length, indent, language })
+    }
+    
+    fn closes(&self, line: &str) -> bool {
+        let trimmed = line.trim_start();
+        let run = trimmed.chars().take_while(|c| *c == self.marker).count();
+        line.len() - trimmed.len() <= 3 && run >= self.length && trimmed[run..].trim().is_empty()
+    }
+    
+    // Content lines lose up to the fence's own indentation
+    fn strip_indent<'a>(&self, line: &'a str) -> &'a str {
+        let spaces = line.len() - line.trim_start_matches(' ').len();
+        &line[spaces.min(self.indent)..]
+    }
+}
@@ -1,12 +1,19 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+use crate::redact;
+use crate::semantic_search::SemanticIndex;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MemoryEntry {
     pub id: String,
@@ -62,6 +69,72 @@ pub struct MemoryMetadata {
 pub struct MemorySystem {
     memory_file: String,
     entries: HashMap<String, MemoryEntry>,
+    max_cache_bytes: Option<usize>,
+    saves: Arc<SaveOrder>,
+    semantic: Option<Arc<SemanticIndex>>,
+}
+
+// Snapshots of the memory file are numbered as they're taken, and the lock
+// holding the newest number written is held from writing the temp file
+// until it's renamed into place, so a late, older snapshot is dropped.
+#[derive(Default)]
+struct SaveOrder {
+    taken: AtomicU64,
+    written: tokio::sync::Mutex<u64>,
+}
+
+/// A file added to memory but not yet saved or indexed, from
+/// `MemorySystem::stage_file`. Callers sharing the system behind a lock
+/// can `persist` it after releasing the lock.
+#[must_use = "the file is only saved and indexed by `persist`"]
+pub struct StagedFile {
+    save: PendingSave,
+    file_id: String,
+    content: String,
+    semantic: Option<Arc<SemanticIndex>>,
+}
+
+impl StagedFile {
+    pub async fn persist(self) -> Result<()> {
+        self.save.write().await?;
+        
+        // The file is stored either way; it just won't turn up in
+        // semantic search until it's indexed
+        if let Some(semantic) = &self.semantic {
+            if let Err(e) = semantic.index(&self.file_id, &self.content).await {
+                warn!("Failed to index {} for semantic search: {:#}", self.file_id, e);
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+struct PendingSave {
+    memory_file: String,
+    json: String,
+    number: u64,
+    order: Arc<SaveOrder>,
+}
+
+impl PendingSave {
+    async fn write(self) -> Result<()> {
+        let mut written = self.order.written.lock().await;
+        if *written > self.number {
+            return Ok(());
+        }
+        
+        let json = self.json;
+        write_atomically(Path::new(&self.memory_file), |mut file| async move {
+            file.write_all(json.as_bytes()).await?;
+            file.sync_all().await?;
+            Ok(())
+        })
+        .await
+        .context("Failed to write memory file")?;
+        *written = self.number;
+        Ok(())
+    }
 }
 
 impl MemorySystem {
@@ -94,18 +167,45 @@ impl MemorySystem {
         Ok(Self {
             memory_file,
             entries,
+            max_cache_bytes: None,
+            saves: Arc::default(),
+            semantic: None,
         })
     }
     
-    pub async fn store_file(&mut self, file_id: &str, content: &str) -> Result<()> {
+    /// Caps the bytes of file content kept in memory; unbounded by default.
+    pub fn set_max_cache_bytes(&mut self, max_cache_bytes: usize) {
+        self.max_cache_bytes = Some(max_cache_bytes);
+    }
+    
+    /// Where the memory is saved; `dev_agent_memory.json` in the working
+    /// directory by default.
+    pub fn set_memory_file(&mut self, memory_file: impl Into<String>) {
+        self.memory_file = memory_file.into();
+    }
+    
+    /// Indexes stored files for `search_semantic`; without one only
+    /// `search_files` is available.
+    pub fn set_semantic_index(&mut self, index: SemanticIndex) {
+        self.semantic = Some(Arc::new(index));
+    }
+    
+    pub async fn store_file(&mut self, file_id: &str, file_path: &str, content: &str) -> Result<()> {
+        self.stage_file(file_id, file_path, content)?.persist().await
+    }
+    
+    /// Adds the file to memory, leaving the slow part, saving the memory
+    /// file and indexing it, to the returned `StagedFile`.
+    pub fn stage_file(&mut self, file_id: &str, file_path: &str, content: &str) -> Result<StagedFile> {
         info!("Storing file in memory: {}", file_id);
         
-        let metadata = self.extract_metadata(content);
+        let metadata = Self::extract_metadata(content);
+        let stored = redact::stored(content).into_owned();
         
         let entry = MemoryEntry {
             id: file_id.to_string(),
-            file_path: file_id.to_string(), // Will be updated when we have actual path
-            content: content.to_string(),
+            file_path: file_path.to_string(),
+            content: stored.clone(),
             analysis_results: None,
             metadata,
             created_at: Utc::now(),
@@ -113,9 +213,16 @@ impl MemorySystem {
         };
         
         self.entries.insert(file_id.to_string(), entry);
-        self.save_memory().await?;
+        self.evict_over_budget(file_id);
+        let json = serde_json::to_string_pretty(&self.entries)
+            .context("Failed to serialize memory")?;
         
-        Ok(())
+        Ok(StagedFile {
+            save: self.pending_save(json),
+            file_id: file_id.to_string(),
+            content: stored,
+            semantic: self.semantic.clone(),
+        })
     }
     
     pub async fn update_analysis(&mut self, file_id: &str, analysis: AnalysisResults) -> Result<()> {
@@ -150,6 +257,20 @@ impl MemorySystem {
         results
     }
     
+    /// The `top_k` stored files closest in meaning to `query`, with their
+    /// similarity, best first. Unlike `search_files` it finds related code
+    /// that doesn't contain the query text.
+    pub async fn search_semantic(&self, query: &str, top_k: usize) -> Result<Vec<(&MemoryEntry, f32)>> {
+        let semantic = self.semantic.as_ref()
+            .context("Semantic search is disabled; enable [semantic_search] in .devagent.toml")?;
+        
+        // Evicted files can still be in the index
+        let hits = semantic.search(query, top_k).await?;
+        Ok(hits.into_iter()
+            .filter_map(|(file_id, score)| Some((self.entries.get(&file_id)?, score)))
+            .collect())
+    }
+    
     pub async fn get_recent_files(&self, limit: usize) -> Vec<&MemoryEntry> {
         let mut entries: Vec<&MemoryEntry> = self.entries.values().collect();
         entries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
@@ -206,10 +327,10 @@ impl MemorySystem {
         }
     }
     
-    fn extract_metadata(&self, content: &str) -> MemoryMetadata {
+    pub(crate) fn extract_metadata(content: &str) -> MemoryMetadata {
         let file_size = content.len();
-        let language = self.detect_language(content);
-        let tags = self.extract_tags(content);
+        let language = Self::detect_language(content);
+        let tags = Self::extract_tags(content);
         
         MemoryMetadata {
             file_size,
@@ -219,7 +340,7 @@ impl MemorySystem {
         }
     }
     
-    fn detect_language(&self, content: &str) -> String {
+    fn detect_language(content: &str) -> String {
         // Simple language detection based on file content patterns
         if content.contains("fn ") && content.contains("use ") {
             "rust".to_string()
@@ -238,7 +359,7 @@ impl MemorySystem {
         }
     }
     
-    fn extract_tags(&self, content: &str) -> Vec<String> {
+    fn extract_tags(content: &str) -> Vec<String> {
         let mut tags = Vec::new();
         
         // Extract TODO tags
@@ -268,14 +389,55 @@ impl MemorySystem {
         tags
     }
     
+    /// Drops the least recently updated entries, never `keep`, until the
+    /// stored content fits `max_cache_bytes`.
+    fn evict_over_budget(&mut self, keep: &str) {
+        let Some(budget) = self.max_cache_bytes else {
+            return;
+        };
+        
+        let mut total: usize = self.entries.values().map(|entry| entry.content.len()).sum();
+        if total <= budget {
+            return;
+        }
+        
+        let mut oldest: Vec<(DateTime<Utc>, String)> = self.entries.iter()
+            .filter(|(id, _)| id.as_str() != keep)
+            .map(|(id, entry)| (entry.updated_at, id.clone()))
+            .collect();
+        oldest.sort();
+        
+        for (_, id) in oldest {
+            if total <= budget {
+                break;
+            }
+            if let Some(entry) = self.entries.remove(&id) {
+                total -= entry.content.len();
+            }
+        }
+        
+        if total > budget {
+            warn!("Memory entry {} alone exceeds the {} byte cache budget", keep, budget);
+        }
+    }
+    
     async fn save_memory(&self) -> Result<()> {
         let json = serde_json::to_string_pretty(&self.entries)
             .context("Failed to serialize memory")?;
-        
-        fs::write(&self.memory_file, json).await
-            .context("Failed to write memory file")?;
-        
-        Ok(())
+        self.write_memory_file(json).await
+    }
+    
+    async fn write_memory_file(&self, json: String) -> Result<()> {
+        self.pending_save(json).write().await
+    }
+    
+    fn pending_save(&self, json: String) -> PendingSave {
+        PendingSave {
+            memory_file: self.memory_file.clone(),
+            json,
+            number: self.saves.taken.fetch_add(1, Ordering::Relaxed) + 1,
+            order: self.saves.clone(),
+        }
     }
     
     fn calculate_memory_size(&self) -> usize {
@@ -285,6 +447,43 @@ impl MemorySystem {
     }
 }
 
+/// Replaces `path` with what `write` puts in `<path>.tmp`, by renaming it
+/// over `path` once `write` succeeds. The rename is atomic on one
+/// filesystem, so a failed or interrupted write leaves `path` as it was.
+pub(crate) async fn write_atomically<F, Fut>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(fs::File) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let mut tmp = PathBuf::from(path).into_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    
+    let file = fs::File::create(&tmp).await
+        .with_context(|| format!("Failed to create {}", tmp.display()))?;
+    if let Err(e) = write(file).await {
+        let _ = fs::remove_file(&tmp).await;
+        return Err(e);
+    }
+    
+    fs::rename(&tmp, path).await
+        .with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VacuumReport {
+    pub removed_entries: usize,
+    pub cleared_analyses: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl VacuumReport {
+    pub fn bytes_reclaimed(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MemoryStatistics {
     pub total_files: usize,
@@ -302,6 +501,48 @@ impl MemorySystem {
         Ok(())
     }
     
+    /// Removes entries whose source file is gone, clears analyses last
+    /// updated before `max_age` ago, and rewrites the file without pretty
+    /// printing.
+    pub async fn vacuum(&mut self, max_age: chrono::Duration) -> Result<VacuumReport> {
+        info!("Vacuuming memory file: {}", self.memory_file);
+        
+        let bytes_before = fs::metadata(&self.memory_file).await
+            .map(|m| m.len())
+            .unwrap_or(0);
+        
+        let before = self.entries.len();
+        // Archive members are stored as "archive!entry"; keep them while
+        // the archive itself exists.
+        self.entries.retain(|_, entry| {
+            let source = entry.file_path.split('!').next().unwrap_or(&entry.file_path);
+            Path::new(source).exists()
+        });
+        let removed_entries = before - self.entries.len();
+        
+        let cutoff = Utc::now() - max_age;
+        let mut cleared_analyses = 0;
+        for entry in self.entries.values_mut() {
+            if entry.updated_at < cutoff && entry.analysis_results.take().is_some() {
+                cleared_analyses += 1;
+            }
+        }
+        
+        let json = serde_json::to_string(&self.entries)
+            .context("Failed to serialize memory")?;
+        let bytes_after = json.len() as u64;
+        self.write_memory_file(json).await?;
+        
+        info!("Removed {} entries, cleared {} stale analyses", removed_entries, cleared_analyses);
+        
+        Ok(VacuumReport {
+            removed_entries,
+            cleared_analyses,
+            bytes_before,
+            bytes_after,
+        })
+    }
+    
     pub async fn export_memory(&self, export_path: &str) -> Result<()> {
         info!("Exporting memory to: {}", export_path);
         
@@ -323,13 +564,201 @@ impl MemorySystem {
         let imported_entries: HashMap<String, MemoryEntry> = serde_json::from_str(&content)
             .context("Failed to parse import file")?;
         
+        // On a key collision the more recently updated entry wins
+        let count = imported_entries.len();
+        let mut kept_existing = 0;
         for (key, entry) in imported_entries {
-            self.entries.insert(key, entry);
+            match self.entries.get(&key) {
+                Some(existing) if existing.updated_at > entry.updated_at => kept_existing += 1,
+                _ => {
+                    self.entries.insert(key, entry);
+                }
+            }
         }
         
         self.save_memory().await?;
-        info!("Imported {} entries", imported_entries.len());
+        info!("Imported {} entries ({} older than the entry already in memory were skipped)", count - kept_existing, kept_existing);
         
         Ok(())
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn importing_memory_keeps_the_newer_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory_file = dir.path().join("memory.json");
+        let import_file = dir.path().join("import.json");
+        
+        let mut memory = MemorySystem::new().await.unwrap();
+        memory.set_memory_file(memory_file.to_string_lossy());
+        memory.store_file("a", "a.rs", "fn current() {}").await.unwrap();
+        memory.store_file("b", "b.rs", "fn old() {}").await.unwrap();
+        
+        // An export from a day earlier, with a newer copy of `b` only
+        let mut exported: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&memory_file).unwrap()).unwrap();
+        let yesterday = serde_json::to_value(chrono::Utc::now() - chrono::Duration::days(1)).unwrap();
+        exported["a"]["content"] = "fn stale() {}".into();
+        exported["a"]["updated_at"] = yesterday;
+        exported["b"]["content"] = "fn new() {}".into();
+        exported["b"]["updated_at"] = serde_json::to_value(chrono::Utc::now() + chrono::Duration::days(1)).unwrap();
+        std::fs::write(&import_file, exported.to_string()).unwrap();
+        
+        memory.import_memory(&import_file.to_string_lossy()).await.unwrap();
+        assert_eq!(memory.get_file("a").await.unwrap().content, "fn current() {}");
+        assert_eq!(memory.get_file("b").await.unwrap().content, "fn new() {}");
+    }
+    
+    #[tokio::test]
+    async fn failed_memory_writes_leave_the_previous_file_intact() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory_file = dir.path().join("memory.json");
+        
+        let large = "fn f() {}\n".repeat(100_000);
+        let mut memory = MemorySystem::new().await.unwrap();
+        memory.set_memory_file(memory_file.to_string_lossy());
+        memory.store_file("large", "large.rs", &large).await.unwrap();
+        let saved = std::fs::read_to_string(&memory_file).unwrap();
+        assert!(!dir.path().join("memory.json.tmp").exists());
+        
+        // A writer that fails halfway through
+        let result = write_atomically(&memory_file, |mut file| async move {
+            file.write_all(&saved.as_bytes()[..saved.len() / 2]).await?;
+            anyhow::bail!("disk full")
+        })
+        .await;
+        
+        assert!(result.is_err());
+        assert!(!dir.path().join("memory.json.tmp").exists());
+        let entries: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&memory_file).unwrap()).unwrap();
+        assert_eq!(entries["large"]["content"], large.as_str());
+    }
+    
+    #[tokio::test]
+    async fn semantic_search_finds_related_files_without_the_query_text() {
+        use crate::semantic_search::{Embedder, VectorStore};
+        
+        // Counts words from a few topics, so "login" lands near "password"
+        struct TopicEmbedder;
+        
+        #[async_trait::async_trait]
+        impl Embedder for TopicEmbedder {
+            async fn embed(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+                let topics: [&[&str]; 3] = [&["login", "password", "session"], &["render", "pixel", "canvas"], &["query", "table", "row"]];
+                Ok(texts.iter()
+                    .map(|text| topics.iter()
+                        .map(|words| words.iter().map(|word| text.matches(word).count() as f32).sum())
+                        .collect())
+                    .collect())
+            }
+        }
+        
+        #[derive(Default)]
+        struct InMemoryStore(std::sync::Mutex<Vec<(String, Vec<f32>)>>);
+        
+        #[async_trait::async_trait]
+        impl VectorStore for InMemoryStore {
+            async fn replace(&self, file_id: &str, vectors: Vec<Vec<f32>>) -> anyhow::Result<()> {
+                let mut points = self.0.lock().unwrap();
+                points.retain(|(id, _)| id != file_id);
+                points.extend(vectors.into_iter().map(|vector| (file_id.to_string(), vector)));
+                Ok(())
+            }
+            
+            async fn nearest(&self, vector: Vec<f32>, limit: usize) -> anyhow::Result<Vec<(String, f32)>> {
+                let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt().max(f32::EPSILON);
+                let mut hits: Vec<(String, f32)> = self.0.lock().unwrap().iter()
+                    .map(|(id, v)| (id.clone(), v.iter().zip(&vector).map(|(a, b)| a * b).sum::<f32>() / (norm(v) * norm(&vector))))
+                    .collect();
+                hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+                hits.truncate(limit);
+                Ok(hits)
+            }
+        }
+        
+        let dir = tempfile::tempdir().unwrap();
+        let memory_file = dir.path().join("memory.json");
+        let mut memory = MemorySystem::new().await.unwrap();
+        memory.set_memory_file(memory_file.to_string_lossy());
+        assert!(memory.search_semantic("login", 1).await.is_err());
+        
+        memory.set_semantic_index(SemanticIndex::new(Box::new(TopicEmbedder), Box::new(InMemoryStore::default()), 2));
+        memory.store_file("auth", "auth.rs", "fn check(password: &str) {}\n// expire the session\n\nfn draw() { canvas; session }").await.unwrap();
+        memory.store_file("ui", "ui.rs", "fn render(canvas: &Canvas) {}\n// one pixel at a time").await.unwrap();
+        memory.store_file("db", "db.rs", "fn fetch(table: &str) -> row {}").await.unwrap();
+        
+        assert!(memory.search_files("login").await.is_empty());
+        let found = memory.search_semantic("login", 1).await.unwrap();
+        assert_eq!(found.iter().map(|(entry, _)| entry.id.as_str()).collect::<Vec<_>>(), vec!["auth"]);
+        assert!((found[0].1 - 1.0).abs() < 1e-6);
+        
+        // Each file appears once, scored by its best chunk
+        let found = memory.search_semantic("canvas pixel", 2).await.unwrap();
+        assert_eq!(found.iter().map(|(entry, _)| entry.id.as_str()).collect::<Vec<_>>(), vec!["ui", "auth"]);
+    }
+    
+    #[tokio::test]
+    async fn vacuum_drops_missing_files_and_stale_analyses() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        for name in ["kept.rs", "stale.rs", "bundle.zip"] {
+            std::fs::write(path(name), "").unwrap();
+        }
+        let analysis = || AnalysisResults {
+            code_metrics: CodeMetrics {
+                lines_of_code: 1,
+                function_count: 1,
+                complexity_score: 1.0,
+                maintainability_score: 1.0,
+                security_score: 1.0,
+            },
+            issues: vec!["Unsafe block".to_string()],
+            suggestions: Vec::new(),
+            wasm_analysis: None,
+            llm_analysis: None,
+        };
+        
+        let mut memory = MemorySystem::new().await.unwrap();
+        memory.set_memory_file(path("memory.json"));
+        for (id, file) in [("kept", path("kept.rs")), ("stale", path("stale.rs")), ("gone", path("gone.rs"))] {
+            memory.store_file(id, &file, "fn f() {}").await.unwrap();
+            memory.update_analysis(id, analysis()).await.unwrap();
+        }
+        memory.store_file("member", &format!("{}!src/lib.rs", path("bundle.zip")), "fn g() {}").await.unwrap();
+        memory.store_file("orphan", &format!("{}!src/lib.rs", path("missing.zip")), "fn h() {}").await.unwrap();
+        memory.entries.get_mut("stale").unwrap().updated_at = Utc::now() - chrono::Duration::days(30);
+        
+        let report = memory.vacuum(chrono::Duration::days(7)).await.unwrap();
+        assert_eq!((report.removed_entries, report.cleared_analyses), (2, 1));
+        assert!(report.bytes_after < report.bytes_before, "{:?}", (report.bytes_before, report.bytes_after));
+        assert_eq!(std::fs::metadata(path("memory.json")).unwrap().len(), report.bytes_after);
+        
+        let mut ids: Vec<&str> = memory.entries.keys().map(String::as_str).collect();
+        ids.sort();
+        assert_eq!(ids, ["kept", "member", "stale"]);
+        assert!(memory.get_file("kept").await.unwrap().analysis_results.is_some());
+        assert!(memory.get_file("stale").await.unwrap().analysis_results.is_none());
+    }
+    
+    #[tokio::test]
+    async fn staged_files_persisted_out_of_order_keep_the_newest_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory_file = dir.path().join("memory.json");
+        let mut memory = MemorySystem::new().await.unwrap();
+        memory.set_memory_file(memory_file.to_string_lossy());
+        
+        let first = memory.stage_file("a", "a.rs", "fn a() {}").unwrap();
+        let second = memory.stage_file("b", "b.rs", "fn b() {}").unwrap();
+        assert!(!memory_file.exists());
+        
+        second.persist().await.unwrap();
+        first.persist().await.unwrap();
+        let saved: HashMap<String, MemoryEntry> = serde_json::from_str(&std::fs::read_to_string(&memory_file).unwrap()).unwrap();
+        let mut ids: Vec<&str> = saved.keys().map(String::as_str).collect();
+        ids.sort();
+        assert_eq!(ids, ["a", "b"]);
+    }
+}
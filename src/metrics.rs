@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use prometheus::{
+    Counter, Encoder, Gauge, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+
+use crate::CodeReview;
+
+/// Prometheus metrics updated as reviews run and served on `/metrics`.
+#[derive(Clone)]
+pub struct ReviewMetrics {
+    registry: Registry,
+    files_reviewed: IntCounter,
+    issues: IntCounterVec,
+    score_total: Counter,
+    average_score: Gauge,
+    llm_latency: Histogram,
+    queue_depth: IntGaugeVec,
+}
+
+impl ReviewMetrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+        
+        let files_reviewed = IntCounter::new("devagent_files_reviewed_total", "Files reviewed since start")?;
+        let issues = IntCounterVec::new(
+            Opts::new("devagent_issues_total", "Issues found, by severity"),
+            &["severity"],
+        )?;
+        let score_total = Counter::new("devagent_score_sum", "Sum of file scores, for averaging")?;
+        let average_score = Gauge::new("devagent_average_score", "Average file score since start")?;
+        let llm_latency = Histogram::with_opts(
+            HistogramOpts::new("devagent_llm_request_seconds", "LLM analysis latency")
+                .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]),
+        )?;
+        let queue_depth = IntGaugeVec::new(
+            Opts::new("devagent_queue_depth", "Pending items per work queue"),
+            &["queue"],
+        )?;
+        
+        registry.register(Box::new(files_reviewed.clone()))?;
+        registry.register(Box::new(issues.clone()))?;
+        registry.register(Box::new(score_total.clone()))?;
+        registry.register(Box::new(average_score.clone()))?;
+        registry.register(Box::new(llm_latency.clone()))?;
+        registry.register(Box::new(queue_depth.clone()))?;
+        
+        Ok(Self {
+            registry,
+            files_reviewed,
+            issues,
+            score_total,
+            average_score,
+            llm_latency,
+            queue_depth,
+        })
+    }
+    
+    pub fn record_review(&self, review: &CodeReview) {
+        self.files_reviewed.inc();
+        
+        for issue in &review.issues {
+            let severity = format!("{:?}", issue.severity).to_lowercase();
+            self.issues.with_label_values(&[severity.as_str()]).inc();
+        }
+        
+        self.score_total.inc_by(f64::from(review.score));
+        self.average_score.set(self.score_total.get() / self.files_reviewed.get() as f64);
+    }
+    
+    pub fn observe_llm_latency(&self, seconds: f64) {
+        self.llm_latency.observe(seconds);
+    }
+    
+    pub fn set_queue_depth(&self, queue: &str, depth: usize) {
+        self.queue_depth.with_label_values(&[queue]).set(depth as i64);
+    }
+    
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .context("Failed to encode metrics")?;
+        
+        String::from_utf8(buffer).context("Metrics output was not UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_analyzer::Severity;
+    use crate::tests::review;
+    
+    #[test]
+    fn reviews_and_latencies_are_exported() {
+        let metrics = ReviewMetrics::new().unwrap();
+        metrics.record_review(&review("src/lib.rs", 0.5, &[Severity::Critical, Severity::High]));
+        metrics.record_review(&review("src/main.rs", 1.0, &[Severity::High]));
+        metrics.observe_llm_latency(0.5);
+        metrics.observe_llm_latency(3.0);
+        metrics.set_queue_depth("files", 3);
+        
+        let rendered = metrics.render().unwrap();
+        let lines: Vec<&str> = rendered.lines().filter(|line| !line.starts_with('#')).collect();
+        for expected in [
+            "devagent_files_reviewed_total 2",
+            "devagent_issues_total{severity=\"critical\"} 1",
+            "devagent_issues_total{severity=\"high\"} 2",
+            "devagent_score_sum 1.5",
+            "devagent_average_score 0.75",
+            "devagent_llm_request_seconds_bucket{le=\"0.25\"} 0",
+            "devagent_llm_request_seconds_bucket{le=\"0.5\"} 1",
+            "devagent_llm_request_seconds_bucket{le=\"5\"} 2",
+            "devagent_llm_request_seconds_sum 3.5",
+            "devagent_llm_request_seconds_count 2",
+            "devagent_queue_depth{queue=\"files\"} 3",
+        ] {
+            assert!(lines.contains(&expected), "missing {:?} in\n{}", expected, rendered);
+        }
+    }
+}
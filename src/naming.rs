@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use syn::visit::{self, Visit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Case {
+    SnakeCase,
+    CamelCase,
+    PascalCase,
+    ScreamingSnakeCase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierKind {
+    Function,
+    Variable,
+    Type,
+}
+
+/// Expected case per identifier kind; `None` turns that check off.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamingConventions {
+    pub functions: Option<Case>,
+    pub variables: Option<Case>,
+    pub types: Option<Case>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NamingViolation {
+    pub name: String,
+    pub kind: IdentifierKind,
+    pub line: usize,
+    pub expected: Case,
+}
+
+impl Case {
+    pub fn matches(&self, name: &str) -> bool {
+        // Leading underscores mark intentionally unused names
+        let name = name.trim_start_matches(['_', '$']);
+        let Some(first) = name.chars().next() else { return true };
+        
+        match self {
+            Case::SnakeCase => name.chars().all(|c| c.is_lowercase() || c.is_ascii_digit() || c == '_'),
+            Case::ScreamingSnakeCase => name.chars().all(|c| c.is_uppercase() || c.is_ascii_digit() || c == '_'),
+            Case::CamelCase => first.is_lowercase() && !name.contains('_'),
+            Case::PascalCase => first.is_uppercase() && !name.contains('_'),
+        }
+    }
+    
+    pub fn convert(&self, name: &str) -> String {
+        let words = split_words(name);
+        
+        match self {
+            Case::SnakeCase => words.join("_"),
+            Case::ScreamingSnakeCase => words.join("_").to_uppercase(),
+            Case::PascalCase => words.iter().map(|w| capitalize(w)).collect(),
+            Case::CamelCase => words.iter()
+                .enumerate()
+                .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+                .collect(),
+        }
+    }
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            Case::SnakeCase => "snake_case",
+            Case::CamelCase => "camelCase",
+            Case::PascalCase => "PascalCase",
+            Case::ScreamingSnakeCase => "SCREAMING_SNAKE_CASE",
+        }
+    }
+}
+
+impl IdentifierKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IdentifierKind::Function => "Function",
+            IdentifierKind::Variable => "Variable",
+            IdentifierKind::Type => "Type",
+        }
+    }
+}
+
+impl NamingConventions {
+    fn new(functions: Case, variables: Option<Case>, types: Case) -> Self {
+        Self {
+            functions: Some(functions),
+            variables,
+            types: Some(types),
+        }
+    }
+    
+    pub fn defaults() -> HashMap<String, NamingConventions> {
+        let mut defaults = HashMap::new();
+        
+        defaults.insert(
+            "rust".to_string(),
+            Self::new(Case::SnakeCase, Some(Case::SnakeCase), Case::PascalCase),
+        );
+        defaults.insert(
+            "python".to_string(),
+            Self::new(Case::SnakeCase, None, Case::PascalCase),
+        );
+        defaults.insert(
+            "javascript".to_string(),
+            Self::new(Case::CamelCase, Some(Case::CamelCase), Case::PascalCase),
+        );
+        
+        defaults
+    }
+    
+    fn expected(&self, kind: IdentifierKind) -> Option<Case> {
+        match kind {
+            IdentifierKind::Function => self.functions,
+            IdentifierKind::Variable => self.variables,
+            IdentifierKind::Type => self.types,
+        }
+    }
+    
+    /// Checks identifiers pulled from a parsed Rust file.
+    pub fn check_rust(&self, ast: &syn::File) -> Vec<NamingViolation> {
+        let mut collector = RustIdentifierCollector::default();
+        collector.visit_file(ast);
+        self.violations(collector.identifiers)
+    }
+    
+    /// Checks declarations found by line scanning, for languages without
+    /// a parser here.
+    pub fn check_lines(&self, language: &str, content: &str) -> Vec<NamingViolation> {
+        let identifiers = content
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let (name, kind) = declared_identifier(language, line.trim())?;
+                Some((name, kind, i + 1))
+            })
+            .collect();
+        
+        self.violations(identifiers)
+    }
+    
+    fn violations(&self, identifiers: Vec<(String, IdentifierKind, usize)>) -> Vec<NamingViolation> {
+        identifiers
+            .into_iter()
+            .filter_map(|(name, kind, line)| {
+                let expected = self.expected(kind)?;
+                if expected.matches(&name) {
+                    return None;
+                }
+                
+                Some(NamingViolation { name, kind, line, expected })
+            })
+            .collect()
+    }
+}
+
+fn declared_identifier(language: &str, line: &str) -> Option<(String, IdentifierKind)> {
+    let (rest, kind) = match language {
+        "python" => {
+            if let Some(rest) = line.strip_prefix("def ").or_else(|| line.strip_prefix("async def ")) {
+                (rest, IdentifierKind::Function)
+            } else {
+                (line.strip_prefix("class ")?, IdentifierKind::Type)
+            }
+        }
+        "javascript" => {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let line = line.strip_prefix("async ").unwrap_or(line);
+            
+            if let Some(rest) = line.strip_prefix("function ") {
+                (rest.trim_start_matches('*').trim_start(), IdentifierKind::Function)
+            } else if let Some(rest) = line.strip_prefix("class ") {
+                (rest, IdentifierKind::Type)
+            } else if let Some(rest) = line.strip_prefix("const ") {
+                // Module-level constants are conventionally SCREAMING_SNAKE_CASE
+                let name = identifier_prefix(rest);
+                if Case::ScreamingSnakeCase.matches(&name) {
+                    return None;
+                }
+                (rest, IdentifierKind::Variable)
+            } else {
+                let rest = line.strip_prefix("let ").or_else(|| line.strip_prefix("var "))?;
+                (rest, IdentifierKind::Variable)
+            }
+        }
+        _ => return None,
+    };
+    
+    // Destructuring patterns and the like have no single name to check
+    let name = identifier_prefix(rest);
+    if name.is_empty() {
+        return None;
+    }
+    
+    Some((name, kind))
+}
+
+fn identifier_prefix(text: &str) -> String {
+    text.chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect()
+}
+
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous: Option<char> = None;
+    
+    for c in name.chars() {
+        if c == '_' || c == '$' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+        } else {
+            // Boundary on lower->upper ("fooBar") and digit->upper ("v2Api")
+            let boundary = c.is_uppercase()
+                && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit());
+            if boundary && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            current.extend(c.to_lowercase());
+        }
+        previous = Some(c);
+    }
+    
+    if !current.is_empty() {
+        words.push(current);
+    }
+    
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[derive(Default)]
+struct RustIdentifierCollector {
+    identifiers: Vec<(String, IdentifierKind, usize)>,
+}
+
+impl RustIdentifierCollector {
+    fn push(&mut self, ident: &syn::Ident, kind: IdentifierKind) {
+        let name = ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name).to_string();
+        self.identifiers.push((name, kind, ident.span().start().line));
+    }
+}
+
+impl<'ast> Visit<'ast> for RustIdentifierCollector {
+    fn visit_signature(&mut self, sig: &'ast syn::Signature) {
+        self.push(&sig.ident, IdentifierKind::Function);
+        visit::visit_signature(self, sig);
+    }
+    
+    fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+        self.push(&pat.ident, IdentifierKind::Variable);
+        visit::visit_pat_ident(self, pat);
+    }
+    
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        self.push(&item.ident, IdentifierKind::Type);
+        visit::visit_item_struct(self, item);
+    }
+    
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        self.push(&item.ident, IdentifierKind::Type);
+        visit::visit_item_enum(self, item);
+    }
+    
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.push(&item.ident, IdentifierKind::Type);
+        visit::visit_item_trait(self, item);
+    }
+    
+    fn visit_item_type(&mut self, item: &'ast syn::ItemType) {
+        self.push(&item.ident, IdentifierKind::Type);
+        visit::visit_item_type(self, item);
+    }
+}
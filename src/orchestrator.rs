@@ -1,7 +1,8 @@
 use std::path::PathBuf;
-use std::process::{Child, Command};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error};
@@ -14,6 +15,15 @@ use prost::Message;
 use qdrant_client::prelude::*;
 use qdrant_client::qdrant::vectors_config::Config as VectorConfig;
 
+use crate::subprocess::ChildProcess;
+use crate::supervisor::{self, RestartPolicy};
+use crate::whisper::WhisperProcess;
+use crate::worker_channel::WorkerChannel;
+
+type ProcessHandle = Arc<Mutex<ChildProcess>>;
+
+const WORKER_QUEUE_CAPACITY: usize = 1000;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OrchestratorConfig {
     pub whisper_path: PathBuf,
@@ -25,6 +35,34 @@ pub struct OrchestratorConfig {
     pub indradb_url: String,
     pub gpu_enabled: bool,
     pub max_concurrent_requests: usize,
+    /// Upper bound on the total size of cached values, in bytes
+    #[serde(default = "OrchestratorConfig::default_memory_cache_bytes")]
+    pub memory_cache_bytes: usize,
+    /// Times each subprocess is restarted after crashing before it's left
+    /// down
+    #[serde(default = "OrchestratorConfig::default_max_restarts")]
+    pub max_restarts: u32,
+    /// Longest a transcription, generation or synthesis may take
+    #[serde(default = "OrchestratorConfig::default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+impl OrchestratorConfig {
+    fn default_memory_cache_bytes() -> usize {
+        256 * 1024 * 1024
+    }
+    
+    fn default_max_restarts() -> u32 {
+        5
+    }
+    
+    fn default_request_timeout_secs() -> u64 {
+        30
+    }
+    
+    fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -75,23 +113,97 @@ pub struct Orchestrator {
     config: OrchestratorConfig,
     
     // Subprocess handles
-    whisper_process: Arc<Mutex<Option<Child>>>,
-    llama_process: Arc<Mutex<Option<Child>>>,
-    piper_process: Arc<Mutex<Option<Child>>>,
+    whisper_process: Arc<Mutex<WhisperProcess>>,
+    llama_process: ProcessHandle,
+    piper_process: ProcessHandle,
     
     // Memory system clients
     qdrant_client: Arc<Mutex<QdrantClient>>,
     
-    // IPC channels
-    stt_tx: tokio::sync::mpsc::Sender<AudioChunk>,
-    stt_rx: tokio::sync::mpsc::Receiver<STTResult>,
-    llm_tx: tokio::sync::mpsc::Sender<LLMRequest>,
-    llm_rx: tokio::sync::mpsc::Receiver<LLMResponse>,
-    tts_tx: tokio::sync::mpsc::Sender<TTSRequest>,
-    tts_rx: tokio::sync::mpsc::Receiver<TTSResponse>,
+    // Request and response channels to each worker
+    stt: WorkerChannel<AudioChunk, STTResult>,
+    llm: WorkerChannel<LLMRequest, LLMResponse>,
+    tts: WorkerChannel<TTSRequest, TTSResponse>,
     
     // Memory cache
-    memory_cache: Arc<Mutex<std::collections::HashMap<String, Vec<u8>>>>,
+    memory_cache: Arc<Mutex<MemoryCache>>,
+    
+    // Tasks restarting crashed subprocesses
+    supervisors: Vec<JoinHandle<()>>,
+}
+
+/// LRU cache bounded by the total size of its values rather than the
+/// number of entries.
+pub struct MemoryCache {
+    entries: std::collections::HashMap<String, (Vec<u8>, u64)>,
+    // Last-use tick -> key; the first entry is the least recently used
+    recency: std::collections::BTreeMap<u64, String>,
+    tick: u64,
+    total_bytes: usize,
+    budget_bytes: usize,
+}
+
+impl MemoryCache {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            entries: std::collections::HashMap::new(),
+            recency: std::collections::BTreeMap::new(),
+            tick: 0,
+            total_bytes: 0,
+            budget_bytes,
+        }
+    }
+    
+    pub fn insert(&mut self, key: String, value: Vec<u8>) {
+        self.remove(&key);
+        
+        if value.len() > self.budget_bytes {
+            warn!("Not caching {}: {} bytes exceeds the {} byte budget", key, value.len(), self.budget_bytes);
+            return;
+        }
+        
+        while self.total_bytes + value.len() > self.budget_bytes {
+            let Some((_, oldest)) = self.recency.pop_first() else { break };
+            if let Some((evicted, _)) = self.entries.remove(&oldest) {
+                self.total_bytes -= evicted.len();
+            }
+        }
+        
+        self.tick += 1;
+        self.total_bytes += value.len();
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(key, (value, self.tick));
+    }
+    
+    pub fn get(&mut self, key: &str) -> Option<&[u8]> {
+        let (_, last_used) = self.entries.get_mut(key)?;
+        
+        self.tick += 1;
+        self.recency.remove(&*last_used);
+        self.recency.insert(self.tick, key.to_string());
+        *last_used = self.tick;
+        
+        self.entries.get(key).map(|(value, _)| value.as_slice())
+    }
+    
+    pub fn remove(&mut self, key: &str) -> Option<Vec<u8>> {
+        let (value, last_used) = self.entries.remove(key)?;
+        self.recency.remove(&last_used);
+        self.total_bytes -= value.len();
+        Some(value)
+    }
+    
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
 }
 
 impl Orchestrator {
@@ -105,24 +217,37 @@ impl Orchestrator {
                 .await?,
         )));
         
-        // Create IPC channels
-        let (stt_tx, stt_rx) = tokio::sync::mpsc::channel(1000);
-        let (llm_tx, llm_rx) = tokio::sync::mpsc::channel(1000);
-        let (tts_tx, tts_rx) = tokio::sync::mpsc::channel(1000);
-        
-        let orchestrator = Self {
-            config,
-            whisper_process: Arc::new(Mutex::new(None)),
-            llama_process: Arc::new(Mutex::new(None)),
-            piper_process: Arc::new(Mutex::new(None)),
+        // Start background workers; they reach the subprocesses through
+        // the shared handles, which are filled in below
+        let whisper_process = Arc::new(Mutex::new(WhisperProcess::new(
+            config.whisper_path.clone(),
+            Self::whisper_args(&config),
+        )));
+        let llama_process = Arc::new(Mutex::new(ChildProcess::new(
+            "Llama.cpp",
+            config.llama_path.clone(),
+            Self::llama_args(&config),
+        )));
+        let piper_process = Arc::new(Mutex::new(ChildProcess::new(
+            "Piper TTS",
+            config.piper_path.clone(),
+            Self::piper_args(&config),
+        )));
+        let stt = Self::start_stt_worker(whisper_process.clone());
+        let llm = Self::start_llm_worker(llama_process.clone());
+        let tts = Self::start_tts_worker(piper_process.clone());
+        
+        let mut orchestrator = Self {
+            whisper_process,
+            llama_process,
+            piper_process,
             qdrant_client: Arc::new(Mutex::new(qdrant_client)),
-            stt_tx,
-            stt_rx,
-            llm_tx,
-            llm_rx,
-            tts_tx,
-            tts_rx,
-            memory_cache: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            stt,
+            llm,
+            tts,
+            memory_cache: Arc::new(Mutex::new(MemoryCache::new(config.memory_cache_bytes))),
+            supervisors: Vec::new(),
+            config,
         };
         
         // Start subprocesses
@@ -130,10 +255,13 @@ impl Orchestrator {
         orchestrator.start_llama_process().await?;
         orchestrator.start_piper_process().await?;
         
-        // Start background workers
-        orchestrator.start_stt_worker().await;
-        orchestrator.start_llm_worker().await;
-        orchestrator.start_tts_worker().await;
+        let policy = RestartPolicy::new(orchestrator.config.max_restarts);
+        orchestrator.supervisors = vec![
+            supervisor::supervise("Whisper.cpp", orchestrator.whisper_process.clone(), policy.clone()),
+            supervisor::supervise("Llama.cpp", orchestrator.llama_process.clone(), policy.clone()),
+            supervisor::supervise("Piper TTS", orchestrator.piper_process.clone(), policy),
+        ];
+        
         orchestrator.start_memory_worker().await;
         
         Ok(orchestrator)
@@ -142,174 +270,116 @@ impl Orchestrator {
     pub async fn process_audio(&self, audio_chunk: AudioChunk) -> Result<STTResult> {
         info!("Processing audio chunk for STT");
         
-        // Send to STT worker
-        self.stt_tx.send(audio_chunk).await
-            .context("Failed to send audio to STT worker")?;
-        
-        // Wait for result
-        let result = self.stt_rx.recv().await
-            .context("Failed to receive STT result")?;
-        
-        Ok(result)
+        self.stt.call_timeout(audio_chunk, self.config.request_timeout()).await
+            .context("Failed to transcribe audio")
     }
     
     pub async fn generate_response(&self, request: LLMRequest) -> Result<LLMResponse> {
         info!("Generating LLM response");
         
-        // Send to LLM worker
-        self.llm_tx.send(request).await
-            .context("Failed to send request to LLM worker")?;
-        
-        // Wait for result
-        let response = self.llm_rx.recv().await
-            .context("Failed to receive LLM response")?;
-        
-        Ok(response)
+        self.llm.call_timeout(request, self.config.request_timeout()).await
+            .context("Failed to generate LLM response")
     }
     
     pub async fn synthesize_speech(&self, request: TTSRequest) -> Result<TTSResponse> {
         info!("Synthesizing speech");
         
-        // Send to TTS worker
-        self.tts_tx.send(request).await
-            .context("Failed to send request to TTS worker")?;
-        
-        // Wait for result
-        let response = self.tts_rx.recv().await
-            .context("Failed to receive TTS response")?;
-        
-        Ok(response)
+        self.tts.call_timeout(request, self.config.request_timeout()).await
+            .context("Failed to synthesize speech")
     }
     
-    async fn start_whisper_process(&self) -> Result<()> {
-        info!("Starting Whisper.cpp process");
-        
-        let mut cmd = Command::new(&self.config.whisper_path);
-        cmd.arg("--model")
-           .arg("base")
-           .arg("--output-format")
-           .arg("json")
-           .arg("--stdin");
-        
-        if self.config.gpu_enabled {
-            cmd.arg("--gpu-layers").arg("32");
+    fn whisper_args(config: &OrchestratorConfig) -> Vec<String> {
+        let mut args: Vec<String> = ["--model", "base", "--output-format", "json", "--stdin"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        
+        if config.gpu_enabled {
+            args.extend(["--gpu-layers".to_string(), "32".to_string()]);
         }
         
-        let child = cmd.spawn()?;
-        
-        {
-            let mut process = self.whisper_process.lock().await;
-            *process = Some(child);
+        args
+    }
+    
+    async fn start_whisper_process(&self) -> Result<()> {
+        self.whisper_process.lock().await.start()
+    }
+    
+    fn llama_args(config: &OrchestratorConfig) -> Vec<String> {
+        let mut args = vec![
+            "-m".to_string(),
+            config.model_path.display().to_string(),
+            "--ctx-size".to_string(),
+            "4096".to_string(),
+            "--temp".to_string(),
+            "0.7".to_string(),
+            "--repeat-penalty".to_string(),
+            "1.1".to_string(),
+        ];
+        
+        if config.gpu_enabled {
+            args.extend(["--n-gpu-layers".to_string(), "32".to_string()]);
         }
         
-        Ok(())
+        args
     }
     
     async fn start_llama_process(&self) -> Result<()> {
-        info!("Starting Llama.cpp process");
-        
-        let mut cmd = Command::new(&self.config.llama_path);
-        cmd.arg("-m")
-           .arg(&self.config.model_path)
-           .arg("--ctx-size")
-           .arg("4096")
-           .arg("--temp")
-           .arg("0.7")
-           .arg("--repeat-penalty")
-           .arg("1.1");
-        
-        if self.config.gpu_enabled {
-            cmd.arg("--n-gpu-layers").arg("32");
-        }
-        
-        let child = cmd.spawn()?;
-        
-        {
-            let mut process = self.llama_process.lock().await;
-            *process = Some(child);
-        }
-        
-        Ok(())
+        self.llama_process.lock().await.start()
+    }
+    
+    fn piper_args(config: &OrchestratorConfig) -> Vec<String> {
+        vec![
+            "--model".to_string(),
+            config.voice_model_path.display().to_string(),
+            "--output-format".to_string(),
+            "wav".to_string(),
+        ]
     }
     
     async fn start_piper_process(&self) -> Result<()> {
-        info!("Starting Piper TTS process");
-        
-        let mut cmd = Command::new(&self.config.piper_path);
-        cmd.arg("--model")
-           .arg(&self.config.voice_model_path)
-           .arg("--output-format")
-           .arg("wav");
-        
-        let child = cmd.spawn()?;
-        
-        {
-            let mut process = self.piper_process.lock().await;
-            *process = Some(child);
-        }
-        
-        Ok(())
+        self.piper_process.lock().await.start()
     }
     
-    async fn start_stt_worker(&self) {
-        let whisper_process = self.whisper_process.clone();
-        let stt_tx = self.stt_tx.clone();
-        
-        tokio::spawn(async move {
-            info!("STT Worker started");
-            
-            while let Some(audio_chunk) = stt_tx.recv().await {
-                // Process audio with Whisper.cpp
-                if let Ok(result) = Self::process_whisper_audio(audio_chunk, &whisper_process).await {
-                    // Send result back
-                    if let Err(e) = stt_tx.send(result).await {
-                        error!("Failed to send STT result: {}", e);
-                    }
-                }
-            }
-        });
+    fn start_stt_worker(whisper_process: Arc<Mutex<WhisperProcess>>) -> WorkerChannel<AudioChunk, STTResult> {
+        WorkerChannel::spawn("STT", WORKER_QUEUE_CAPACITY, move |audio_chunk| {
+            let whisper_process = whisper_process.clone();
+            async move { Self::process_whisper_audio(audio_chunk, &whisper_process).await }
+        })
     }
     
-    async fn start_llm_worker(&self) {
-        let llama_process = self.llama_process.clone();
-        let llm_tx = self.llm_tx.clone();
-        
-        tokio::spawn(async move {
-            info!("LLM Worker started");
-            
-            while let Some(request) = llm_tx.recv().await {
-                // Process with Llama.cpp
-                if let Ok(response) = Self::process_llama_request(request, &llama_process).await {
-                    // Send result back
-                    if let Err(e) = llm_tx.send(response).await {
-                        error!("Failed to send LLM response: {}", e);
-                    }
-                }
-            }
-        });
+    fn start_llm_worker(llama_process: ProcessHandle) -> WorkerChannel<LLMRequest, LLMResponse> {
+        WorkerChannel::spawn("LLM", WORKER_QUEUE_CAPACITY, move |request| {
+            let llama_process = llama_process.clone();
+            async move { Self::process_llama_request(request, &llama_process).await }
+        })
     }
     
-    async fn start_tts_worker(&self) {
-        let piper_process = self.piper_process.clone();
-        let tts_tx = self.tts_tx.clone();
-        
-        tokio::spawn(async move {
-            info!("TTS Worker started");
-            
-            while let Some(request) = tts_tx.recv().await {
-                // Process with Piper
-                if let Ok(response) = Self::process_piper_request(request, &piper_process).await {
-                    // Send result back
-                    if let Err(e) = tts_tx.send(response).await {
-                        error!("Failed to send TTS response: {}", e);
-                    }
-                }
-            }
-        });
+    fn start_tts_worker(piper_process: ProcessHandle) -> WorkerChannel<TTSRequest, TTSResponse> {
+        WorkerChannel::spawn("TTS", WORKER_QUEUE_CAPACITY, move |request| {
+            let piper_process = piper_process.clone();
+            async move { Self::process_piper_request(request, &piper_process).await }
+        })
+    }
+    
+    /// Messages waiting in each worker channel.
+    pub fn queue_depths(&self) -> Vec<(&'static str, usize)> {
+        vec![
+            ("stt", self.stt.queue_depth()),
+            ("llm", self.llm.queue_depth()),
+            ("tts", self.tts.queue_depth()),
+        ]
+    }
+    
+    pub async fn cache_insert(&self, key: String, value: Vec<u8>) {
+        self.memory_cache.lock().await.insert(key, value);
+    }
+    
+    pub async fn cache_get(&self, key: &str) -> Option<Vec<u8>> {
+        self.memory_cache.lock().await.get(key).map(|value| value.to_vec())
     }
     
     async fn start_memory_worker(&self) {
-        let qdrant_client = self.qdrant_client.clone();
         let memory_cache = self.memory_cache.clone();
         
         tokio::spawn(async move {
@@ -319,37 +389,36 @@ impl Orchestrator {
             loop {
                 tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
                 
-                // Clean up cache
+                // The cache evicts on insert; just report its footprint
                 {
-                    let mut cache = memory_cache.lock().await;
-                    if cache.len() > 1000 {
-                        cache.clear();
-                    }
+                    let cache = memory_cache.lock().await;
+                    info!("Memory cache: {} entries, {} bytes", cache.len(), cache.total_bytes());
                 }
                 
-                // Sync with Qdrant
-                if let Ok(client) = qdrant_client.lock().await {
-                    // TODO: Implement memory sync
-                }
+                // TODO: Sync with Qdrant
             }
         });
     }
     
     async fn process_whisper_audio(
         audio_chunk: AudioChunk,
-        whisper_process: &Arc<Mutex<Option<Child>>>,
+        whisper_process: &Mutex<WhisperProcess>,
     ) -> Result<STTResult> {
-        // TODO: Implement actual Whisper.cpp communication
+        // Restarts the child first if it has died
+        let transcription = whisper_process.lock().await
+            .transcribe(&audio_chunk.data, audio_chunk.sample_rate)
+            .await?;
+        
         Ok(STTResult {
-            text: "voice command detected".to_string(),
-            confidence: 0.9,
+            text: transcription.text,
+            confidence: transcription.confidence,
             timestamp: chrono::Utc::now(),
         })
     }
     
     async fn process_llama_request(
         request: LLMRequest,
-        llama_process: &Arc<Mutex<Option<Child>>>,
+        llama_process: &ProcessHandle,
     ) -> Result<LLMResponse> {
         // TODO: Implement actual Llama.cpp communication
         Ok(LLMResponse {
@@ -361,7 +430,7 @@ impl Orchestrator {
     
     async fn process_piper_request(
         request: TTSRequest,
-        piper_process: &Arc<Mutex<Option<Child>>>,
+        piper_process: &ProcessHandle,
     ) -> Result<TTSResponse> {
         // TODO: Implement actual Piper communication
         Ok(TTSResponse {
@@ -374,19 +443,63 @@ impl Orchestrator {
     pub async fn shutdown(&self) -> Result<()> {
         info!("Shutting down orchestrator");
         
-        // Terminate subprocesses
-        if let Some(mut process) = self.whisper_process.lock().await.take() {
-            let _ = process.kill();
-        }
-        
-        if let Some(mut process) = self.llama_process.lock().await.take() {
-            let _ = process.kill();
+        // Stop the supervisors first, or they would restart what we kill
+        for supervisor in &self.supervisors {
+            supervisor.abort();
         }
         
-        if let Some(mut process) = self.piper_process.lock().await.take() {
-            let _ = process.kill();
-        }
+        // Terminate subprocesses
+        self.whisper_process.lock().await.kill().await;
+        self.llama_process.lock().await.kill().await;
+        self.piper_process.lock().await.kill().await;
         
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn reading_an_entry_protects_it_from_eviction() {
+        let mut cache = MemoryCache::new(10);
+        cache.insert("a".to_string(), vec![0; 4]);
+        cache.insert("b".to_string(), vec![0; 4]);
+        assert!(cache.get("a").is_some());
+        
+        // `b` is now the least recently used
+        cache.insert("c".to_string(), vec![0; 4]);
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.total_bytes(), 8);
+    }
+    
+    #[test]
+    fn reinserting_a_key_replaces_its_size() {
+        let mut cache = MemoryCache::new(10);
+        cache.insert("a".to_string(), vec![0; 4]);
+        cache.insert("a".to_string(), vec![1; 6]);
+        assert_eq!((cache.len(), cache.total_bytes()), (1, 6));
+        assert_eq!(cache.get("a"), Some(&[1; 6][..]));
+        
+        // Fits alongside the new `a` without evicting it
+        cache.insert("b".to_string(), vec![0; 4]);
+        assert_eq!((cache.len(), cache.total_bytes()), (2, 10));
+    }
+    
+    #[test]
+    fn values_over_the_budget_are_not_cached() {
+        let mut cache = MemoryCache::new(10);
+        cache.insert("a".to_string(), vec![0; 4]);
+        cache.insert("huge".to_string(), vec![0; 11]);
+        assert!(cache.get("huge").is_none());
+        assert_eq!((cache.len(), cache.total_bytes()), (1, 4));
+        
+        // Replacing an entry with an oversized value drops the old one
+        cache.insert("a".to_string(), vec![0; 11]);
+        assert!(cache.is_empty());
+        assert_eq!(cache.total_bytes(), 0);
+    }
+}
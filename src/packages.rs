@@ -0,0 +1,198 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::Serialize;
+
+use crate::CodeReview;
+
+/// Files that mark the root of a crate or package.
+const MANIFESTS: [&str; 4] = ["Cargo.toml", "package.json", "pyproject.toml", "go.mod"];
+
+/// Group used for files that sit directly in the report root.
+pub const ROOT_PACKAGE: &str = "(root)";
+
+/// Maps files to the crate or package that owns them: the nearest manifest
+/// above the file, named after its declared package name when it has one.
+/// Files with no manifest below the report root are grouped by their
+/// top-level directory, which covers polyglot monorepos.
+pub struct PackageResolver {
+    root: PathBuf,
+    // Manifest lookups per directory, shared by every file in it
+    cache: Mutex<HashMap<PathBuf, Option<String>>>,
+}
+
+impl PackageResolver {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    pub fn resolve(&self, file_path: &Path) -> String {
+        let absolute = std::fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+        
+        if absolute.starts_with(&self.root) {
+            let mut dir = absolute.parent();
+            while let Some(current) = dir.filter(|d| d.starts_with(&self.root)) {
+                if let Some(name) = self.package_in(current) {
+                    return name;
+                }
+                dir = current.parent();
+            }
+        }
+        
+        // No manifest (or not on disk, e.g. archive entries): top-level directory
+        let relative = absolute.strip_prefix(&self.root).unwrap_or(file_path);
+        let mut components = relative.components();
+        match (components.next(), components.next()) {
+            (Some(first), Some(_)) => first.as_os_str().to_string_lossy().to_string(),
+            _ => ROOT_PACKAGE.to_string(),
+        }
+    }
+    
+    fn package_in(&self, dir: &Path) -> Option<String> {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.entry(dir.to_path_buf())
+            .or_insert_with(|| self.read_manifest(dir))
+            .clone()
+    }
+    
+    fn read_manifest(&self, dir: &Path) -> Option<String> {
+        let manifest = MANIFESTS.iter().map(|name| dir.join(name)).find(|path| path.is_file())?;
+        let content = std::fs::read_to_string(&manifest).unwrap_or_default();
+        
+        let declared = match manifest.file_name().and_then(|name| name.to_str()) {
+            Some("Cargo.toml") => {
+                let value: toml::Value = toml::from_str(&content).ok()?;
+                // A virtual workspace manifest owns no files itself
+                value.get("package")?.get("name")?.as_str().map(str::to_string)
+            }
+            Some("pyproject.toml") => toml::from_str::<toml::Value>(&content).ok().and_then(|value| {
+                let project = value.get("project").or_else(|| value.get("tool")?.get("poetry"))?;
+                project.get("name")?.as_str().map(str::to_string)
+            }),
+            Some("package.json") => serde_json::from_str::<serde_json::Value>(&content).ok()
+                .and_then(|value| value.get("name")?.as_str().map(str::to_string)),
+            _ => None,
+        };
+        
+        declared.or_else(|| {
+            let relative = dir.strip_prefix(&self.root).ok()?;
+            if relative.as_os_str().is_empty() {
+                Some(ROOT_PACKAGE.to_string())
+            } else {
+                Some(relative.to_string_lossy().replace('\\', "/"))
+            }
+        })
+    }
+}
+
+/// Per-package rollup of a review run.
+#[derive(Debug, Serialize)]
+pub struct PackageSummary {
+    pub package: String,
+    pub files: usize,
+    pub average_score: f32,
+    /// Issue counts keyed by severity name
+    pub issues: BTreeMap<String, usize>,
+}
+
+impl PackageSummary {
+    pub fn total_issues(&self) -> usize {
+        self.issues.values().sum()
+    }
+}
+
+/// Rolls reviews up per package, sorted by package name.
+pub fn summarize(reviews: &[CodeReview]) -> Vec<PackageSummary> {
+    let mut grouped: BTreeMap<&str, Vec<&CodeReview>> = BTreeMap::new();
+    for review in reviews {
+        grouped.entry(review.package.as_str()).or_default().push(review);
+    }
+    
+    grouped
+        .into_iter()
+        .map(|(package, reviews)| {
+            let mut issues = BTreeMap::new();
+            for issue in reviews.iter().flat_map(|review| &review.issues) {
+                *issues.entry(format!("{:?}", issue.severity)).or_insert(0) += 1;
+            }
+            
+            PackageSummary {
+                package: package.to_string(),
+                files: reviews.len(),
+                average_score: reviews.iter().map(|review| review.score).sum::<f32>() / reviews.len() as f32,
+                issues,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_analyzer::Severity;
+    use crate::tests::review;
+    
+    #[test]
+    fn files_belong_to_the_nearest_named_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        // A virtual workspace manifest owns nothing, so the root falls through
+        write("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n");
+        write("crates/core/Cargo.toml", "[package]\nname = \"core-lib\"\n");
+        write("crates/core/src/parser/mod.rs", "");
+        write("crates/unnamed/Cargo.toml", "[workspace]\n");
+        write("crates/unnamed/src/lib.rs", "");
+        write("web/package.json", "{\"name\": \"web-ui\"}");
+        write("web/src/app.js", "");
+        write("tools/py/pyproject.toml", "[tool.poetry]\nname = \"tools-py\"\n");
+        write("tools/py/run.py", "");
+        write("scripts/deploy.sh", "");
+        write("build.rs", "");
+        
+        let resolver = PackageResolver::new(root.clone());
+        let package = |path: &str| resolver.resolve(&root.join(path));
+        assert_eq!(package("crates/core/src/parser/mod.rs"), "core-lib");
+        assert_eq!(package("crates/unnamed/src/lib.rs"), "crates");
+        assert_eq!(package("web/src/app.js"), "web-ui");
+        assert_eq!(package("tools/py/run.py"), "tools-py");
+        assert_eq!(package("scripts/deploy.sh"), "scripts");
+        assert_eq!(package("build.rs"), ROOT_PACKAGE);
+        
+        // Not on disk, like an archive entry: its top-level directory
+        assert_eq!(resolver.resolve(Path::new("docs/guide.rs")), "docs");
+    }
+    
+    #[test]
+    fn summaries_average_scores_and_count_issues_per_package() {
+        let in_package = |package: &str, path: &str, score: f32, severities: &[Severity]| {
+            let mut review = review(path, score, severities);
+            review.package = package.to_string();
+            review
+        };
+        let reviews = [
+            in_package("web-ui", "web/a.js", 0.5, &[Severity::High, Severity::Low]),
+            in_package("core-lib", "core/a.rs", 1.0, &[]),
+            in_package("web-ui", "web/b.js", 0.9, &[Severity::Low]),
+        ];
+        
+        let summaries = summarize(&reviews);
+        let names: Vec<&str> = summaries.iter().map(|summary| summary.package.as_str()).collect();
+        assert_eq!(names, ["core-lib", "web-ui"]);
+        
+        let web = &summaries[1];
+        assert_eq!(web.files, 2);
+        assert!((web.average_score - 0.7).abs() < 1e-6);
+        assert_eq!(web.issues, BTreeMap::from([("High".to_string(), 1), ("Low".to_string(), 2)]));
+        assert_eq!(web.total_issues(), 3);
+        assert_eq!((summaries[0].total_issues(), summaries[0].average_score), (0, 1.0));
+        assert!(summarize(&[]).is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+use similar::TextDiff;
+
+/// Lines of context around each change, as `git diff` uses.
+const CONTEXT_LINES: usize = 3;
+
+/// `content` with its `line`th line (from 1) replaced by `replacement`,
+/// which may span several lines. `None` if the file is shorter.
+pub fn replace_line(content: &str, line: usize, replacement: &str) -> Option<String> {
+    let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let target = *lines.get(line.checked_sub(1)?)?;
+    
+    // Keep the file's line endings, and a missing newline at the end
+    let newline = if target.ends_with("\r\n") { "\r\n" } else { "\n" };
+    let ending = if target.ends_with('\n') { newline } else { "" };
+    let replaced = format!("{}{}", replacement.replace('\n', newline), ending);
+    lines[line - 1] = &replaced;
+    Some(lines.concat())
+}
+
+/// A unified diff from `original` to `modified` that `git apply` takes
+/// from the repository root, `path` being relative to it. `None` when
+/// nothing changed.
+pub fn unified_diff(path: &str, original: &str, modified: &str) -> Option<String> {
+    if original == modified {
+        return None;
+    }
+    
+    let diff = TextDiff::from_lines(original, modified);
+    let patch = diff.unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string();
+    Some(patch)
+}
+
+/// `<path>-L<line>-<title>.patch` with anything but letters, digits, `.`
+/// and `-` turned into `_`, so names are the same on every run and safe
+/// on every platform.
+pub fn file_name(path: &str, line: usize, title: &str) -> String {
+    let clean = |text: &str| -> String {
+        text.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .collect()
+    };
+    format!("{}-L{}-{}.patch", clean(path), line, clean(&title.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    
+    #[test]
+    fn patches_apply_cleanly_with_git() {
+        let original = "fn main() {\n    let a = 1;\n    let b = 2;\n    let c = 3;\n    let d = 4;\n    println!(\"{}\", a + b + c + d);\n}\n";
+        let modified = replace_line(original, 6, "    // Sum them all\n    println!(\"{}\", a + b + c + d);").unwrap();
+        assert_eq!(replace_line(original, 8, "x"), None);
+        assert_eq!(unified_diff("src/main.rs", original, original), None);
+        
+        let patch = unified_diff("src/main.rs", original, &modified).unwrap();
+        assert!(patch.starts_with("--- a/src/main.rs\n+++ b/src/main.rs\n@@ -3,5 +3,6 @@\n"), "{}", patch);
+        
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::write(repo.join("src/main.rs"), original).unwrap();
+        let name = file_name("src/main.rs", 6, "Add documentation");
+        assert_eq!(name, "src_main.rs-L6-add_documentation.patch");
+        std::fs::write(repo.join(&name), &patch).unwrap();
+        
+        let git = |args: &[&str]| Command::new("git").args(args).current_dir(repo).output().unwrap();
+        assert!(git(&["init", "-q"]).status.success());
+        let applied = git(&["apply", &name]);
+        assert!(applied.status.success(), "{}", String::from_utf8_lossy(&applied.stderr));
+        assert_eq!(std::fs::read_to_string(repo.join("src/main.rs")).unwrap(), modified);
+        
+        // A file without a final newline keeps it missing
+        assert_eq!(replace_line("a\nb", 2, "// b\nb").as_deref(), Some("a\n// b\nb"));
+    }
+}
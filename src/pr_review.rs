@@ -0,0 +1,102 @@
+use std::path::Path;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::git_ops::{self, LineRange};
+use crate::DevAgent;
+
+/// Payload shaped like the GitHub "create a review" API body, so a thin
+/// script can post it as-is.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrReviewPayload {
+    pub body: String,
+    pub event: String,
+    pub comments: Vec<ReviewComment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReviewComment {
+    pub path: String,
+    pub line: usize,
+    pub side: String,
+    pub body: String,
+}
+
+pub async fn review_changes(agent: &DevAgent, base: &str) -> Result<PrReviewPayload> {
+    let review_path = &agent.args.path;
+    let root = git_ops::repo_root(review_path).await?;
+    let changes = git_ops::changed_lines(review_path, base).await?;
+    
+    info!("Found {} changed files against {}", changes.len(), base);
+    
+    let mut comments = Vec::new();
+    
+    for (relative_path, ranges) in &changes {
+        let file_path = root.join(relative_path);
+        
+        if !agent.is_code_file(&file_path) {
+            continue;
+        }
+        
+        let content = match fs::read_to_string(&file_path).await {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("Skipping {}: {}", file_path.display(), e);
+                continue;
+            }
+        };
+        
+        comments.extend(comments_for_file(agent, relative_path, &file_path, &content, ranges).await?);
+    }
+    
+    comments.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    
+    let body = if comments.is_empty() {
+        format!("DevAgent found no issues in lines changed since {}.", base)
+    } else {
+        format!("DevAgent found {} issues in lines changed since {}.", comments.len(), base)
+    };
+    
+    Ok(PrReviewPayload {
+        body,
+        event: "COMMENT".to_string(),
+        comments,
+    })
+}
+
+async fn comments_for_file(
+    agent: &DevAgent,
+    relative_path: &Path,
+    file_path: &Path,
+    content: &str,
+    ranges: &[LineRange],
+) -> Result<Vec<ReviewComment>> {
+    // Analyze the whole file so rules see full context, then keep only
+    // findings that land on changed lines. Subdirectory `.devagent.toml`
+    // files apply as in a full review.
+    let directory_analyzer = agent.directory_analyzer(file_path).await?;
+    let analyzer = directory_analyzer.as_deref().unwrap_or(&agent.code_analyzer);
+    let issues = analyzer.analyze_code(content, file_path).await
+        .with_context(|| format!("Failed to analyze {}", file_path.display()))?;
+    
+    let path = relative_path.to_string_lossy().replace('\\', "/");
+    
+    Ok(issues
+        .into_iter()
+        .filter_map(|issue| {
+            let line = issue.line?;
+            if !ranges.iter().any(|range| range.contains(line)) {
+                return None;
+            }
+            
+            Some(ReviewComment {
+                path: path.clone(),
+                line,
+                side: "RIGHT".to_string(),
+                body: format!("**{:?}** ({:?}): {}", issue.severity, issue.category, issue.message),
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Process-wide so analyzers deep in the call tree (syn parsing, pattern
+// matching) can record without threading a handle through every call.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TOTALS: Mutex<BTreeMap<&'static str, StageTotal>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, Copy, Default)]
+struct StageTotal {
+    elapsed: Duration,
+    calls: u64,
+}
+
+/// Starts accumulating stage timings (`--profile-cpu`). Until then
+/// `measure` only costs an atomic load.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn record(stage: &'static str, elapsed: Duration) {
+    let mut totals = TOTALS.lock().unwrap_or_else(|e| e.into_inner());
+    let total = totals.entry(stage).or_default();
+    total.elapsed += elapsed;
+    total.calls += 1;
+}
+
+/// Runs `f`, adding its duration to `stage` when profiling is enabled.
+pub fn measure<T>(stage: &'static str, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    
+    let started = Instant::now();
+    let output = f();
+    record(stage, started.elapsed());
+    output
+}
+
+/// Async `measure`. The time includes waiting, which is the point for
+/// stages like the LLM call.
+pub async fn measure_async<F: Future>(stage: &'static str, future: F) -> F::Output {
+    if !is_enabled() {
+        return future.await;
+    }
+    
+    let started = Instant::now();
+    let output = future.await;
+    record(stage, started.elapsed());
+    output
+}
+
+/// Breakdown of every stage over the whole run, slowest first. Stages
+/// nest (syn parsing is part of static analysis), so they don't sum to
+/// the run time.
+pub fn report() -> String {
+    let totals = TOTALS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut stages: Vec<(&&str, &StageTotal)> = totals.iter().collect();
+    stages.sort_by(|a, b| b.1.elapsed.cmp(&a.1.elapsed).then_with(|| a.0.cmp(b.0)));
+    
+    let mut report = String::from("=== CPU Profile ===\n");
+    for (stage, total) in stages {
+        let ms = total.elapsed.as_secs_f64() * 1000.0;
+        report.push_str(&format!(
+            "{:<20} {:>10.1} ms  {:>6} calls  {:>8.3} ms/call\n",
+            stage,
+            ms,
+            total.calls,
+            ms / total.calls as f64
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn measured_calls_add_up_per_stage() {
+        // Other tests share the totals, so only these stages are checked
+        assert_eq!(measure("test: disabled", || 1), 1);
+        
+        enable();
+        for _ in 0..3 {
+            measure("test: sleep", || std::thread::sleep(Duration::from_millis(5)));
+        }
+        record("test: slowest", Duration::from_secs(60));
+        
+        let report = report();
+        let position = |stage: &str| report.lines().position(|line| line.starts_with(stage));
+        assert_eq!(position("test: disabled"), None);
+        assert!(position("test: slowest").unwrap() < position("test: sleep").unwrap(), "{}", report);
+        
+        let sleep = report.lines().find(|line| line.starts_with("test: sleep")).unwrap();
+        let columns: Vec<&str> = sleep["test: sleep".len()..].split_whitespace().collect();
+        let number = |column: usize| columns[column].parse::<f64>().unwrap();
+        assert_eq!(columns[2], "3", "{}", sleep);
+        assert!(number(0) >= 15.0 && number(4) >= 5.0, "{}", sleep);
+    }
+}
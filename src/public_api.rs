@@ -0,0 +1,170 @@
+use syn::visit::{self, Visit};
+
+use crate::code_analyzer::{Issue, IssueCategory, Severity};
+
+/// Macros that abort the caller when reached.
+const PANIC_MACROS: [&str; 4] = ["panic", "todo", "unimplemented", "unreachable"];
+
+/// The public API surface of a Rust file, for `--public-only` reviews.
+///
+/// An item counts as public when it is `pub` and every enclosing inline
+/// module is too; methods count when they are `pub` in an inherent impl
+/// or part of a trait impl. This is per file, so re-exports and
+/// `pub(crate)` modules elsewhere in the crate aren't taken into account.
+#[derive(Debug, Default)]
+pub struct PublicApi {
+    items: Vec<PublicItem>,
+    panics: Vec<PanicSite>,
+}
+
+#[derive(Debug)]
+struct PublicItem {
+    line: usize,
+    end_line: usize,
+    is_fn: bool,
+}
+
+#[derive(Debug)]
+struct PanicSite {
+    call: String,
+    line: usize,
+}
+
+impl PublicApi {
+    pub fn collect(ast: &syn::File) -> Self {
+        let mut collector = Collector::default();
+        collector.items(&ast.items, true);
+        collector.api
+    }
+    
+    /// Whether `line` falls inside a public item.
+    pub fn contains(&self, line: usize) -> bool {
+        self.items.iter().any(|item| (item.line..=item.end_line).contains(&line))
+    }
+    
+    /// Whether `line` falls inside the body of a public function.
+    pub fn in_public_fn(&self, line: usize) -> bool {
+        self.items.iter().any(|item| item.is_fn && (item.line..=item.end_line).contains(&line))
+    }
+    
+    /// Panicking calls in public functions. Missing docs on public items
+    /// are reported by the regular missing-docs check.
+    pub fn issues(&self, lines: &[&str]) -> Vec<Issue> {
+        self.panics
+            .iter()
+            .map(|site| Issue {
+                rule: "rust/public-panic".to_string(),
+                severity: Severity::High,
+                message: format!("`{}` can panic in a public function; return an error instead", site.call),
+                line: Some(site.line),
+                code: lines.get(site.line - 1).map(|line| line.to_string()),
+                category: IssueCategory::ErrorHandling,
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct Collector {
+    api: PublicApi,
+}
+
+impl Collector {
+    fn items(&mut self, items: &[syn::Item], reachable: bool) {
+        for item in items {
+            match item {
+                syn::Item::Fn(function) if reachable && is_pub(&function.vis) => {
+                    self.push_fn(&function.sig.ident, &function.block);
+                }
+                syn::Item::Struct(item) if reachable && is_pub(&item.vis) => {
+                    self.push(&item.ident, item.ident.span().start().line);
+                }
+                syn::Item::Enum(item) if reachable && is_pub(&item.vis) => {
+                    self.push(&item.ident, item.brace_token.span.close().end().line);
+                }
+                syn::Item::Trait(item) if reachable && is_pub(&item.vis) => {
+                    self.push(&item.ident, item.brace_token.span.close().end().line);
+                }
+                syn::Item::Type(item) if reachable && is_pub(&item.vis) => {
+                    self.push(&item.ident, item.ident.span().start().line);
+                }
+                syn::Item::Const(item) if reachable && is_pub(&item.vis) => {
+                    self.push(&item.ident, item.ident.span().start().line);
+                }
+                syn::Item::Impl(block) if reachable => {
+                    let trait_impl = block.trait_.is_some();
+                    for impl_item in &block.items {
+                        if let syn::ImplItem::Fn(method) = impl_item {
+                            if trait_impl || is_pub(&method.vis) {
+                                self.push_fn(&method.sig.ident, &method.block);
+                            }
+                        }
+                    }
+                }
+                syn::Item::Mod(module) => {
+                    if let Some((_, items)) = &module.content {
+                        self.items(items, reachable && is_pub(&module.vis));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    fn push(&mut self, ident: &syn::Ident, end_line: usize) {
+        self.api.items.push(PublicItem {
+            line: ident.span().start().line,
+            end_line,
+            is_fn: false,
+        });
+    }
+    
+    fn push_fn(&mut self, ident: &syn::Ident, block: &syn::Block) {
+        let mut finder = PanicFinder::default();
+        finder.visit_block(block);
+        self.api.panics.extend(finder.sites);
+        
+        self.api.items.push(PublicItem {
+            line: ident.span().start().line,
+            end_line: block.brace_token.span.close().end().line,
+            is_fn: true,
+        });
+    }
+}
+
+#[derive(Default)]
+struct PanicFinder {
+    sites: Vec<PanicSite>,
+}
+
+impl<'ast> Visit<'ast> for PanicFinder {
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Some(segment) = mac.path.segments.last() {
+            let name = segment.ident.to_string();
+            if PANIC_MACROS.contains(&name.as_str()) {
+                self.sites.push(PanicSite {
+                    call: format!("{}!", name),
+                    line: segment.ident.span().start().line,
+                });
+            }
+        }
+        visit::visit_macro(self, mac);
+    }
+    
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        if call.method == "expect" {
+            self.sites.push(PanicSite {
+                call: ".expect()".to_string(),
+                line: call.method.span().start().line,
+            });
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+    
+    // Items declared inside a body aren't part of the public API
+    fn visit_item(&mut self, _item: &'ast syn::Item) {}
+}
+
+fn is_pub(vis: &syn::Visibility) -> bool {
+    matches!(vis, syn::Visibility::Public(_))
+}
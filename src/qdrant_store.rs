@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use kalosm::language::*;
+use qdrant_client::qdrant::{
+    Condition, CreateCollectionBuilder, DeletePointsBuilder, Distance, Filter, PointStruct,
+    SearchPointsBuilder, UpsertPointsBuilder, VectorParamsBuilder,
+};
+use qdrant_client::{Payload, Qdrant};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::semantic_search::{Embedder, SemanticIndex, SemanticSearchConfig, VectorStore};
+
+/// Connects the embedding model and Qdrant named in `config`, or `None`
+/// when semantic search is disabled.
+pub async fn connect(config: &SemanticSearchConfig) -> Result<Option<SemanticIndex>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    
+    let embedder = BertEmbedder::load(config).await?;
+    // The collection's vector size has to match the model's
+    let dimensions = embedder.embed(&["".to_string()]).await?
+        .first()
+        .map(Vec::len)
+        .context("Embedding model returned no vector")?;
+    let store = QdrantStore::connect(config, dimensions as u64).await?;
+    
+    Ok(Some(SemanticIndex::new(Box::new(embedder), Box::new(store), config.chunk_lines)))
+}
+
+/// A local BERT model loaded from `model_path`.
+pub struct BertEmbedder {
+    bert: Bert,
+}
+
+impl BertEmbedder {
+    pub async fn load(config: &SemanticSearchConfig) -> Result<Self> {
+        info!("Loading embedding model from {}", config.model_path.display());
+        
+        let source = BertSource::default()
+            .with_model(FileSource::Local(config.model_path.join("model.safetensors")))
+            .with_tokenizer(FileSource::Local(config.model_path.join("tokenizer.json")))
+            .with_config(FileSource::Local(config.model_path.join("config.json")));
+        let bert = Bert::builder()
+            .with_source(source)
+            .build()
+            .await
+            .with_context(|| format!("Failed to load embedding model from {}", config.model_path.display()))?;
+        
+        Ok(Self { bert })
+    }
+}
+
+#[async_trait]
+impl Embedder for BertEmbedder {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let embeddings = self.bert.embed_batch(texts.to_vec()).await?;
+        Ok(embeddings.into_iter().map(|embedding| embedding.to_vec()).collect())
+    }
+}
+
+/// Chunk vectors in a Qdrant collection, one point per chunk with the
+/// file id in its payload.
+pub struct QdrantStore {
+    client: Qdrant,
+    collection: String,
+}
+
+impl QdrantStore {
+    /// Connects and creates the collection if it doesn't exist yet.
+    pub async fn connect(config: &SemanticSearchConfig, dimensions: u64) -> Result<Self> {
+        let client = Qdrant::from_url(&config.qdrant_url)
+            .build()
+            .with_context(|| format!("Failed to connect to Qdrant at {}", config.qdrant_url))?;
+        
+        if !client.collection_exists(&config.collection).await? {
+            info!("Creating Qdrant collection {}", config.collection);
+            client.create_collection(
+                CreateCollectionBuilder::new(&config.collection)
+                    .vectors_config(VectorParamsBuilder::new(dimensions, Distance::Cosine)),
+            )
+            .await
+            .with_context(|| format!("Failed to create Qdrant collection {}", config.collection))?;
+        }
+        
+        Ok(Self { client, collection: config.collection.clone() })
+    }
+    
+    fn file_filter(file_id: &str) -> Filter {
+        Filter::must([Condition::matches("file_id", file_id.to_string())])
+    }
+}
+
+#[async_trait]
+impl VectorStore for QdrantStore {
+    async fn replace(&self, file_id: &str, vectors: Vec<Vec<f32>>) -> Result<()> {
+        // A shorter file leaves fewer chunks, so clear the old ones first
+        self.client.delete_points(
+            DeletePointsBuilder::new(&self.collection)
+                .points(Self::file_filter(file_id))
+                .wait(true),
+        )
+        .await?;
+        
+        if vectors.is_empty() {
+            return Ok(());
+        }
+        
+        let points = vectors.into_iter()
+            .enumerate()
+            .map(|(chunk, vector)| {
+                // Stable ids, so re-indexing a file overwrites its points
+                let id = Uuid::new_v5(&Uuid::NAMESPACE_OID, format!("{}#{}", file_id, chunk).as_bytes());
+                let payload = Payload::try_from(serde_json::json!({ "file_id": file_id, "chunk": chunk }))?;
+                Ok(PointStruct::new(id.to_string(), vector, payload))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.client.upsert_points(UpsertPointsBuilder::new(&self.collection, points).wait(true)).await?;
+        
+        Ok(())
+    }
+    
+    async fn nearest(&self, vector: Vec<f32>, limit: usize) -> Result<Vec<(String, f32)>> {
+        let response = self.client.search_points(
+            SearchPointsBuilder::new(&self.collection, vector, limit as u64).with_payload(true),
+        )
+        .await?;
+        
+        Ok(response.result
+            .into_iter()
+            .filter_map(|point| {
+                let file_id = point.payload.get("file_id")?.clone().into_json();
+                Some((file_id.as_str()?.to_string(), point.score))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,164 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::report_meta;
+
+// Process-wide like profiling, so log sites anywhere can check it
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub const MASK: &str = "[REDACTED]";
+
+/// Characters of a redacted log value kept before it's cut short.
+const LOG_PREVIEW_CHARS: usize = 80;
+
+/// Lowercase key fragments whose assigned value is taken to be a secret.
+const SECRET_KEYS: [&str; 9] = [
+    "password", "passwd", "secret", "api_key", "apikey", "token", "private_key", "access_key", "credential",
+];
+
+/// Prefixes of well-known token formats, with the fewest characters that
+/// must follow for a match.
+const TOKEN_PREFIXES: [(&str, usize); 9] = [
+    ("AKIA", 16),
+    ("ghp_", 20),
+    ("gho_", 20),
+    ("ghs_", 20),
+    ("github_pat_", 20),
+    ("glpat-", 20),
+    ("sk-", 20),
+    ("xoxb-", 10),
+    ("xoxp-", 10),
+];
+
+/// Turns on `--redact`: secrets are masked and content is cut short in
+/// logs, the memory file and report code snippets.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// `text` with secrets masked when redaction is on, unchanged otherwise.
+/// For content that is kept, like the memory file and report snippets.
+pub fn stored(text: &str) -> Cow<'_, str> {
+    if is_enabled() {
+        Cow::Owned(mask_secrets(text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// `text` for a log line: unchanged without redaction, otherwise masked
+/// and cut to a short preview tagged with its length and hash, so entries
+/// can still be correlated.
+pub fn log(text: &str) -> Cow<'_, str> {
+    if !is_enabled() {
+        return Cow::Borrowed(text);
+    }
+    
+    let masked = mask_secrets(text);
+    if masked.chars().count() <= LOG_PREVIEW_CHARS {
+        return Cow::Owned(masked);
+    }
+    
+    let preview: String = masked.chars().take(LOG_PREVIEW_CHARS).collect();
+    Cow::Owned(format!("{}... [{} bytes, hash {}]", preview, text.len(), report_meta::stable_hash(text)))
+}
+
+/// Masks values assigned to secret-looking keys (`api_key = "..."`,
+/// `PASSWORD=...`), well-known token formats and PEM private key blocks.
+pub fn mask_secrets(text: &str) -> String {
+    let mut masked = Vec::new();
+    let mut in_private_key = false;
+    
+    for line in text.split('\n') {
+        if line.contains("-----BEGIN") && line.contains("PRIVATE KEY-----") {
+            in_private_key = true;
+            masked.push(MASK.to_string());
+            continue;
+        }
+        if in_private_key {
+            in_private_key = !line.contains("-----END");
+            continue;
+        }
+        
+        masked.push(mask_tokens(&mask_assignments(line)));
+    }
+    
+    masked.join("\n")
+}
+
+fn mask_assignments(line: &str) -> String {
+    let lower = line.to_ascii_lowercase();
+    let Some(key_end) = SECRET_KEYS.iter()
+        .filter_map(|key| lower.find(key).map(|start| start + key.len()))
+        .min()
+    else {
+        return line.to_string();
+    };
+    
+    // The rest of the key name, closing quotes and spaces, then = or :
+    let after_key = line[key_end..].trim_start_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '"' || c == '\'' || c == ' ');
+    let Some(mut after_operator) = after_key.strip_prefix(['=', ':']) else {
+        return line.to_string();
+    };
+    // A typed binding (`PASSWORD: &str = "..."`): the value follows the `=`
+    if after_key.starts_with(':') && !after_operator.trim_start().starts_with(['"', '\'', '`']) {
+        if let Some(equals) = after_operator.find('=') {
+            after_operator = &after_operator[equals + 1..];
+        }
+    }
+    // `==` is a comparison, not an assignment
+    if after_operator.starts_with('=') {
+        return line.to_string();
+    }
+    
+    let value_start = line.len() - after_operator.trim_start().len();
+    let value = &line[value_start..];
+    let (open, value_end) = match value.chars().next() {
+        Some(quote @ ('"' | '\'' | '`')) => match value[1..].find(quote) {
+            Some(close) => (1, 1 + close),
+            None => (1, value.len()),
+        },
+        _ => (0, value.find(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | ')' | '}')).unwrap_or(value.len())),
+    };
+    
+    if value_end <= open {
+        return line.to_string();
+    }
+    
+    format!("{}{}{}", &line[..value_start + open], MASK, &line[value_start + value_end..])
+}
+
+fn mask_tokens(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    
+    'scan: while !rest.is_empty() {
+        let at_word_start = !result.chars().next_back().is_some_and(is_token_char);
+        if at_word_start {
+            for (prefix, min_tail) in TOKEN_PREFIXES {
+                if let Some(tail) = rest.strip_prefix(prefix) {
+                    let tail_len = tail.find(|c: char| !is_token_char(c)).unwrap_or(tail.len());
+                    if tail_len >= min_tail {
+                        result.push_str(MASK);
+                        rest = &tail[tail_len..];
+                        continue 'scan;
+                    }
+                }
+            }
+        }
+        
+        let c = rest.chars().next().unwrap_or_default();
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    
+    result
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
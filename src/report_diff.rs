@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tokio::fs;
+
+use crate::code_analyzer::Issue;
+use crate::report_meta::{self, ReportMeta};
+
+// Scores within this of each other count as unchanged
+const SCORE_EPSILON: f32 = 0.005;
+
+/// The parts of a JSON report that `diff` compares.
+#[derive(Debug, Deserialize)]
+pub struct Snapshot {
+    format_version: u32,
+    meta: ReportMeta,
+    reviews: Vec<ReviewEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewEntry {
+    /// Derived from the normalized path, so it matches across machines
+    id: String,
+    file_path: String,
+    issues: Vec<Issue>,
+    score: f32,
+}
+
+impl Snapshot {
+    pub async fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        
+        let snapshot: Self = serde_json::from_str(&content)
+            .with_context(|| format!("{} is not a versioned DevAgent JSON report", path.display()))?;
+        report_meta::check_format_version(path, snapshot.format_version)?;
+        Ok(snapshot)
+    }
+    
+    fn average_score(&self) -> f32 {
+        if self.reviews.is_empty() {
+            return 0.0;
+        }
+        self.reviews.iter().map(|review| review.score).sum::<f32>() / self.reviews.len() as f32
+    }
+}
+
+/// Per-file changes between two reports of the same codebase.
+#[derive(Debug)]
+pub struct ReportDiff {
+    /// Files in both reports with a different score or issue set
+    pub files: Vec<FileDiff>,
+    pub new_files: Vec<String>,
+    pub removed_files: Vec<String>,
+    pub old_score: f32,
+    pub new_score: f32,
+    /// Set when the reports were made under different configs, so some
+    /// changes may come from the config rather than the code
+    pub config_changed: Option<(String, String)>,
+}
+
+#[derive(Debug)]
+pub struct FileDiff {
+    pub path: String,
+    pub old_score: f32,
+    pub new_score: f32,
+    pub added: Vec<Issue>,
+    pub removed: Vec<Issue>,
+    /// Same rule on the same line, with a new severity or message
+    pub changed: Vec<(Issue, Issue)>,
+}
+
+impl FileDiff {
+    /// Lower score, or more issues than before.
+    pub fn regressed(&self) -> bool {
+        self.new_score < self.old_score - SCORE_EPSILON || self.added.len() > self.removed.len()
+    }
+    
+    fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && (self.new_score - self.old_score).abs() <= SCORE_EPSILON
+    }
+}
+
+impl ReportDiff {
+    pub fn regressions(&self) -> Vec<&FileDiff> {
+        self.files.iter().filter(|file| file.regressed()).collect()
+    }
+}
+
+/// Matches files by review id and compares their issues. An issue that
+/// only moved to another line counts as unchanged.
+pub fn diff(old: Snapshot, new: Snapshot) -> ReportDiff {
+    let old_score = old.average_score();
+    let new_score = new.average_score();
+    let config_changed = (old.meta.config_hash != new.meta.config_hash)
+        .then(|| (old.meta.config_hash.clone(), new.meta.config_hash.clone()));
+    
+    let mut old_reviews: HashMap<String, ReviewEntry> = old.reviews.into_iter()
+        .map(|review| (review.id.clone(), review))
+        .collect();
+    
+    let mut files = Vec::new();
+    let mut new_files = Vec::new();
+    for review in new.reviews {
+        match old_reviews.remove(&review.id) {
+            Some(before) => {
+                let file = diff_file(before, review);
+                if !file.is_empty() {
+                    files.push(file);
+                }
+            }
+            None => new_files.push(review.file_path),
+        }
+    }
+    
+    let mut removed_files: Vec<String> = old_reviews.into_values().map(|review| review.file_path).collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    new_files.sort();
+    removed_files.sort();
+    
+    ReportDiff { files, new_files, removed_files, old_score, new_score, config_changed }
+}
+
+fn diff_file(before: ReviewEntry, after: ReviewEntry) -> FileDiff {
+    let mut removed = before.issues;
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    
+    // Identical issues first, so a same-line match isn't taken by a
+    // different issue of the same rule
+    for issue in after.issues {
+        let identical = take_matching(&mut removed, |old| {
+            old.rule == issue.rule && old.line == issue.line && old.message == issue.message && old.severity == issue.severity
+        });
+        if identical.is_none() {
+            added.push(issue);
+        }
+    }
+    
+    for issue in std::mem::take(&mut added) {
+        if let Some(old) = take_matching(&mut removed, |old| old.rule == issue.rule && old.line == issue.line) {
+            changed.push((old, issue));
+        } else if take_matching(&mut removed, |old| old.rule == issue.rule && old.message == issue.message).is_none() {
+            // Otherwise it only moved to another line
+            added.push(issue);
+        }
+    }
+    
+    FileDiff {
+        path: after.file_path,
+        old_score: before.score,
+        new_score: after.score,
+        added,
+        removed,
+        changed,
+    }
+}
+
+fn take_matching(issues: &mut Vec<Issue>, matches: impl Fn(&Issue) -> bool) -> Option<Issue> {
+    let index = issues.iter().position(matches)?;
+    Some(issues.remove(index))
+}
+
+impl fmt::Display for ReportDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((old, new)) = &self.config_changed {
+            writeln!(f, "Note: the reports were made with different configs ({} -> {})", old, new)?;
+        }
+        
+        for file in &self.files {
+            writeln!(f, "{}: score {:.2} -> {:.2} ({:+.2})", file.path, file.old_score, file.new_score, file.new_score - file.old_score)?;
+            for issue in &file.added {
+                writeln!(f, "  + {}", describe(issue))?;
+            }
+            for issue in &file.removed {
+                writeln!(f, "  - {}", describe(issue))?;
+            }
+            for (old, new) in &file.changed {
+                writeln!(f, "  ~ {} (was {:?}: {})", describe(new), old.severity, old.message)?;
+            }
+        }
+        
+        for path in &self.new_files {
+            writeln!(f, "New file: {}", path)?;
+        }
+        for path in &self.removed_files {
+            writeln!(f, "Removed file: {}", path)?;
+        }
+        
+        let count = |issues: fn(&FileDiff) -> usize| self.files.iter().map(issues).sum::<usize>();
+        write!(
+            f,
+            "Overall: average score {:.2} -> {:.2} ({:+.2}); {} added, {} removed, {} changed issues; {} of {} changed files regressed",
+            self.old_score,
+            self.new_score,
+            self.new_score - self.old_score,
+            count(|file| file.added.len()),
+            count(|file| file.removed.len()),
+            count(|file| file.changed.len()),
+            self.regressions().len(),
+            self.files.len(),
+        )
+    }
+}
+
+fn describe(issue: &Issue) -> String {
+    let line = issue.line.map(|line| format!(" line {}", line)).unwrap_or_default();
+    format!("[{:?}] {}{}: {}", issue.severity, issue.rule, line, issue.message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_analyzer::Severity;
+    
+    #[tokio::test]
+    async fn report_diff_matches_files_and_issues_across_runs() {
+        let issue = |rule: &str, line: usize, severity: &str| serde_json::json!({
+            "rule": rule, "severity": severity, "message": format!("{} found", rule),
+            "line": line, "code": null, "category": "Style",
+        });
+        let review = |path: &str, score: f32, issues: Vec<serde_json::Value>| serde_json::json!({
+            "id": format!("id-{}", path), "file_path": path, "issues": issues, "score": score,
+        });
+        let report = |hash: &str, reviews: Vec<serde_json::Value>| serde_json::json!({
+            "format_version": 1,
+            "meta": { "tool_version": "0.1.0", "profile": "default", "config_hash": hash, "rules": [] },
+            "reviews": reviews,
+            "skipped": [],
+        });
+        
+        let old = report("aaaa", vec![
+            review("src/a.rs", 0.9, vec![issue("rust/unwrap", 3, "High"), issue("all/todo", 5, "Low")]),
+            review("src/b.rs", 0.8, vec![issue("all/todo", 1, "Low")]),
+            review("src/gone.rs", 0.5, vec![]),
+        ]);
+        let new = report("aaaa", vec![
+            // The unwrap moved down a line, the TODO got stricter, a long line is new
+            review("src/a.rs", 0.7, vec![issue("rust/unwrap", 4, "High"), issue("all/todo", 5, "Medium"), issue("all/long-line", 9, "Low")]),
+            review("src/b.rs", 0.8, vec![issue("all/todo", 1, "Low")]),
+            review("src/new.rs", 1.0, vec![]),
+        ]);
+        
+        let dir = tempfile::tempdir().unwrap();
+        let (old_path, new_path) = (dir.path().join("old.json"), dir.path().join("new.json"));
+        std::fs::write(&old_path, old.to_string()).unwrap();
+        std::fs::write(&new_path, new.to_string()).unwrap();
+        
+        let diff = diff(Snapshot::load(&old_path).await.unwrap(), Snapshot::load(&new_path).await.unwrap());
+        assert_eq!(diff.files.len(), 1);
+        let file = &diff.files[0];
+        assert_eq!(file.path, "src/a.rs");
+        assert_eq!(file.added.iter().map(|i| i.rule.as_str()).collect::<Vec<_>>(), vec!["all/long-line"]);
+        assert!(file.removed.is_empty());
+        assert_eq!(file.changed.len(), 1);
+        assert_eq!((&file.changed[0].0.severity, &file.changed[0].1.severity), (&Severity::Low, &Severity::Medium));
+        assert_eq!(diff.regressions().len(), 1);
+        assert_eq!((diff.new_files.as_slice(), diff.removed_files.as_slice()), (&["src/new.rs".to_string()][..], &["src/gone.rs".to_string()][..]));
+        assert!(diff.config_changed.is_none());
+        assert!(diff.to_string().ends_with("1 added, 0 removed, 1 changed issues; 1 of 1 changed files regressed"));
+    
+    }
+}
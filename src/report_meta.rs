@@ -0,0 +1,122 @@
+use std::fmt;
+use std::path::Path;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::config::ReviewConfig;
+
+/// Version of the JSON report layout. Bump when fields are renamed or
+/// removed; readers should refuse versions they don't know.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Profile name used when `.devagent.toml` doesn't set one.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Identifies the rule set a report was produced under. Scores are only
+/// comparable between reports with the same config hash and rules.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportMeta {
+    pub tool_version: String,
+    pub profile: String,
+    /// Hash of the effective review config, env overrides included
+    pub config_hash: String,
+    /// Rule ids the analyzer could report, sorted
+    pub rules: Vec<String>,
+}
+
+impl ReportMeta {
+    pub fn new(config: &ReviewConfig, rules: Vec<String>) -> Self {
+        Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            profile: config.profile.clone().unwrap_or_else(|| DEFAULT_PROFILE.to_string()),
+            config_hash: config_hash(config),
+            rules,
+        }
+    }
+}
+
+impl fmt::Display for ReportMeta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Tool version: {}", self.tool_version)?;
+        writeln!(f, "Profile: {}", self.profile)?;
+        writeln!(f, "Config hash: {}", self.config_hash)?;
+        write!(f, "Rules ({}): {}", self.rules.len(), self.rules.join(", "))
+    }
+}
+
+/// Stable hash of the config: FNV-1a over its JSON form. `serde_json`
+/// objects keep keys sorted, so map ordering doesn't change the hash.
+pub fn config_hash(config: &ReviewConfig) -> String {
+    let canonical = serde_json::to_value(config)
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+    
+    stable_hash(&canonical)
+}
+
+/// FNV-1a of `text` as 16 hex digits; the same across runs and platforms,
+/// unlike `std`'s hashers.
+pub fn stable_hash(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    
+    format!("{:016x}", hash)
+}
+
+// Only the header of a report; the reviews are skipped over
+#[derive(Deserialize)]
+struct ReportHeader {
+    format_version: u32,
+    meta: ReportMeta,
+}
+
+/// Reads the header of a JSON report written by `JsonReporter`.
+pub async fn read(path: &Path) -> Result<(u32, ReportMeta)> {
+    let content = fs::read_to_string(path).await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    
+    let header: ReportHeader = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not a versioned DevAgent JSON report", path.display()))?;
+    
+    check_format_version(path, header.format_version)?;
+    Ok((header.format_version, header.meta))
+}
+
+pub fn check_format_version(path: &Path, format_version: u32) -> Result<()> {
+    if format_version > FORMAT_VERSION {
+        anyhow::bail!(
+            "{} uses report format {}, newer than the supported {}",
+            path.display(), format_version, FORMAT_VERSION
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_analyzer::CodeAnalyzer;
+    
+    #[tokio::test]
+    async fn report_meta_records_profile_config_hash_and_rules() {
+        let mut analyzer = CodeAnalyzer::new().await.unwrap();
+        let config = ReviewConfig::check("profile = \"ci\"\n").unwrap();
+        let meta = ReportMeta::new(&config, analyzer.enabled_rules());
+        
+        assert_eq!(meta.profile, "ci");
+        assert_eq!(meta.config_hash, config_hash(&ReviewConfig::check("profile = \"ci\"\n").unwrap()));
+        assert!(meta.rules.contains(&"rust/unwrap".to_string()));
+        assert!(!meta.rules.contains(&"all/max-file-lines".to_string()));
+        assert_eq!(ReportMeta::new(&ReviewConfig::default(), Vec::new()).profile, "default");
+        
+        let stricter = ReviewConfig::check("profile = \"ci\"\n[size_limits]\nmax_file_lines = 300\n").unwrap();
+        assert_ne!(config_hash(&stricter), meta.config_hash);
+        
+        analyzer.set_size_limits(stricter.size_limits.clone());
+        assert!(analyzer.enabled_rules().contains(&"all/max-file-lines".to_string()));
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tokio::fs;
+use tracing::info;
+
+use crate::packages;
+use crate::report_meta;
+use crate::skipped;
+use crate::{CodeReview, ReviewReport, Severity};
+
+/// Output sink for a finished review. The CLI picks one of the built-ins by
+/// `--format`; embedders can register their own (webhooks, databases, ...).
+/// Reporters get the skipped files too, so gaps in coverage aren't silent.
+#[async_trait]
+pub trait Reporter: Send + Sync {
+    async fn report(&self, report: &ReviewReport) -> Result<()>;
+}
+
+pub struct ReporterRegistry {
+    reporters: HashMap<String, Box<dyn Reporter>>,
+}
+
+impl ReporterRegistry {
+    pub fn new() -> Self {
+        Self {
+            reporters: HashMap::new(),
+        }
+    }
+    
+    /// Registry with the built-in `json`, `sarif`, `html` and `codeclimate`
+    /// writers. `output` overrides the default file name for whichever
+    /// format is selected.
+    pub fn with_builtins(output: Option<PathBuf>) -> Self {
+        let path_for = |default: &str| output.clone().unwrap_or_else(|| PathBuf::from(default));
+        
+        let mut registry = Self::new();
+        registry.register("json", JsonReporter { output: path_for("code_review_results.json") });
+        registry.register("sarif", SarifReporter { output: path_for("code_review_results.sarif") });
+        registry.register("html", HtmlReporter { output: path_for("code_review_results.html") });
+        registry.register("codeclimate", CodeClimateReporter { output: path_for("gl-code-quality-report.json") });
+        registry
+    }
+    
+    pub fn register(&mut self, name: &str, reporter: impl Reporter + 'static) {
+        self.reporters.insert(name.to_string(), Box::new(reporter));
+    }
+    
+    pub fn get(&self, name: &str) -> Result<&dyn Reporter> {
+        self.reporters.get(name)
+            .map(|reporter| reporter.as_ref())
+            .ok_or_else(|| {
+                let mut known: Vec<&str> = self.reporters.keys().map(|k| k.as_str()).collect();
+                known.sort();
+                anyhow::anyhow!("Unknown output format '{}' (available: {})", name, known.join(", "))
+            })
+    }
+}
+
+pub struct JsonReporter {
+    pub output: PathBuf,
+}
+
+#[async_trait]
+impl Reporter for JsonReporter {
+    async fn report(&self, report: &ReviewReport) -> Result<()> {
+        let json = serde_json::to_string_pretty(report)
+            .context("Failed to serialize reviews")?;
+        
+        write_report(&self.output, json).await
+    }
+}
+
+pub struct SarifReporter {
+    pub output: PathBuf,
+}
+
+#[async_trait]
+impl Reporter for SarifReporter {
+    async fn report(&self, report: &ReviewReport) -> Result<()> {
+        let mut results = Vec::new();
+        
+        for review in &report.reviews {
+            for issue in &review.issues {
+                let mut location = serde_json::json!({
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": review.file_path.replace('\\', "/") }
+                    }
+                });
+                if let Some(line) = issue.line {
+                    location["physicalLocation"]["region"] = serde_json::json!({ "startLine": line });
+                }
+                
+                let mut result = serde_json::json!({
+                    "level": sarif_level(&issue.severity),
+                    "message": { "text": issue.message },
+                    "locations": [location],
+                });
+                if !issue.rule.is_empty() {
+                    result["ruleId"] = serde_json::json!(issue.rule);
+                }
+                
+                results.push(result);
+            }
+        }
+        
+        let sarif = serde_json::json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "DevAgent",
+                        "version": report.meta.tool_version,
+                    }
+                },
+                "properties": {
+                    "profile": report.meta.profile,
+                    "configHash": report.meta.config_hash,
+                    "rules": report.meta.rules,
+                },
+                "invocations": [{
+                    "executionSuccessful": true,
+                    "toolExecutionNotifications": skipped_notifications(report),
+                }],
+                "results": results,
+            }]
+        });
+        
+        let json = serde_json::to_string_pretty(&sarif)
+            .context("Failed to serialize SARIF report")?;
+        
+        write_report(&self.output, json).await
+    }
+}
+
+// Skipped files are run-level notifications rather than results, since
+// there's no finding to attach them to
+fn skipped_notifications(report: &ReviewReport) -> Vec<serde_json::Value> {
+    report.skipped.iter()
+        .map(|file| serde_json::json!({
+            "level": "warning",
+            "message": { "text": format!("File skipped ({}): {}", file.reason, file.error) },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": file.path.replace('\\', "/") }
+                }
+            }],
+        }))
+        .collect()
+}
+
+fn sarif_level(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "note",
+    }
+}
+
+/// Code Climate issues, the format of GitLab's code quality widget. Skipped
+/// files have no place in the format and are left out.
+pub struct CodeClimateReporter {
+    pub output: PathBuf,
+}
+
+#[async_trait]
+impl Reporter for CodeClimateReporter {
+    async fn report(&self, report: &ReviewReport) -> Result<()> {
+        let json = serde_json::to_string_pretty(&codeclimate_issues(&report.reviews))
+            .context("Failed to serialize Code Climate report")?;
+        
+        write_report(&self.output, json).await
+    }
+}
+
+fn codeclimate_issues(reviews: &[CodeReview]) -> Vec<serde_json::Value> {
+    let mut issues = Vec::new();
+    // Identical issues on one line are numbered so their fingerprints
+    // don't collide
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    
+    for review in reviews {
+        let path = review.file_path.replace('\\', "/");
+        for issue in &review.issues {
+            // Line 1 stands in for whole-file issues, which the format
+            // can't express
+            let line = issue.line.unwrap_or(1);
+            let key = format!("{}:{}:{}:{}", path, line, issue.rule, issue.message);
+            let occurrence = occurrences.entry(key.clone()).or_default();
+            *occurrence += 1;
+            
+            issues.push(serde_json::json!({
+                "type": "issue",
+                "check_name": issue.rule,
+                "description": issue.message,
+                "fingerprint": report_meta::stable_hash(&format!("{}:{}", key, occurrence)),
+                "severity": codeclimate_severity(&issue.severity),
+                "location": {
+                    "path": path,
+                    "lines": { "begin": line },
+                },
+            }));
+        }
+    }
+    
+    issues
+}
+
+// Code Climate's `info` is left for non-findings
+fn codeclimate_severity(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Low => "minor",
+        Severity::Medium => "major",
+        Severity::High => "critical",
+        Severity::Critical => "blocker",
+    }
+}
+
+pub struct HtmlReporter {
+    pub output: PathBuf,
+}
+
+#[async_trait]
+impl Reporter for HtmlReporter {
+    async fn report(&self, report: &ReviewReport) -> Result<()> {
+        let reviews = &report.reviews;
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>DevAgent Review</title></head>\n<body>\n<h1>DevAgent Review</h1>\n",
+        );
+        html.push_str(&format!(
+            "<p>Profile: {} &middot; config {} &middot; {} rules</p>\n",
+            escape_html(&report.meta.profile),
+            report.meta.config_hash,
+            report.meta.rules.len()
+        ));
+        
+        let summaries = packages::summarize(reviews);
+        
+        if summaries.len() > 1 {
+            html.push_str("<table>\n<tr><th>Package</th><th>Files</th><th>Avg score</th><th>Issues</th></tr>\n");
+            for summary in &summaries {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{:.2}</td><td>{}</td></tr>\n",
+                    escape_html(&summary.package),
+                    summary.files,
+                    summary.average_score,
+                    summary.total_issues()
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+        
+        let mut current_package = None;
+        
+        for review in reviews {
+            if summaries.len() > 1 && current_package != Some(&review.package) {
+                html.push_str(&format!("<h2>{}</h2>\n", escape_html(&review.package)));
+                current_package = Some(&review.package);
+            }
+            
+            html.push_str(&format!(
+                "<h3>{}</h3>\n<p>Score: {:.2}</p>\n",
+                escape_html(&review.file_path),
+                review.score
+            ));
+            
+            if review.issues.is_empty() {
+                html.push_str("<p>No issues found.</p>\n");
+                continue;
+            }
+            
+            html.push_str("<table>\n<tr><th>Line</th><th>Severity</th><th>Message</th></tr>\n");
+            for issue in &review.issues {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{:?}</td><td>{}</td></tr>\n",
+                    issue.line.map(|line| line.to_string()).unwrap_or_default(),
+                    issue.severity,
+                    escape_html(&issue.message)
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+        
+        if let Some(summary) = skipped::summary(&report.skipped) {
+            html.push_str(&format!("<h2>Skipped files</h2>\n<p>{}</p>\n", escape_html(&summary)));
+            html.push_str("<table>\n<tr><th>File</th><th>Reason</th><th>Error</th></tr>\n");
+            for file in &report.skipped {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    escape_html(&file.path),
+                    file.reason,
+                    escape_html(&file.error)
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+        
+        html.push_str("</body>\n</html>\n");
+        
+        write_report(&self.output, html).await
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+async fn write_report(output: &PathBuf, contents: String) -> Result<()> {
+    fs::write(output, contents).await
+        .context("Failed to write review results")?;
+    
+    info!("Review results saved to: {}", output.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ReviewConfig;
+    use crate::report_meta::ReportMeta;
+    use crate::tests::review;
+    
+    #[test]
+    fn unknown_formats_list_the_registered_ones_sorted() {
+        let registry = ReporterRegistry::with_builtins(None);
+        assert!(registry.get("sarif").is_ok());
+        let error = registry.get("xml").err().unwrap().to_string();
+        assert_eq!(error, "Unknown output format 'xml' (available: codeclimate, html, json, sarif)");
+    }
+    
+    #[tokio::test]
+    async fn sarif_leaves_out_empty_rule_ids_and_missing_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("results.sarif");
+        let mut report = ReviewReport::new(ReportMeta::new(&ReviewConfig::default(), Vec::new()));
+        let mut reviewed = review("src\\lib.rs", 0.5, &[Severity::High, Severity::Low]);
+        reviewed.issues[1].rule = String::new();
+        reviewed.issues[1].line = None;
+        report.reviews.push(reviewed);
+        
+        SarifReporter { output: output.clone() }.report(&report).await.unwrap();
+        let sarif: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        let results = &sarif["runs"][0]["results"];
+        let location = |i: usize| &results[i]["locations"][0]["physicalLocation"];
+        
+        assert_eq!(results[0]["ruleId"], "rust/unsafe");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(location(0)["artifactLocation"]["uri"], "src/lib.rs");
+        assert_eq!(location(0)["region"]["startLine"], 1);
+        assert!(results[1].get("ruleId").is_none());
+        assert!(location(1).get("region").is_none());
+        assert_eq!(results[1]["level"], "note");
+    }
+    
+    #[test]
+    fn html_special_characters_are_escaped() {
+        assert_eq!(escape_html("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+        assert_eq!(escape_html("plain"), "plain");
+    }
+    
+    #[test]
+    fn codeclimate_issues_have_mapped_severities_and_distinct_stable_fingerprints() {
+        let mut reviews = vec![
+            review("src\\lib.rs", 0.5, &[Severity::Low, Severity::Medium, Severity::High, Severity::Critical]),
+            review("src/main.rs", 0.9, &[Severity::High, Severity::High]),
+        ];
+        let issues = codeclimate_issues(&reviews);
+        let severities: Vec<&str> = issues.iter().map(|issue| issue["severity"].as_str().unwrap()).collect();
+        assert_eq!(severities, ["minor", "major", "critical", "blocker", "critical", "critical"]);
+        assert_eq!(issues[0]["location"]["path"], "src/lib.rs");
+        
+        // Same rule, line and message each time, yet no two collide
+        let fingerprints: Vec<&str> = issues.iter().map(|issue| issue["fingerprint"].as_str().unwrap()).collect();
+        let unique: std::collections::HashSet<&str> = fingerprints.iter().copied().collect();
+        assert_eq!(unique.len(), fingerprints.len());
+        
+        // Unchanged findings keep their fingerprints from run to run
+        reviews[1].issues[1].message = "Unsafe block without a SAFETY comment".to_string();
+        let rerun = codeclimate_issues(&reviews);
+        let rerun: Vec<&str> = rerun.iter().map(|issue| issue["fingerprint"].as_str().unwrap()).collect();
+        assert_eq!(rerun[..5], fingerprints[..5]);
+        assert_ne!(rerun[5], fingerprints[5]);
+    }
+}
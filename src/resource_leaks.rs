@@ -0,0 +1,94 @@
+use crate::code_analyzer::{Issue, IssueCategory, Severity};
+
+// Any of these in a JS file counts as closing what it opened
+const JS_CLOSE_MARKERS: [&str; 6] = ["close(", "closeSync(", ".destroy(", ".end(", "pipeline(", "finally"];
+const JS_OPEN_CALLS: [&str; 4] = ["fs.open(", "fs.openSync(", "createReadStream(", "createWriteStream("];
+
+/// Heuristic checks for resources opened without an obvious close:
+/// Python `open()` outside `with` and never closed, JS file handles and
+/// streams in files with no close/`finally`, and Rust child processes in
+/// files that never wait on one (`kill()` alone leaves a zombie).
+pub fn check(language: &str, content: &str) -> Vec<Issue> {
+    match language {
+        "python" => unclosed_python_files(content),
+        "javascript" => unclosed_javascript_resources(content),
+        "rust" => unwaited_rust_children(content),
+        _ => Vec::new(),
+    }
+}
+
+fn unclosed_python_files(content: &str) -> Vec<Issue> {
+    code_lines(content, "#")
+        .filter_map(|(line_num, line)| {
+            let (target, value) = line.trim().split_once('=')?;
+            let target = target.trim();
+            let is_target = !target.is_empty() && target.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+            
+            if !is_target || !value.trim_start().starts_with("open(") || content.contains(&format!("{}.close()", target)) {
+                return None;
+            }
+            
+            Some(leak(
+                "python/unclosed-file",
+                format!("File opened into `{}` outside a `with` block is never closed", target),
+                line_num,
+                line,
+            ))
+        })
+        .collect()
+}
+
+fn unclosed_javascript_resources(content: &str) -> Vec<Issue> {
+    if JS_CLOSE_MARKERS.iter().any(|marker| content.contains(marker)) {
+        return Vec::new();
+    }
+    
+    code_lines(content, "//")
+        .filter_map(|(line_num, line)| {
+            let call = JS_OPEN_CALLS.iter().find(|call| line.contains(*call))?;
+            Some(leak(
+                "javascript/unclosed-resource",
+                format!("{}) is never closed; close it or clean up in a `finally`", call),
+                line_num,
+                line,
+            ))
+        })
+        .collect()
+}
+
+fn unwaited_rust_children(content: &str) -> Vec<Issue> {
+    let waits = [".wait()", ".wait_with_output()", ".try_wait()", "kill_on_drop(true)"];
+    if waits.iter().any(|wait| content.contains(wait)) {
+        return Vec::new();
+    }
+    
+    // `Command::spawn` takes no arguments, unlike `tokio::spawn(..)` and
+    // `thread::spawn(..)`
+    code_lines(content, "//")
+        .filter(|(_, line)| line.contains(".spawn()"))
+        .map(|(line_num, line)| leak(
+            "rust/unwaited-child",
+            "Child process is never waited on; call wait() after kill() so it doesn't linger as a zombie".to_string(),
+            line_num,
+            line,
+        ))
+        .collect()
+}
+
+fn code_lines<'a>(content: &'a str, comment: &'a str) -> impl Iterator<Item = (usize, &'a str)> {
+    content.lines()
+        .enumerate()
+        .filter(move |(_, line)| !line.trim_start().starts_with(comment))
+        .map(|(i, line)| (i + 1, line))
+}
+
+fn leak(rule: &str, message: String, line_num: usize, line: &str) -> Issue {
+    Issue {
+        rule: rule.to_string(),
+        severity: Severity::Medium,
+        message,
+        line: Some(line_num),
+        code: Some(line.to_string()),
+        category: IssueCategory::Performance,
+    }
+}
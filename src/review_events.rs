@@ -0,0 +1,93 @@
+use std::fmt;
+use std::sync::Mutex;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Per-file progress of a review run, so front ends can show results as
+/// they arrive instead of waiting for the whole codebase.
+#[derive(Debug, Clone)]
+pub enum ReviewEvent {
+    FileReviewed {
+        file_path: String,
+        score: f32,
+        issues: usize,
+    },
+    FileFailed {
+        file_path: String,
+        error: String,
+    },
+}
+
+impl fmt::Display for ReviewEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewEvent::FileReviewed { file_path, score, issues } => {
+                write!(f, "  reviewed {}  score {:.2}, {} issue(s)", file_path, score, issues)
+            }
+            ReviewEvent::FileFailed { file_path, error } => {
+                write!(f, "  failed   {}: {}", file_path, error)
+            }
+        }
+    }
+}
+
+/// Single-subscriber event channel. Emitting with no subscriber is a no-op.
+#[derive(Default)]
+pub struct ReviewEvents {
+    sender: Mutex<Option<UnboundedSender<ReviewEvent>>>,
+}
+
+impl ReviewEvents {
+    /// Starts a new stream, replacing any previous subscriber. The stream
+    /// ends once `close` is called.
+    pub fn subscribe(&self) -> UnboundedReceiver<ReviewEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        *self.sender.lock().unwrap_or_else(|e| e.into_inner()) = Some(sender);
+        receiver
+    }
+    
+    pub fn close(&self) {
+        self.sender.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
+    
+    pub fn emit(&self, event: ReviewEvent) {
+        if let Some(sender) = self.sender.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            // A dropped receiver just means nobody is listening any more
+            let _ = sender.send(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn reviewed(file_path: &str) -> ReviewEvent {
+        ReviewEvent::FileReviewed { file_path: file_path.to_string(), score: 1.0, issues: 0 }
+    }
+    
+    fn path(event: Option<ReviewEvent>) -> Option<String> {
+        match event? {
+            ReviewEvent::FileReviewed { file_path, .. } | ReviewEvent::FileFailed { file_path, .. } => Some(file_path),
+        }
+    }
+    
+    #[tokio::test]
+    async fn events_reach_only_the_latest_subscriber_until_closed() {
+        let events = ReviewEvents::default();
+        events.emit(reviewed("before.rs"));
+        
+        let mut first = events.subscribe();
+        events.emit(reviewed("a.rs"));
+        assert_eq!(path(first.recv().await).as_deref(), Some("a.rs"));
+        
+        // Subscribing again ends the first stream
+        let mut second = events.subscribe();
+        events.emit(ReviewEvent::FileFailed { file_path: "b.rs".to_string(), error: "timed out".to_string() });
+        assert_eq!(path(first.recv().await), None);
+        assert_eq!(path(second.recv().await).as_deref(), Some("b.rs"));
+        
+        events.close();
+        events.emit(reviewed("after.rs"));
+        assert_eq!(path(second.recv().await), None);
+    }
+}
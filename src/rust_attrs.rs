@@ -0,0 +1,33 @@
+/// `#[test]`, `#[tokio::test]` and the like, or `#[cfg(test)]`. The Rust
+/// checks use this to leave test code out of what they report.
+pub fn is_test(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(is_test_attr)
+}
+
+fn is_test_attr(attr: &syn::Attribute) -> bool {
+    let path = attr.path();
+    if path.segments.last().is_some_and(|segment| segment.ident == "test") {
+        return true;
+    }
+    
+    match &attr.meta {
+        syn::Meta::List(list) if path.is_ident("cfg") => list.tokens.to_string() == "test",
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_attributes_and_cfg_test_are_recognized() {
+        let attrs = |item: &str| syn::parse_str::<syn::ItemFn>(item).unwrap().attrs;
+        assert!(is_test(&attrs("#[test] fn a() {}")));
+        assert!(is_test(&attrs("#[tokio::test] async fn a() {}")));
+        assert!(is_test(&attrs("#[inline] #[cfg(test)] fn a() {}")));
+        assert!(!is_test(&attrs("#[cfg(not(test))] fn a() {}")));
+        assert!(!is_test(&attrs("#[testing] fn a() {}")));
+        assert!(!is_test(&attrs("fn a() {}")));
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use syn::visit::{self, Visit};
+
+use crate::profiling;
+
+/// Cyclomatic complexity of one function or method.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionComplexity {
+    /// `name`, or `Type::name` for methods
+    pub name: String,
+    pub line: usize,
+    /// 1 plus each `if`, loop condition, extra `match` arm and `&&`/`||`
+    pub complexity: usize,
+}
+
+/// Function counts and complexity from a parsed Rust file, as opposed to
+/// the substring counts used for other languages.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RustAstMetrics {
+    /// Free functions, impl methods and trait methods, in source order
+    pub functions: Vec<FunctionComplexity>,
+}
+
+impl RustAstMetrics {
+    /// File-wide complexity: 1 plus every function's branches, so it stays
+    /// comparable with the substring estimate.
+    pub fn cyclomatic_complexity(&self) -> f32 {
+        1.0 + self.functions.iter().map(|function| function.complexity - 1).sum::<usize>() as f32
+    }
+    
+    /// The most complex function, the first one on ties.
+    pub fn hot_spot(&self) -> Option<&FunctionComplexity> {
+        self.functions.iter().rev().max_by_key(|function| function.complexity)
+    }
+}
+
+/// Parses `content` and measures every function in it. Fails on code `syn`
+/// can't parse, e.g. partial files.
+pub fn analyze(content: &str) -> Result<RustAstMetrics> {
+    let ast = profiling::measure("syn parsing", || syn::parse_file(content))?;
+    
+    let mut collector = Collector::default();
+    collector.visit_file(&ast);
+    Ok(RustAstMetrics { functions: collector.functions })
+}
+
+#[derive(Default)]
+struct Collector {
+    functions: Vec<FunctionComplexity>,
+    /// Self type or trait name while inside an impl or trait block
+    owner: Option<String>,
+}
+
+impl Collector {
+    fn push(&mut self, sig: &syn::Signature, block: Option<&syn::Block>) {
+        let mut branches = Branches::default();
+        if let Some(block) = block {
+            branches.visit_block(block);
+        }
+        
+        let name = match &self.owner {
+            Some(owner) => format!("{}::{}", owner, sig.ident),
+            None => sig.ident.to_string(),
+        };
+        self.functions.push(FunctionComplexity {
+            name,
+            line: sig.ident.span().start().line,
+            complexity: 1 + branches.count,
+        });
+    }
+    
+    fn with_owner(&mut self, owner: String, visit: impl FnOnce(&mut Self)) {
+        let outer = self.owner.replace(owner);
+        visit(self);
+        self.owner = outer;
+    }
+}
+
+impl<'ast> Visit<'ast> for Collector {
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        // A free function, even when nested in a method body
+        let outer = self.owner.take();
+        self.push(&node.sig, Some(&node.block));
+        visit::visit_item_fn(self, node);
+        self.owner = outer;
+    }
+    
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let owner = match &*node.self_ty {
+            syn::Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+            _ => None,
+        };
+        self.with_owner(owner.unwrap_or_else(|| "_".to_string()), |collector| visit::visit_item_impl(collector, node));
+    }
+    
+    fn visit_item_trait(&mut self, node: &'ast syn::ItemTrait) {
+        self.with_owner(node.ident.to_string(), |collector| visit::visit_item_trait(collector, node));
+    }
+    
+    fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        self.push(&node.sig, Some(&node.block));
+        visit::visit_impl_item_fn(self, node);
+    }
+    
+    fn visit_trait_item_fn(&mut self, node: &'ast syn::TraitItemFn) {
+        self.push(&node.sig, node.default.as_ref());
+        visit::visit_trait_item_fn(self, node);
+    }
+}
+
+/// Decision points in one function body, closures included.
+#[derive(Default)]
+struct Branches {
+    count: usize,
+}
+
+impl<'ast> Visit<'ast> for Branches {
+    fn visit_expr_if(&mut self, node: &'ast syn::ExprIf) {
+        self.count += 1;
+        visit::visit_expr_if(self, node);
+    }
+    
+    fn visit_expr_while(&mut self, node: &'ast syn::ExprWhile) {
+        self.count += 1;
+        visit::visit_expr_while(self, node);
+    }
+    
+    fn visit_expr_for_loop(&mut self, node: &'ast syn::ExprForLoop) {
+        self.count += 1;
+        visit::visit_expr_for_loop(self, node);
+    }
+    
+    fn visit_expr_match(&mut self, node: &'ast syn::ExprMatch) {
+        self.count += node.arms.len().saturating_sub(1);
+        visit::visit_expr_match(self, node);
+    }
+    
+    fn visit_expr_binary(&mut self, node: &'ast syn::ExprBinary) {
+        if matches!(node.op, syn::BinOp::And(_) | syn::BinOp::Or(_)) {
+            self.count += 1;
+        }
+        visit::visit_expr_binary(self, node);
+    }
+    
+    // Nested items are separate functions, counted by the collector
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
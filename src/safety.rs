@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+/// Characters that chain, substitute or redirect in a shell, letting an
+/// allowed prefix smuggle in another command.
+const SHELL_METACHARACTERS: &[char] = &[';', '&', '|', '`', '$', '>', '<', '\n', '\r'];
+
+/// Which LLM-chosen shell commands `LocalBrain` may run on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SafetyPolicy {
+    /// Commands starting with one of these (whole words) run without asking
+    pub allowed_prefixes: Vec<String>,
+    /// Ask before running anything else; off runs every command
+    pub require_confirmation: bool,
+    /// Log what would run instead of running it
+    pub dry_run: bool,
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_prefixes: ["cargo build", "cargo check", "cargo test", "cargo fmt", "git status", "git diff", "git log"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            require_confirmation: true,
+            dry_run: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Run,
+    /// Not allowed as is; the reason is read back to the user
+    Confirm(String),
+}
+
+impl SafetyPolicy {
+    pub fn review(&self, command: &str) -> Verdict {
+        let command = command.trim();
+        if command.is_empty() {
+            return Verdict::Confirm("The command is empty".to_string());
+        }
+        if self.is_allowed(command) || !self.require_confirmation {
+            return Verdict::Run;
+        }
+        Verdict::Confirm(format!("`{}` is not on the allow-list. Say \"confirm\" to run it.", command))
+    }
+    
+    /// Whether `text` confirms the command last held back by `review`.
+    pub fn is_confirmation(text: &str) -> bool {
+        let text = text.trim().trim_end_matches(['.', '!']).to_lowercase();
+        matches!(text.as_str(), "confirm" | "yes")
+    }
+    
+    fn is_allowed(&self, command: &str) -> bool {
+        if command.contains(SHELL_METACHARACTERS) {
+            return false;
+        }
+        let words: Vec<&str> = command.split_whitespace().collect();
+        self.allowed_prefixes.iter().any(|prefix| {
+            let prefix: Vec<&str> = prefix.split_whitespace().collect();
+            !prefix.is_empty() && words.starts_with(&prefix)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn destructive_commands_need_confirmation_unless_allowed() {
+        let policy = SafetyPolicy::default();
+        assert_eq!(policy.review("cargo test --workspace"), Verdict::Run);
+        assert_eq!(policy.review("  git   status "), Verdict::Run);
+        
+        for command in ["rm -rf /", "cargo testify", "cargo test; rm -rf /", "cargo test && rm -rf ~", "git log $(rm -rf /)", "cargo build > /etc/passwd", ""] {
+            assert!(matches!(policy.review(command), Verdict::Confirm(_)), "{:?} should need confirmation", command);
+        }
+        
+        let policy = SafetyPolicy { allowed_prefixes: vec!["rm -rf".to_string()], ..SafetyPolicy::default() };
+        assert_eq!(policy.review("rm -rf /"), Verdict::Run);
+        
+        let policy = SafetyPolicy { require_confirmation: false, ..SafetyPolicy::default() };
+        assert_eq!(policy.review("rm -rf /"), Verdict::Run);
+        
+        assert!(SafetyPolicy::is_confirmation(" Confirm."));
+        assert!(!SafetyPolicy::is_confirmation("confirm rm -rf /"));
+    }
+}
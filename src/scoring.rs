@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use crate::code_analyzer::{Issue, Severity};
+
+/// Rules whose findings are often wrong: keyword matches and checks built
+/// on partial type or control flow inference. Everything else counts as
+/// certain.
+const HEURISTIC_RULES: [(&str, f32); 10] = [
+    // Keyword matches; assigned literals are checked, but still guessed at
+    ("all/hardcoded-secret", 0.3),
+    ("rust/computed-index", 0.5),
+    ("rust/len-underflow", 0.6),
+    ("rust/truncating-cast", 0.7),
+    ("rust/unwaited-child", 0.7),
+    ("rust/clone", 0.5),
+    ("javascript/missing-await", 0.6),
+    ("javascript/unclosed-resource", 0.6),
+    ("python/unclosed-file", 0.7),
+    ("yaml/ambiguous-boolean", 0.8),
+];
+
+/// How likely a finding of `rule` is a real problem, from 0.0 to 1.0.
+pub fn rule_confidence(rule: &str) -> f32 {
+    HEURISTIC_RULES.iter()
+        .find(|(heuristic, _)| *heuristic == rule)
+        .map_or(1.0, |(_, confidence)| *confidence)
+}
+
+/// A file's score from its issues, with and without confidence weighting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    /// Each issue's penalty scaled by its confidence; the headline score
+    pub weighted: f32,
+    /// Every issue at full penalty
+    pub raw: f32,
+}
+
+/// Score penalty per issue of each severity, before it's spread over the
+/// file's lines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SeverityWeights {
+    pub critical: f32,
+    pub high: f32,
+    pub medium: f32,
+    pub low: f32,
+}
+
+impl Default for SeverityWeights {
+    fn default() -> Self {
+        Self {
+            critical: 0.4,
+            high: 0.2,
+            medium: 0.1,
+            low: 0.05,
+        }
+    }
+}
+
+impl SeverityWeights {
+    fn penalty(&self, severity: &Severity) -> f32 {
+        match severity {
+            Severity::Low => self.low,
+            Severity::Medium => self.medium,
+            Severity::High => self.high,
+            Severity::Critical => self.critical,
+        }
+    }
+    
+    /// 1.0 plus `bonus`, less the issues' penalties per line, clamped to
+    /// 0.0..=1.0. Penalties are by severity, so a file's score only moves
+    /// on issues that are reported, after ignore rules and overrides.
+    pub fn aggregate(&self, issues: &[Issue], line_count: usize, bonus: f32) -> Score {
+        if line_count == 0 {
+            return Score { weighted: 1.0, raw: 1.0 };
+        }
+        
+        let score = |penalties: f32| (1.0 + bonus - penalties / line_count as f32).clamp(0.0, 1.0);
+        Score {
+            weighted: score(issues.iter().map(|issue| self.penalty(&issue.severity) * issue.confidence()).sum()),
+            raw: score(issues.iter().map(|issue| self.penalty(&issue.severity)).sum()),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Settings for `MemorySystem::search_semantic`. Off by default, since it
+/// needs a local embedding model and a running Qdrant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SemanticSearchConfig {
+    pub enabled: bool,
+    /// Directory with the BERT embedding model's `model.safetensors`,
+    /// `tokenizer.json` and `config.json`
+    pub model_path: PathBuf,
+    /// Qdrant gRPC endpoint
+    pub qdrant_url: String,
+    pub collection: String,
+    /// Lines per embedded chunk of a file
+    pub chunk_lines: usize,
+}
+
+impl Default for SemanticSearchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model_path: PathBuf::from("models/bge-small-en"),
+            qdrant_url: "http://localhost:6334".to_string(),
+            collection: "devagent_memory".to_string(),
+            chunk_lines: 40,
+        }
+    }
+}
+
+/// Turns text into vectors; one per input, all the same length.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Where chunk vectors are kept, tagged with the file they came from.
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    /// Replaces every vector stored for `file_id`.
+    async fn replace(&self, file_id: &str, vectors: Vec<Vec<f32>>) -> Result<()>;
+    /// Up to `limit` stored vectors closest to `vector`, as (file id,
+    /// similarity) with the most similar first.
+    async fn nearest(&self, vector: Vec<f32>, limit: usize) -> Result<Vec<(String, f32)>>;
+}
+
+/// Embeds files chunk by chunk and finds the files closest to a query.
+pub struct SemanticIndex {
+    embedder: Box<dyn Embedder>,
+    store: Box<dyn VectorStore>,
+    chunk_lines: usize,
+}
+
+impl SemanticIndex {
+    pub fn new(embedder: Box<dyn Embedder>, store: Box<dyn VectorStore>, chunk_lines: usize) -> Self {
+        Self { embedder, store, chunk_lines: chunk_lines.max(1) }
+    }
+    
+    pub async fn index(&self, file_id: &str, content: &str) -> Result<()> {
+        let chunks = chunks(content, self.chunk_lines);
+        let vectors = if chunks.is_empty() {
+            Vec::new()
+        } else {
+            self.embedder.embed(&chunks).await.context("Failed to embed file")?
+        };
+        self.store.replace(file_id, vectors).await
+    }
+    
+    /// The `top_k` files with the chunk closest to `query`, scored by that
+    /// chunk's similarity.
+    pub async fn search(&self, query: &str, top_k: usize) -> Result<Vec<(String, f32)>> {
+        let vector = self.embedder.embed(&[query.to_string()]).await
+            .context("Failed to embed query")?
+            .pop()
+            .context("Embedder returned no vector for the query")?;
+        
+        // Several chunks of one file can be among the nearest, so ask for
+        // more than `top_k` to still end up with `top_k` files
+        let hits = self.store.nearest(vector, top_k * 4).await?;
+        let mut best: HashMap<String, f32> = HashMap::new();
+        for (file_id, score) in hits {
+            let entry = best.entry(file_id).or_insert(score);
+            *entry = entry.max(score);
+        }
+        
+        let mut files: Vec<(String, f32)> = best.into_iter().collect();
+        files.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        files.truncate(top_k);
+        Ok(files)
+    }
+}
+
+/// `content` split into runs of `chunk_lines` lines, skipping blank runs.
+pub fn chunks(content: &str, chunk_lines: usize) -> Vec<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines.chunks(chunk_lines.max(1))
+        .map(|chunk| chunk.join("\n"))
+        .filter(|chunk| !chunk.trim().is_empty())
+        .collect()
+}
@@ -0,0 +1,64 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// What a shell command printed and how it exited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandOutput {
+    pub success: bool,
+    /// `None` when the command was killed by a signal
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// The platform shell and the flag that makes it run one command string.
+pub fn default_shell() -> Vec<String> {
+    let shell: &[&str] = if cfg!(windows) { &["cmd", "/C"] } else { &["sh", "-c"] };
+    shell.iter().map(|part| part.to_string()).collect()
+}
+
+/// Runs `command` through `shell` (program and leading arguments, the
+/// command string is appended), or the platform shell when `None`.
+pub async fn run(command: &str, shell: Option<&[String]>) -> Result<CommandOutput> {
+    let default = default_shell();
+    let shell = shell.unwrap_or(&default);
+    let Some((program, args)) = shell.split_first() else {
+        bail!("Shell override is empty");
+    };
+    
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .arg(command)
+        .output()
+        .await
+        .with_context(|| format!("Failed to start {}", program))?;
+    
+    Ok(CommandOutput {
+        success: output.status.success(),
+        exit_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn shell_commands_capture_their_output() {
+        let output = run("echo hello", None).await.unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout.trim(), "hello");
+        
+        if cfg!(unix) {
+            let shell = vec!["sh".to_string(), "-c".to_string()];
+            let output = run("echo oops >&2; exit 3", Some(&shell)).await.unwrap();
+            assert!(!output.success);
+            assert_eq!(output.exit_code, Some(3));
+            assert_eq!(output.stderr.trim(), "oops");
+        }
+        
+        assert!(run("echo hello", Some(&[])).await.is_err());
+    }
+}
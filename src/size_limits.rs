@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use syn::visit::{self, Visit};
+
+use crate::code_analyzer::Severity;
+
+/// Hard size limits. Unlike the soft penalties in the maintainability
+/// score, exceeding these emits an issue. Both are off by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SizeLimits {
+    pub max_file_lines: Option<usize>,
+    pub max_function_lines: Option<usize>,
+    pub severity: Severity,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        Self {
+            max_file_lines: None,
+            max_function_lines: None,
+            severity: Severity::Medium,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionSpan {
+    pub name: String,
+    pub line: usize,
+    pub lines: usize,
+}
+
+impl SizeLimits {
+    /// Functions longer than `max_function_lines`, in file order.
+    pub fn oversized_functions(&self, language: &str, content: &str) -> Vec<FunctionSpan> {
+        let Some(max) = self.max_function_lines else {
+            return Vec::new();
+        };
+        
+        function_spans(language, content)
+            .into_iter()
+            .filter(|function| function.lines > max)
+            .collect()
+    }
+}
+
+/// Declared functions with their first line and length in lines. Rust uses
+/// the AST; Python goes by indentation and brace languages by matching the
+/// body's braces, so those are best-effort.
+pub fn function_spans(language: &str, content: &str) -> Vec<FunctionSpan> {
+    match language {
+        "rust" => match crate::profiling::measure("syn parsing", || syn::parse_file(content)) {
+            Ok(ast) => {
+                let mut collector = RustFunctionCollector::default();
+                collector.visit_file(&ast);
+                collector.functions
+            }
+            Err(_) => brace_function_spans(content, &["fn "]),
+        },
+        "python" => python_function_spans(content),
+        "javascript" => brace_function_spans(content, &["function ", "function* "]),
+        "go" => brace_function_spans(content, &["func "]),
+        _ => Vec::new(),
+    }
+}
+
+#[derive(Default)]
+struct RustFunctionCollector {
+    functions: Vec<FunctionSpan>,
+}
+
+impl RustFunctionCollector {
+    fn push(&mut self, ident: &syn::Ident, block: &syn::Block) {
+        let line = ident.span().start().line;
+        let end = block.brace_token.span.close().end().line;
+        
+        self.functions.push(FunctionSpan {
+            name: ident.to_string(),
+            line,
+            lines: end.saturating_sub(line) + 1,
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for RustFunctionCollector {
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.push(&item.sig.ident, &item.block);
+        visit::visit_item_fn(self, item);
+    }
+    
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.push(&item.sig.ident, &item.block);
+        visit::visit_impl_item_fn(self, item);
+    }
+    
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        if let Some(block) = &item.default {
+            self.push(&item.sig.ident, block);
+        }
+        visit::visit_trait_item_fn(self, item);
+    }
+}
+
+// A `def` runs until the next non-blank line indented at or below it.
+fn python_function_spans(content: &str) -> Vec<FunctionSpan> {
+    let lines: Vec<&str> = content.lines().collect();
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let mut functions = Vec::new();
+    
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        let Some(rest) = trimmed.strip_prefix("def ").or_else(|| trimmed.strip_prefix("async def ")) else {
+            continue;
+        };
+        let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        let def_indent = indent(line);
+        
+        let mut last = i;
+        for (j, body_line) in lines.iter().enumerate().skip(i + 1) {
+            if body_line.trim().is_empty() {
+                continue;
+            }
+            if indent(body_line) <= def_indent {
+                break;
+            }
+            last = j;
+        }
+        
+        functions.push(FunctionSpan { name, line: i + 1, lines: last - i + 1 });
+    }
+    
+    functions
+}
+
+// Starts at a line containing one of `keywords` followed by a name, and
+// ends where the first brace opened from there is closed. Braces inside
+// strings and comments are not special-cased.
+fn brace_function_spans(content: &str, keywords: &[&str]) -> Vec<FunctionSpan> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut functions = Vec::new();
+    
+    for (i, line) in lines.iter().enumerate() {
+        let Some(name) = keywords.iter().find_map(|keyword| declared_name(line, keyword)) else {
+            continue;
+        };
+        
+        let mut depth = 0usize;
+        let mut opened = false;
+        let mut end = None;
+        
+        'scan: for (j, body_line) in lines.iter().enumerate().skip(i) {
+            for c in body_line.chars() {
+                match c {
+                    '{' => {
+                        depth += 1;
+                        opened = true;
+                    }
+                    '}' if opened => {
+                        depth = depth.saturating_sub(1);
+                        if depth == 0 {
+                            end = Some(j);
+                            break 'scan;
+                        }
+                    }
+                    // Declaration without a body (trait or interface signature)
+                    ';' if !opened => break 'scan,
+                    _ => {}
+                }
+            }
+        }
+        
+        if let Some(end) = end {
+            functions.push(FunctionSpan { name, line: i + 1, lines: end - i + 1 });
+        }
+    }
+    
+    functions
+}
+
+fn declared_name(line: &str, keyword: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("//") || trimmed.starts_with('*') {
+        return None;
+    }
+    
+    let start = line.find(keyword)?;
+    // The keyword has to start a word: `fn ` but not `defn `
+    if line[..start].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    
+    let rest = line[start + keyword.len()..].trim_start();
+    // Go methods: `func (r *Recv) Name(`
+    let rest = match rest.strip_prefix('(') {
+        Some(receiver) => receiver.split_once(')')?.1.trim_start(),
+        None => rest,
+    };
+    
+    let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+    (!name.is_empty()).then_some(name)
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use serde::{Deserialize, Serialize};
+
+/// Why a file couldn't be reviewed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    NonUtf8,
+    PermissionDenied,
+    NotFound,
+    Unreadable,
+    TooLarge,
+    TimedOut,
+    AnalysisFailed,
+}
+
+impl SkipReason {
+    /// Classifies a review error by the first I/O, UTF-8, size or timeout
+    /// error in its chain; anything else failed after the file was read.
+    pub fn classify(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if cause.is::<FileTooLarge>() {
+                return SkipReason::TooLarge;
+            }
+            
+            if cause.is::<tokio::time::error::Elapsed>() {
+                return SkipReason::TimedOut;
+            }
+            
+            if let Some(io_error) = cause.downcast_ref::<io::Error>() {
+                return match io_error.kind() {
+                    // read_to_string reports invalid UTF-8 as InvalidData
+                    io::ErrorKind::InvalidData => SkipReason::NonUtf8,
+                    io::ErrorKind::PermissionDenied => SkipReason::PermissionDenied,
+                    io::ErrorKind::NotFound => SkipReason::NotFound,
+                    _ => SkipReason::Unreadable,
+                };
+            }
+            
+            if cause.is::<std::string::FromUtf8Error>() || cause.is::<std::str::Utf8Error>() {
+                return SkipReason::NonUtf8;
+            }
+        }
+        
+        SkipReason::AnalysisFailed
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            SkipReason::NonUtf8 => "non-UTF-8",
+            SkipReason::PermissionDenied => "permission denied",
+            SkipReason::NotFound => "not found",
+            SkipReason::Unreadable => "unreadable",
+            SkipReason::TooLarge => "too large",
+            SkipReason::TimedOut => "timed out",
+            SkipReason::AnalysisFailed => "analysis failed",
+        };
+        f.write_str(text)
+    }
+}
+
+/// Raised instead of reading a file over `DevAgentConfig::max_file_size`.
+#[derive(Debug)]
+pub struct FileTooLarge {
+    pub size: u64,
+    pub limit: u64,
+}
+
+impl fmt::Display for FileTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "file is {} bytes, over the {} byte limit", self.size, self.limit)
+    }
+}
+
+impl std::error::Error for FileTooLarge {}
+
+/// A file the run had to leave out, with the full error for debugging.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+    pub error: String,
+}
+
+impl SkippedFile {
+    pub fn new(path: &str, error: &anyhow::Error) -> Self {
+        Self {
+            path: path.to_string(),
+            reason: SkipReason::classify(error),
+            error: format!("{:#}", error),
+        }
+    }
+}
+
+/// One line for the run summary, most common reason first, e.g.
+/// "12 files skipped: 8 non-UTF-8, 4 permission denied". `None` when
+/// nothing was skipped.
+pub fn summary(skipped: &[SkippedFile]) -> Option<String> {
+    if skipped.is_empty() {
+        return None;
+    }
+    
+    let mut counts: BTreeMap<SkipReason, usize> = BTreeMap::new();
+    for file in skipped {
+        *counts.entry(file.reason).or_default() += 1;
+    }
+    
+    let mut counts: Vec<(SkipReason, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    
+    let reasons: Vec<String> = counts.iter()
+        .map(|(reason, count)| format!("{} {}", count, reason))
+        .collect();
+    let noun = if skipped.len() == 1 { "file" } else { "files" };
+    
+    Some(format!("{} {} skipped: {}", skipped.len(), noun, reasons.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn skipped_files_are_classified_and_summarized() {
+        let invalid = anyhow::Error::new(io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8"))
+            .context("Failed to read file");
+        let denied = anyhow::Error::new(io::Error::from(io::ErrorKind::PermissionDenied))
+            .context("Failed to read file");
+        let git_output = anyhow::Error::new(String::from_utf8(vec![0xff]).unwrap_err());
+        
+        let skipped = vec![
+            SkippedFile::new("a.rs", &invalid),
+            SkippedFile::new("b.rs", &denied),
+            SkippedFile::new("c.py", &git_output),
+            SkippedFile::new("d.rs", &anyhow::anyhow!("WASM analysis failed")),
+            SkippedFile::new("e.js", &anyhow::Error::new(FileTooLarge { size: 10, limit: 5 })),
+        ];
+        
+        let reasons: Vec<SkipReason> = skipped.iter().map(|file| file.reason).collect();
+        assert_eq!(reasons, vec![
+            SkipReason::NonUtf8,
+            SkipReason::PermissionDenied,
+            SkipReason::NonUtf8,
+            SkipReason::AnalysisFailed,
+            SkipReason::TooLarge,
+        ]);
+        assert!(skipped[0].error.starts_with("Failed to read file: "));
+        assert_eq!(
+            summary(&skipped).as_deref(),
+            Some("5 files skipped: 2 non-UTF-8, 1 permission denied, 1 too large, 1 analysis failed")
+        );
+        assert_eq!(summary(&[]), None);
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use tracing::{info, warn};
+
+use crate::memory_system::{AnalysisResults, MemoryEntry, MemoryMetadata, MemorySystem, MemoryStatistics};
+use crate::redact;
+
+/// Default database, next to where `MemorySystem` keeps its JSON file.
+pub const DB_FILE: &str = "memory.db";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS files (
+        id TEXT PRIMARY KEY,
+        file_path TEXT NOT NULL,
+        content TEXT NOT NULL,
+        line_count INTEGER NOT NULL,
+        language TEXT NOT NULL,
+        file_size INTEGER NOT NULL,
+        -- JSON array
+        tags TEXT NOT NULL,
+        last_modified TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS files_file_path ON files (file_path);
+    CREATE INDEX IF NOT EXISTS files_language ON files (language);
+    CREATE INDEX IF NOT EXISTS files_updated_at ON files (updated_at);
+    
+    CREATE TABLE IF NOT EXISTS analyses (
+        file_id TEXT PRIMARY KEY REFERENCES files (id) ON DELETE CASCADE,
+        -- JSON `AnalysisResults`
+        results TEXT NOT NULL,
+        issue_count INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS analyses_issue_count ON analyses (issue_count);
+";
+
+const ENTRY_COLUMNS: &str = "files.id, files.file_path, files.content, files.language, files.file_size, files.tags,
+    files.last_modified, files.created_at, files.updated_at, analyses.results";
+
+/// The memory store kept in SQLite: each write touches only its own rows
+/// and commits atomically, where `MemorySystem` rewrites its whole JSON
+/// file. Same methods, but results are owned since they come from queries.
+pub struct SqliteMemory {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteMemory {
+    pub async fn new() -> Result<Self> {
+        Self::open(Path::new(DB_FILE))
+    }
+    
+    pub fn open(path: &Path) -> Result<Self> {
+        info!("Opening memory database: {}", path.display());
+        
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        // WAL keeps readers going during a write and survives a crash mid-write
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.execute_batch(SCHEMA).context("Failed to create memory tables")?;
+        
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+    
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    /// Stores or replaces a file; its previous analysis is dropped like in
+    /// `MemorySystem`, where the entry is rebuilt without one.
+    pub async fn store_file(&self, file_id: &str, file_path: &str, content: &str) -> Result<()> {
+        info!("Storing file in memory: {}", file_id);
+        
+        let metadata = MemorySystem::extract_metadata(content);
+        let content = redact::stored(content);
+        let now = Utc::now();
+        
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM analyses WHERE file_id = ?1", [file_id])?;
+        tx.execute(
+            "INSERT INTO files (id, file_path, content, line_count, language, file_size, tags, last_modified, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)
+             ON CONFLICT (id) DO UPDATE SET
+                 file_path = excluded.file_path, content = excluded.content, line_count = excluded.line_count,
+                 language = excluded.language, file_size = excluded.file_size, tags = excluded.tags,
+                 last_modified = excluded.last_modified, created_at = excluded.created_at, updated_at = excluded.updated_at",
+            params![
+                file_id,
+                file_path,
+                content,
+                content.lines().count(),
+                metadata.language,
+                metadata.file_size,
+                serde_json::to_string(&metadata.tags)?,
+                metadata.last_modified,
+                now,
+            ],
+        )?;
+        tx.commit().context("Failed to store file")?;
+        
+        Ok(())
+    }
+    
+    pub async fn update_analysis(&self, file_id: &str, analysis: AnalysisResults) -> Result<()> {
+        let conn = self.conn();
+        let updated = conn.execute("UPDATE files SET updated_at = ?2 WHERE id = ?1", params![file_id, Utc::now()])?;
+        if updated == 0 {
+            warn!("File not found in memory: {}", file_id);
+            return Ok(());
+        }
+        
+        conn.execute(
+            "INSERT OR REPLACE INTO analyses (file_id, results, issue_count) VALUES (?1, ?2, ?3)",
+            params![file_id, serde_json::to_string(&analysis)?, analysis.issues.len()],
+        )?;
+        info!("Updated analysis for file: {}", file_id);
+        
+        Ok(())
+    }
+    
+    pub async fn get_file(&self, file_id: &str) -> Result<Option<MemoryEntry>> {
+        let sql = format!("SELECT {} FROM files LEFT JOIN analyses ON analyses.file_id = files.id WHERE files.id = ?1", ENTRY_COLUMNS);
+        let entry = self.conn().query_row(&sql, [file_id], entry_from_row).optional()?;
+        Ok(entry)
+    }
+    
+    /// Files whose content, path or tags contain `query`, ignoring ASCII
+    /// case, most recently updated first.
+    pub async fn search_files(&self, query: &str) -> Result<Vec<MemoryEntry>> {
+        let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let pattern = format!("%{}%", escaped);
+        let sql = format!(
+            "SELECT {} FROM files LEFT JOIN analyses ON analyses.file_id = files.id
+             WHERE files.content LIKE ?1 ESCAPE '\\' OR files.file_path LIKE ?1 ESCAPE '\\' OR files.tags LIKE ?1 ESCAPE '\\'
+             ORDER BY files.updated_at DESC",
+            ENTRY_COLUMNS
+        );
+        
+        let conn = self.conn();
+        let mut statement = conn.prepare(&sql)?;
+        let entries = statement.query_map([pattern], entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+    
+    pub async fn get_statistics(&self) -> Result<MemoryStatistics> {
+        let conn = self.conn();
+        let (total_files, total_lines): (usize, usize) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(line_count), 0) FROM files",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        
+        let mut statement = conn.prepare("SELECT language, COUNT(*) FROM files GROUP BY language")?;
+        let languages = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<HashMap<String, usize>>>()?;
+        
+        let files_with_issues = conn.query_row("SELECT COUNT(*) FROM analyses WHERE issue_count > 0", [], |row| row.get(0))?;
+        let memory_size_bytes = conn.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            [],
+            |row| row.get(0),
+        )?;
+        
+        Ok(MemoryStatistics {
+            total_files,
+            total_lines,
+            languages,
+            files_with_issues,
+            memory_size_bytes,
+        })
+    }
+}
+
+fn json_column<T: serde::de::DeserializeOwned>(index: usize, text: String) -> rusqlite::Result<T> {
+    serde_json::from_str(&text)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<MemoryEntry> {
+    Ok(MemoryEntry {
+        id: row.get(0)?,
+        file_path: row.get(1)?,
+        content: row.get(2)?,
+        metadata: MemoryMetadata {
+            language: row.get(3)?,
+            file_size: row.get(4)?,
+            tags: json_column(5, row.get(5)?)?,
+            last_modified: row.get(6)?,
+        },
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+        analysis_results: row.get::<_, Option<String>>(9)?
+            .map(|results| json_column(9, results))
+            .transpose()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_system::CodeMetrics;
+    
+    #[tokio::test]
+    async fn files_and_analyses_round_trip_through_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory = SqliteMemory::open(&dir.path().join("memory.db")).unwrap();
+        
+        memory.store_file("a", "src/a.rs", "use std::io;\nfn main() {} // TODO\n").await.unwrap();
+        memory.store_file("b", "src/b_100%.py", "import os\ndef f(): pass\n").await.unwrap();
+        memory.update_analysis("a", AnalysisResults {
+            code_metrics: CodeMetrics {
+                lines_of_code: 2,
+                function_count: 1,
+                complexity_score: 1.0,
+                maintainability_score: 1.0,
+                security_score: 1.0,
+            },
+            issues: vec!["unwrap".to_string()],
+            suggestions: Vec::new(),
+            wasm_analysis: None,
+            llm_analysis: None,
+        }).await.unwrap();
+        
+        let found = memory.search_files("FN MAIN").await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].metadata.tags, vec!["todo", "fn:main"]);
+        assert_eq!(found[0].analysis_results.as_ref().unwrap().issues, vec!["unwrap"]);
+        // `%` and `_` are matched literally
+        let ids = |entries: Vec<MemoryEntry>| entries.into_iter().map(|entry| entry.id).collect::<Vec<_>>();
+        assert_eq!(ids(memory.search_files("100%").await.unwrap()), vec!["b"]);
+        assert!(memory.search_files("b_1%%").await.unwrap().is_empty());
+        
+        let stats = memory.get_statistics().await.unwrap();
+        assert_eq!((stats.total_files, stats.total_lines, stats.files_with_issues), (2, 4, 1));
+        assert_eq!(stats.languages["rust"], 1);
+        
+        // Storing a file again replaces it and drops its old analysis
+        memory.store_file("a", "src/a.rs", "fn main() {}\n").await.unwrap();
+        assert!(memory.get_file("a").await.unwrap().unwrap().analysis_results.is_none());
+        assert_eq!(memory.get_statistics().await.unwrap().files_with_issues, 0);
+    }
+}
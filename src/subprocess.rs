@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+use anyhow::{Context, Result};
+use tokio::io::BufReader;
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tracing::{info, warn};
+
+use crate::supervisor::Supervised;
+
+struct Running {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+/// A helper program run with piped stdin and stdout. Starting it again
+/// replaces the pipes along with the process.
+pub struct ChildProcess {
+    name: &'static str,
+    program: PathBuf,
+    args: Vec<String>,
+    running: Option<Running>,
+}
+
+impl ChildProcess {
+    pub fn new(name: &'static str, program: PathBuf, args: Vec<String>) -> Self {
+        Self { name, program, args, running: None }
+    }
+    
+    pub fn start(&mut self) -> Result<()> {
+        info!("Starting {} process", self.name);
+        
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {} ({})", self.name, self.program.display()))?;
+        let stdin = child.stdin.take().context("Child stdin was not captured")?;
+        let stdout = BufReader::new(child.stdout.take().context("Child stdout was not captured")?);
+        
+        self.running = Some(Running { child, stdin, stdout });
+        Ok(())
+    }
+    
+    /// Whether the child is alive; reaps and logs it if it has exited.
+    pub fn is_running(&mut self) -> bool {
+        let Some(running) = &mut self.running else {
+            return false;
+        };
+        match running.child.try_wait() {
+            Ok(None) => true,
+            Ok(Some(status)) => {
+                warn!("{} process exited with {}", self.name, status);
+                self.running = None;
+                false
+            }
+            Err(e) => {
+                warn!("Could not check the {} process: {}", self.name, e);
+                self.running = None;
+                false
+            }
+        }
+    }
+    
+    pub fn id(&self) -> Option<u32> {
+        self.running.as_ref().and_then(|running| running.child.id())
+    }
+    
+    /// The child's stdin and stdout, if it's running.
+    pub fn pipes(&mut self) -> Option<(&mut ChildStdin, &mut BufReader<ChildStdout>)> {
+        self.running.as_mut().map(|running| (&mut running.stdin, &mut running.stdout))
+    }
+    
+    pub async fn kill(&mut self) {
+        if let Some(mut running) = self.running.take() {
+            let _ = running.child.kill().await;
+        }
+    }
+}
+
+impl Supervised for ChildProcess {
+    fn is_running(&mut self) -> bool {
+        ChildProcess::is_running(self)
+    }
+    
+    fn start(&mut self) -> Result<()> {
+        ChildProcess::start(self)
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// A process that can be checked on and started again.
+pub trait Supervised: Send + 'static {
+    fn is_running(&mut self) -> bool;
+    fn start(&mut self) -> Result<()>;
+}
+
+/// How often a supervised process is checked and how it is restarted.
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    /// Restarts before giving up on the process
+    pub max_restarts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub poll_interval: Duration,
+}
+
+impl RestartPolicy {
+    pub fn new(max_restarts: u32) -> Self {
+        Self {
+            max_restarts,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+    
+    /// Wait before restart number `restarts` (from 0): doubling from
+    /// `initial_backoff` up to `max_backoff`.
+    pub fn backoff(&self, restarts: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(restarts))
+            .min(self.max_backoff)
+    }
+}
+
+/// Watches `process` and starts it again whenever it has stopped, until
+/// `policy.max_restarts` is used up. Abort the task before stopping the
+/// process on purpose.
+pub fn supervise<P: Supervised>(name: &'static str, process: Arc<Mutex<P>>, policy: RestartPolicy) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut restarts = 0;
+        loop {
+            tokio::time::sleep(policy.poll_interval).await;
+            if process.lock().await.is_running() {
+                continue;
+            }
+            
+            if restarts >= policy.max_restarts {
+                error!("{} stopped and has used all {} restarts, giving up", name, policy.max_restarts);
+                return;
+            }
+            let backoff = policy.backoff(restarts);
+            warn!("{} is not running, restarting in {:?}", name, backoff);
+            tokio::time::sleep(backoff).await;
+            restarts += 1;
+            
+            // A request may have restarted it in the meantime
+            let mut process = process.lock().await;
+            if process.is_running() {
+                continue;
+            }
+            match process.start() {
+                Ok(()) => info!("Restarted {} ({}/{})", name, restarts, policy.max_restarts),
+                Err(e) => error!("Failed to restart {}: {:#}", name, e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Crashes right after every start.
+    #[derive(Default)]
+    struct Crashing {
+        starts: u32,
+    }
+    
+    impl Supervised for Crashing {
+        fn is_running(&mut self) -> bool {
+            false
+        }
+        
+        fn start(&mut self) -> Result<()> {
+            self.starts += 1;
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn crashed_processes_restart_with_backoff_until_the_cap() {
+        let policy = RestartPolicy {
+            max_restarts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(3),
+            poll_interval: Duration::from_millis(1),
+        };
+        assert_eq!(
+            (0..4).map(|restarts| policy.backoff(restarts)).collect::<Vec<_>>(),
+            [1, 2, 3, 3].map(Duration::from_millis)
+        );
+        
+        let process = Arc::new(Mutex::new(Crashing::default()));
+        tokio::time::timeout(Duration::from_secs(5), supervise("crashing", process.clone(), policy))
+            .await
+            .expect("supervisor gives up after max_restarts")
+            .unwrap();
+        assert_eq!(process.lock().await.starts, 3);
+    }
+}
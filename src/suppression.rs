@@ -0,0 +1,67 @@
+use crate::code_analyzer::Issue;
+
+/// Starts an inline suppression comment: `// devagent:ignore [rule...]`,
+/// or `# devagent:ignore` in Python and other `#`-comment languages.
+pub const MARKER: &str = "devagent:ignore";
+
+/// Rules named by a suppression comment on `line`: empty when it names
+/// none (everything is silenced), `None` without a comment.
+pub fn parse(line: &str) -> Option<Vec<String>> {
+    let start = line.find(MARKER)?;
+    let before = &line[..start];
+    if !before.contains("//") && !before.contains('#') {
+        return None;
+    }
+    
+    // Stops at the first word that isn't a rule, so a reason can follow
+    let rules = line[start + MARKER.len()..]
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .take_while(|word| word.chars().any(|c| c.is_ascii_alphanumeric())
+            && word.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '*')))
+        .map(str::to_string)
+        .collect();
+    Some(rules)
+}
+
+/// A named rule matches an issue by full id (`rust/unwrap`), by the part
+/// after the language (`unwrap`), by a `prefix*` pattern or by category
+/// (`security`, `error-handling`).
+fn matches(name: &str, issue: &Issue) -> bool {
+    if let Some(prefix) = name.strip_suffix('*') {
+        return issue.rule.starts_with(prefix);
+    }
+    
+    let short_rule = issue.rule.split_once('/').map_or(issue.rule.as_str(), |(_, rule)| rule);
+    let category = format!("{:?}", issue.category).to_lowercase();
+    name == issue.rule || name == short_rule || name.replace(['-', '_'], "").to_lowercase() == category
+}
+
+/// Drops issues silenced by a suppression comment trailing their line or
+/// on its own line just above, and returns how many were dropped. Only
+/// issues for which `suppressible` holds are considered; issues without a
+/// line can't be suppressed inline.
+pub fn retain_issues(lines: &[&str], issues: &mut Vec<Issue>, suppressible: impl Fn(&Issue) -> bool) -> usize {
+    if !lines.iter().any(|line| line.contains(MARKER)) {
+        return 0;
+    }
+    
+    let before = issues.len();
+    issues.retain(|issue| {
+        let Some(line) = issue.line.filter(|_| suppressible(issue)) else {
+            return true;
+        };
+        
+        // The line above only counts as a comment of its own, not one
+        // trailing other code
+        let above = line.checked_sub(2)
+            .and_then(|index| lines.get(index))
+            .filter(|above| above.trim_start().starts_with("//") || above.trim_start().starts_with('#'));
+        let suppressed = lines.get(line - 1).into_iter()
+            .chain(above)
+            .filter_map(|line| parse(line))
+            .any(|rules| rules.is_empty() || rules.iter().any(|name| matches(name, issue)));
+        !suppressed
+    });
+    before - issues.len()
+}
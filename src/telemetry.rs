@@ -0,0 +1,121 @@
+use std::future::Future;
+use std::time::Instant;
+use anyhow::{Context, Result};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing::field::Empty;
+use tracing::{info_span, Instrument, Span};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Installs the log subscriber, plus an OTLP span exporter when an
+/// endpoint is given. Logs go to stderr so they stay apart from report
+/// and menu output. Call `shutdown` before exiting to flush spans.
+pub fn init(log_level: &str, otlp_endpoint: Option<&str>) -> Result<()> {
+    let filter = EnvFilter::new(log_level);
+    
+    let otel_layer = match otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", "devagent"),
+                ])))
+                .install_batch(runtime::Tokio)
+                .with_context(|| format!("Failed to set up OTLP export to {}", endpoint))?;
+            
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+    
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(otel_layer)
+        .init();
+    
+    Ok(())
+}
+
+/// Flushes pending spans. A no-op when export was not configured.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+/// Span for one reviewed file; pipeline stages run inside it via `stage`.
+pub fn review_span(file_path: &str) -> Span {
+    info_span!("review_file", file.path = file_path, duration_ms = Empty)
+}
+
+/// Runs `future` as a child span named `name`, recording its duration.
+pub async fn stage<F: Future>(name: &'static str, future: F) -> F::Output {
+    let span = info_span!("stage", otel.name = name, duration_ms = Empty);
+    timed(span, future).await
+}
+
+/// Awaits `future` inside `span` and records `duration_ms` on it.
+pub async fn timed<F: Future>(span: Span, future: F) -> F::Output {
+    let started = Instant::now();
+    let output = future.instrument(span.clone()).await;
+    span.record("duration_ms", started.elapsed().as_millis() as u64);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Id, Record};
+    use tracing_subscriber::layer::Context as LayerContext;
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+    
+    /// (span name, duration_ms) for each span that records a duration.
+    #[derive(Clone, Default)]
+    struct Durations(Arc<Mutex<Vec<(&'static str, u64)>>>);
+    
+    struct DurationField(Option<u64>);
+    
+    impl Visit for DurationField {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "duration_ms" {
+                self.0 = Some(value);
+            }
+        }
+        
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+    
+    impl<S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Durations {
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+            let mut duration = DurationField(None);
+            values.record(&mut duration);
+            if let (Some(duration_ms), Some(span)) = (duration.0, ctx.span(id)) {
+                self.0.lock().unwrap().push((span.name(), duration_ms));
+            }
+        }
+    }
+    
+    #[tokio::test]
+    async fn stages_and_reviews_record_their_duration() {
+        let durations = Durations::default();
+        let _guard = tracing_subscriber::registry().with(durations.clone()).set_default();
+        
+        let analyze = stage("analyze", async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            7
+        });
+        assert_eq!(timed(review_span("src/lib.rs"), analyze).await, 7);
+        
+        // The stage finishes, and records, before the file's span
+        let recorded = durations.0.lock().unwrap().clone();
+        assert_eq!(recorded.iter().map(|(name, _)| *name).collect::<Vec<_>>(), ["stage", "review_file"]);
+        assert!(recorded.iter().all(|(_, duration_ms)| *duration_ms >= 20), "{:?}", recorded);
+    }
+}
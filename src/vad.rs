@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+
+/// Zero crossings per sample above which a loud chunk is taken as hiss or
+/// broadband noise rather than voice.
+const MAX_SPEECH_ZERO_CROSSING_RATE: f32 = 0.35;
+
+/// Whether `chunk` sounds like speech: loud enough (RMS at or above
+/// `threshold`) without crossing zero as often as noise does.
+pub fn is_speech(chunk: &[f32], threshold: f32) -> bool {
+    if chunk.is_empty() {
+        return false;
+    }
+    
+    let rms = (chunk.iter().map(|sample| sample * sample).sum::<f32>() / chunk.len() as f32).sqrt();
+    let crossings = chunk.windows(2)
+        .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+        .count();
+    let zero_crossing_rate = crossings as f32 / chunk.len() as f32;
+    
+    rms >= threshold && zero_crossing_rate <= MAX_SPEECH_ZERO_CROSSING_RATE
+}
+
+/// Collects audio chunks into utterances: from shortly before the first
+/// speech chunk until `silence_samples` of non-speech follow it.
+pub struct SpeechSegmenter {
+    /// Recent silence, kept so the start of a word isn't clipped
+    pre_roll: VecDeque<f32>,
+    pre_roll_samples: usize,
+    utterance: Vec<f32>,
+    in_speech: bool,
+    trailing_silence: usize,
+    silence_samples: usize,
+}
+
+impl SpeechSegmenter {
+    pub fn new(pre_roll_samples: usize, silence_samples: usize) -> Self {
+        Self {
+            pre_roll: VecDeque::with_capacity(pre_roll_samples),
+            pre_roll_samples,
+            utterance: Vec::new(),
+            in_speech: false,
+            trailing_silence: 0,
+            silence_samples,
+        }
+    }
+    
+    /// Adds the next chunk; returns the utterance once it has ended.
+    pub fn push(&mut self, chunk: &[f32], speech: bool) -> Option<Vec<f32>> {
+        if !self.in_speech {
+            if speech {
+                self.in_speech = true;
+                self.trailing_silence = 0;
+                self.utterance.extend(self.pre_roll.drain(..));
+                self.utterance.extend_from_slice(chunk);
+            } else {
+                self.pre_roll.extend(chunk);
+                let excess = self.pre_roll.len().saturating_sub(self.pre_roll_samples);
+                self.pre_roll.drain(..excess);
+            }
+            return None;
+        }
+        
+        self.utterance.extend_from_slice(chunk);
+        if speech {
+            self.trailing_silence = 0;
+            return None;
+        }
+        
+        self.trailing_silence += chunk.len();
+        if self.trailing_silence < self.silence_samples {
+            return None;
+        }
+        self.in_speech = false;
+        self.trailing_silence = 0;
+        Some(std::mem::take(&mut self.utterance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn utterances_start_on_speech_and_end_after_trailing_silence() {
+        let silence = vec![0.001; 160];
+        // A 200 Hz tone at 16 kHz
+        let voice: Vec<f32> = (0..160).map(|i| (i as f32 * 200.0 * std::f32::consts::TAU / 16_000.0).sin() * 0.5).collect();
+        // Loud but flipping sign every sample
+        let hiss: Vec<f32> = (0..160).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 }).collect();
+        assert!(!is_speech(&silence, 0.02));
+        assert!(is_speech(&voice, 0.02));
+        assert!(!is_speech(&hiss, 0.02));
+        
+        let mut segmenter = SpeechSegmenter::new(160, 480);
+        let mut utterances = Vec::new();
+        let stream = [&silence, &silence, &hiss, &voice, &voice, &silence, &voice, &silence, &silence, &silence, &silence];
+        for chunk in stream {
+            utterances.extend(segmenter.push(chunk, is_speech(chunk, 0.02)));
+        }
+        
+        // One pre-roll chunk, three speech chunks with a short pause between,
+        // and the three silent chunks that ended it
+        assert_eq!(utterances.len(), 1);
+        assert_eq!(utterances[0].len(), 160 * 8);
+        assert_eq!(utterances[0][..160], hiss[..]);
+    }
+}
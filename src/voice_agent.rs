@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
@@ -17,15 +18,47 @@ use cuda_runtime_sys::*;
 use wasmtime::{Engine, Instance, Module, Store};
 use wasmtime_wasi::{WasiCtx, WasiCtxBuilder};
 
+use crate::audio_file;
+use crate::vad::{self, SpeechSegmenter};
+use crate::wake_word::WakeWordGate;
+
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VoiceConfig {
     pub sample_rate: u32,
     pub channels: u16,
     pub voice_model_path: PathBuf,
     pub gpu_enabled: bool,
     pub wasm_storage_path: PathBuf,
+    /// RMS level a microphone chunk needs to count as speech
+    pub vad_threshold: f32,
+    /// Silence after speech that ends a voice command
+    pub vad_silence_ms: u64,
+    /// Phrase that has to be said before commands are acted on
+    pub wake_word: Option<String>,
+    /// Idle time after which the wake word has to be said again
+    pub wake_word_timeout_secs: u64,
+}
+
+impl Default for VoiceConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 16000,
+            channels: 1,
+            voice_model_path: PathBuf::from("models/voices"),
+            gpu_enabled: false,
+            wasm_storage_path: PathBuf::from("voice_storage"),
+            vad_threshold: 0.02,
+            vad_silence_ms: 800,
+            wake_word: None,
+            wake_word_timeout_secs: 10,
+        }
+    }
 }
 
+/// Audio kept from before speech starts, so the first word isn't clipped.
+const VAD_PRE_ROLL_MS: u64 = 300;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VoiceClone {
     pub id: String,
@@ -56,6 +89,7 @@ pub struct VoiceAgent {
     config: VoiceConfig,
     voice_clones: Arc<Mutex<Vec<VoiceClone>>>,
     wasm_store: Store<WasiCtx>,
+    wake_word: std::sync::Mutex<WakeWordGate>,
     #[cfg(feature = "gpu")]
     cuda_context: Option<*mut cuda_runtime_sys::cudaContext_t>,
 }
@@ -80,13 +114,17 @@ impl VoiceAgent {
             None
         };
         
-        #[cfg(not(feature = "gpu"))]
-        let cuda_context = None;
+        let wake_word = WakeWordGate::new(
+            config.wake_word.as_deref(),
+            Duration::from_secs(config.wake_word_timeout_secs),
+        );
         
         Ok(Self {
             config,
             voice_clones: Arc::new(Mutex::new(Vec::new())),
             wasm_store,
+            wake_word: std::sync::Mutex::new(wake_word),
+            #[cfg(feature = "gpu")]
             cuda_context,
         })
     }
@@ -169,28 +207,51 @@ impl VoiceAgent {
         
         stream.play()?;
         
-        // Process incoming audio for voice commands
+        // Only run STT on whole utterances, not on every chunk of silence
+        let samples_per_ms = self.config.sample_rate as u64 * self.config.channels as u64 / 1000;
+        let mut segmenter = SpeechSegmenter::new(
+            (VAD_PRE_ROLL_MS * samples_per_ms) as usize,
+            (self.config.vad_silence_ms * samples_per_ms) as usize,
+        );
         while let Some(audio_chunk) = rx.recv().await {
-            self.process_voice_command(audio_chunk).await?;
+            let speech = self.is_speech(&audio_chunk);
+            if let Some(utterance) = segmenter.push(&audio_chunk, speech) {
+                self.process_voice_command(utterance).await?;
+            }
         }
         
         Ok(())
     }
     
+    fn is_speech(&self, chunk: &[f32]) -> bool {
+        vad::is_speech(chunk, self.config.vad_threshold)
+    }
+    
     async fn process_voice_command(&self, audio_chunk: Vec<f32>) -> Result<()> {
         // Convert audio to text using local Phi-3-mini-instruct
         let text = self.speech_to_text(audio_chunk).await?;
         
-        if !text.trim().is_empty() {
-            info!("Voice command detected: {}", text);
+        let command = self.wake_word.lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .accept(&text, Instant::now());
+        if let Some(command) = command {
+            info!("Voice command detected: {}", command);
             
             // Send to local brain for processing
-            self.delegate_to_local_brain(&text).await?;
+            self.delegate_to_local_brain(&command).await?;
         }
         
         Ok(())
     }
     
+    /// Requires `phrase` before voice commands from now on; an empty phrase
+    /// turns the wake word off.
+    pub fn set_wake_word(&self, phrase: &str) {
+        self.wake_word.lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .set_wake_word(Some(phrase));
+    }
+    
     async fn speech_to_text(&self, audio_chunk: Vec<f32>) -> Result<String> {
         // TODO: Implement speech-to-text with Phi-3-mini-instruct
         // For now, return placeholder
@@ -230,8 +291,10 @@ impl VoiceAgent {
     }
     
     async fn load_audio_file(&self, path: &PathBuf) -> Result<Vec<f32>> {
-        // TODO: Implement audio file loading
-        Ok(vec![0.0; 16000]) // Placeholder
+        let path = path.clone();
+        let sample_rate = self.config.sample_rate;
+        // Decoding is CPU-bound, so keep it off the async workers
+        tokio::task::spawn_blocking(move || audio_file::load(&path, sample_rate)).await?
     }
     
     async fn store_voice_clone(&self, voice_clone: &VoiceClone) -> Result<()> {
@@ -0,0 +1,146 @@
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// Passes transcripts on as commands only after the wake word has been
+/// heard. Once armed, every transcript is a command until the listener has
+/// been idle for `idle_timeout`.
+pub struct WakeWordGate {
+    /// Normalized words of the phrase; `None` lets every transcript through
+    wake_word: Option<Vec<String>>,
+    idle_timeout: Duration,
+    armed_until: Option<Instant>,
+    /// The last unarmed transcript, in case the phrase was split across
+    /// two utterances
+    pending: String,
+}
+
+impl WakeWordGate {
+    pub fn new(wake_word: Option<&str>, idle_timeout: Duration) -> Self {
+        let mut gate = Self {
+            wake_word: None,
+            idle_timeout,
+            armed_until: None,
+            pending: String::new(),
+        };
+        gate.set_wake_word(wake_word);
+        gate
+    }
+    
+    /// Changes the phrase and disarms; `None` or a phrase without words
+    /// turns the gate off.
+    pub fn set_wake_word(&mut self, wake_word: Option<&str>) {
+        self.wake_word = wake_word
+            .map(|phrase| words(phrase).into_iter().map(|(word, _)| word).collect::<Vec<_>>())
+            .filter(|phrase| !phrase.is_empty());
+        self.armed_until = None;
+        self.pending.clear();
+    }
+    
+    pub fn is_armed(&self, now: Instant) -> bool {
+        self.armed_until.is_some_and(|until| now < until)
+    }
+    
+    /// The command in `transcript`, if any: the text after the wake word,
+    /// as transcribed. Only finding the phrase ignores case and punctuation.
+    pub fn accept(&mut self, transcript: &str, now: Instant) -> Option<String> {
+        let Some(wake_word) = &self.wake_word else {
+            return command(transcript);
+        };
+        
+        if self.is_armed(now) {
+            let command = command(transcript)?;
+            self.armed_until = Some(now + self.idle_timeout);
+            return Some(command);
+        }
+        self.armed_until = None;
+        
+        let heard = format!("{} {}", self.pending, transcript);
+        let Some(end) = find_phrase(&heard, wake_word) else {
+            self.pending = transcript.to_string();
+            return None;
+        };
+        
+        self.pending.clear();
+        self.armed_until = Some(now + self.idle_timeout);
+        // Drop what separated the phrase from the command, as in "Hey agent, ..."
+        command(heard[end..].trim_start_matches(|c: char| c.is_whitespace() || ",.!?:;".contains(c)))
+    }
+}
+
+/// `text` trimmed, `None` if it has no words.
+fn command(text: &str) -> Option<String> {
+    Some(text.trim().to_string()).filter(|text| !words(text).is_empty())
+}
+
+/// The lowercase words of `text` with their byte ranges in it.
+fn words(text: &str) -> Vec<(String, Range<usize>)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        let in_word = c.is_alphanumeric() || c == '\'';
+        match (start, in_word) {
+            (None, true) => start = Some(i),
+            (Some(from), false) => {
+                words.push((text[from..i].to_lowercase(), from..i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Byte offset in `text` just past the first whole-word occurrence of
+/// `phrase`.
+fn find_phrase(text: &str, phrase: &[String]) -> Option<usize> {
+    let words = words(text);
+    words.windows(phrase.len())
+        .find(|window| window.iter().map(|(word, _)| word).eq(phrase))
+        .map(|window| window[window.len() - 1].1.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn commands_before_the_wake_word_are_dropped() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut gate = WakeWordGate::new(Some("Hey Agent"), Duration::from_secs(10));
+        
+        assert_eq!(gate.accept("open the file", at(0)), None);
+        // Only as a whole word
+        assert_eq!(gate.accept("they agents run tests", at(1)), None);
+        assert_eq!(gate.accept("Hey, agent! Run the tests.", at(2)), Some("Run the tests.".to_string()));
+        // Still armed for the next utterance, which extends the timeout
+        assert_eq!(gate.accept("and build", at(11)), Some("and build".to_string()));
+        assert_eq!(gate.accept("save", at(22)), None);
+        
+        // The phrase split across two utterances still arms
+        assert_eq!(gate.accept("hey", at(30)), None);
+        assert_eq!(gate.accept("agent", at(31)), None);
+        assert!(gate.is_armed(at(32)));
+        assert_eq!(gate.accept("close it", at(32)), Some("close it".to_string()));
+        
+        gate.set_wake_word(Some("computer"));
+        assert!(!gate.is_armed(at(33)));
+        assert_eq!(gate.accept("hey agent open", at(33)), None);
+        assert_eq!(gate.accept("computer open", at(34)), Some("open".to_string()));
+        
+        gate.set_wake_word(None);
+        assert_eq!(gate.accept("anything", at(35)), Some("anything".to_string()));
+        assert_eq!(gate.accept(" ... ", at(35)), None);
+    }
+    
+    #[test]
+    fn commands_keep_their_punctuation() {
+        let now = Instant::now();
+        let mut gate = WakeWordGate::new(None, Duration::from_secs(10));
+        assert_eq!(gate.accept(" cargo test --release ", now), Some("cargo test --release".to_string()));
+        
+        gate.set_wake_word(Some("hey agent"));
+        assert_eq!(gate.accept("Hey agent, open main.rs", now), Some("open main.rs".to_string()));
+        assert_eq!(gate.accept("git commit -m \"Fix it\"", now), Some("git commit -m \"Fix it\"".to_string()));
+    }
+}
@@ -2,12 +2,44 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
-use wasmtime::{Engine, Instance, Module, Store};
-use wasmtime_wasi::{WasiCtx, WasiCtxBuilder};
+use std::sync::atomic::{AtomicBool, Ordering};
+use sha2::{Digest, Sha256};
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimitsBuilder, Val};
 use tokio::fs;
-use tracing::{info, warn, error};
+use tokio::sync::{OnceCell, Semaphore, SemaphorePermit};
+use tracing::{debug, info, warn};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// wasm-pack and wasm-opt runs allowed at once unless configured.
+pub const DEFAULT_MAX_COMPILATIONS: usize = 2;
+
+// Shared by every WasmAgent, so the web server and a batch review together
+// still stay under the limit
+static COMPILE_PERMITS: OnceCell<Semaphore> = OnceCell::const_new();
+
+// Held for a whole wasm-pack run: every build writes the same
+// `wasm_module.wasm` in the shared target directory, and wasm-bindgen reads
+// it after cargo has released its own lock
+static TARGET_DIR_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Caps concurrent wasm-pack and wasm-opt processes across the whole
+/// process. Must be called before the first compilation.
+pub fn set_max_compilations(limit: usize) -> Result<()> {
+    if limit == 0 {
+        anyhow::bail!("WASM compile jobs must be at least 1");
+    }
+    COMPILE_PERMITS.set(Semaphore::new(limit))
+        .map_err(|_| anyhow::anyhow!("WASM compile limit is already set"))
+}
+
+async fn compile_permit() -> Result<SemaphorePermit<'static>> {
+    let permits = COMPILE_PERMITS.get_or_init(|| async { Semaphore::new(DEFAULT_MAX_COMPILATIONS) }).await;
+    if permits.available_permits() == 0 {
+        debug!("Waiting for a WASM compile slot");
+    }
+    Ok(permits.acquire().await?)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WasmAnalysis {
     pub compile_time: f64,
     pub binary_size: usize,
@@ -16,9 +48,47 @@ pub struct WasmAnalysis {
     pub wasm_compatibility: bool,
     pub memory_usage: usize,
     pub export_functions: Vec<String>,
+    /// Size and exports are guessed from the source rather than read from
+    /// a compiled module
+    pub estimated: bool,
+}
+
+/// Why bytes given as a WASM module can't be analyzed.
+#[derive(Debug, thiserror::Error)]
+pub enum WasmValidationError {
+    #[error("not a WebAssembly module: the file does not start with the `\\0asm` header")]
+    NotWasm,
+    #[error("the module uses a WebAssembly feature that isn't supported: {0}")]
+    UnsupportedFeature(String),
+    #[error("WebAssembly validation failed{}: {message}", .offset.map(|offset| format!(" at offset {:#x}", offset)).unwrap_or_default())]
+    Invalid {
+        offset: Option<usize>,
+        message: String,
+    },
+    #[error("the module is valid but could not be compiled: {0:#}")]
+    Compile(anyhow::Error),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl WasmValidationError {
+    /// Sorts a validation error from wasmtime by its message, which ends
+    /// in `(at offset 0x..)` when the position is known.
+    fn from_validation(error: anyhow::Error) -> Self {
+        let message = format!("{:#}", error);
+        if message.contains("not enabled") || message.contains("not supported") {
+            return Self::UnsupportedFeature(message);
+        }
+        
+        match message.rsplit_once(" (at offset 0x") {
+            Some((text, offset)) => Self::Invalid {
+                offset: usize::from_str_radix(offset.trim_end_matches(')'), 16).ok(),
+                message: text.to_string(),
+            },
+            None => Self::Invalid { offset: None, message },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WasmOptimization {
     pub name: String,
     pub description: String,
@@ -26,22 +96,42 @@ pub struct WasmOptimization {
     pub code_example: String,
 }
 
+/// Bounds on a module run with `WasmAgent::run_export`.
+#[derive(Debug, Clone)]
+pub struct RunLimits {
+    /// Roughly one unit per instruction executed
+    pub fuel: u64,
+    /// Largest size any one linear memory may grow to
+    pub max_memory_bytes: usize,
+}
+
+impl Default for RunLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 10_000_000,
+            max_memory_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
 pub struct WasmAgent {
     engine: Engine,
-    store: Store<WasiCtx>,
+    run_limits: RunLimits,
     optimizations: HashMap<String, WasmOptimization>,
+    compile: bool,
+    /// Compiled analyses by SHA-256 of the source; `None` for sources that
+    /// didn't build
+    compiled: std::sync::Mutex<HashMap<String, Option<WasmAnalysis>>>,
+    toolchain_missing: AtomicBool,
 }
 
 impl WasmAgent {
     pub async fn new() -> Result<Self> {
         info!("Initializing WASM Agent...");
         
-        let engine = Engine::default();
-        let wasi = WasiCtxBuilder::new()
-            .inherit_stdio()
-            .inherit_args()?
-            .build();
-        let store = Store::new(&engine, wasi);
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
         
         let mut optimizations = HashMap::new();
         
@@ -78,11 +168,50 @@ impl WasmAgent {
         
         Ok(Self {
             engine,
-            store,
+            run_limits: RunLimits::default(),
             optimizations,
+            compile: false,
+            compiled: std::sync::Mutex::new(HashMap::new()),
+            toolchain_missing: AtomicBool::new(false),
         })
     }
     
+    pub fn set_run_limits(&mut self, run_limits: RunLimits) {
+        self.run_limits = run_limits;
+    }
+    
+    /// Instantiates `wasm` and calls its export `func` with `args`, within
+    /// the run limits. Running out of fuel or memory fails with the
+    /// `wasmtime::Trap` as the cause. Modules may not have imports, and
+    /// each call gets a fresh store, dropped with its instance on return.
+    pub async fn run_export(&self, wasm: &[u8], func: &str, args: &[Val]) -> Result<Vec<Val>> {
+        let module = Module::new(&self.engine, wasm).context("Invalid WASM module")?;
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.run_limits.max_memory_bytes)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.run_limits.fuel)?;
+        
+        let instance = Instance::new(&mut store, &module, &[])
+            .context("Failed to instantiate WASM module")?;
+        let export = instance.get_func(&mut store, func)
+            .with_context(|| format!("WASM module has no exported function `{}`", func))?;
+        
+        let mut results = vec![Val::I32(0); export.ty(&store).results().len()];
+        export.call(&mut store, args, &mut results)
+            .with_context(|| format!("WASM function `{}` failed", func))?;
+        debug!("`{}` used {} fuel", func, self.run_limits.fuel - store.get_fuel()?);
+        Ok(results)
+    }
+    
+    /// Build Rust files with wasm-pack to report their real size and
+    /// exports. Without it, or when a file doesn't build, both are
+    /// estimated from the source.
+    pub fn set_compile(&mut self, compile: bool) {
+        self.compile = compile;
+    }
+    
     pub async fn analyze_rust_file(&self, content: &str) -> Result<WasmAnalysis> {
         info!("Analyzing Rust file for WASM compatibility...");
         
@@ -127,6 +256,16 @@ impl WasmAgent {
             compatibility_score -= 0.1;
         }
         
+        if let Some(compiled) = self.compiled_analysis(content).await {
+            suggestions.extend(compiled.optimization_suggestions);
+            return Ok(WasmAnalysis {
+                optimization_suggestions: suggestions,
+                performance_score: compatibility_score,
+                wasm_compatibility: compatibility_score > 0.5,
+                ..compiled
+            });
+        }
+        
         // Estimate binary size based on code complexity
         let lines = content.lines().count();
         let estimated_size = lines * 100; // Rough estimate
@@ -141,9 +280,46 @@ impl WasmAgent {
             wasm_compatibility: compatibility_score > 0.5,
             memory_usage: estimated_size / 2,
             export_functions: self.extract_export_functions(content),
+            estimated: true,
         })
     }
     
+    /// The analysis of `content` built to WASM, if compiling is on and it
+    /// builds. Each distinct source is built at most once.
+    async fn compiled_analysis(&self, content: &str) -> Option<WasmAnalysis> {
+        if !self.compile || self.toolchain_missing.load(Ordering::Relaxed) {
+            return None;
+        }
+        
+        let key = format!("{:x}", Sha256::digest(content.as_bytes()));
+        if let Some(cached) = self.compiled.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            return cached.clone();
+        }
+        
+        let start_time = std::time::Instant::now();
+        let analysis = match self.compile_source(content).await {
+            Ok(wasm_bytes) => match self.analyze_wasm_module(&wasm_bytes).await {
+                Ok(analysis) => Some(WasmAnalysis { compile_time: start_time.elapsed().as_secs_f64(), ..analysis }),
+                Err(e) => {
+                    warn!("Built WASM module could not be loaded: {:#}", e);
+                    None
+                }
+            },
+            Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) => {
+                warn!("wasm-pack is not installed, WASM sizes will be estimated");
+                self.toolchain_missing.store(true, Ordering::Relaxed);
+                return None;
+            }
+            Err(e) => {
+                debug!("File does not build to WASM, estimating its size: {:#}", e);
+                None
+            }
+        };
+        
+        self.compiled.lock().unwrap_or_else(|e| e.into_inner()).insert(key, analysis.clone());
+        analysis
+    }
+    
     fn extract_export_functions(&self, content: &str) -> Vec<String> {
         let mut functions = Vec::new();
         
@@ -164,13 +340,28 @@ impl WasmAgent {
     pub async fn compile_to_wasm(&self, rust_file: &Path) -> Result<Vec<u8>> {
         info!("Compiling Rust file to WASM: {}", rust_file.display());
         
-        // Create temporary directory for compilation
-        let temp_dir = std::env::temp_dir().join("wasm_compile");
-        fs::create_dir_all(&temp_dir).await?;
-        
-        // Copy file to temp directory
-        let temp_file = temp_dir.join("main.rs");
-        fs::copy(rust_file, &temp_file).await?;
+        let source = fs::read_to_string(rust_file).await
+            .with_context(|| format!("Failed to read {}", rust_file.display()))?;
+        self.compile_source(&source).await
+    }
+    
+    /// Builds `source` as the whole of a `cdylib` crate. Each build gets its
+    /// own directory, sharing one target directory so dependencies are
+    /// only compiled once; builds therefore run one at a time.
+    async fn compile_source(&self, source: &str) -> Result<Vec<u8>> {
+        let _permit = compile_permit().await?;
+        
+        let temp_dir = std::env::temp_dir().join(format!("wasm_compile-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(temp_dir.join("src")).await?;
+        let result = Self::build_crate(&temp_dir, source).await;
+        if let Err(e) = fs::remove_dir_all(&temp_dir).await {
+            debug!("Failed to remove {}: {}", temp_dir.display(), e);
+        }
+        result
+    }
+    
+    async fn build_crate(temp_dir: &Path, source: &str) -> Result<Vec<u8>> {
+        fs::write(temp_dir.join("src").join("lib.rs"), source).await?;
         
         // Create Cargo.toml for WASM compilation
         let cargo_toml = format!(
@@ -197,17 +388,18 @@ panic = "abort"
         fs::write(&cargo_file, cargo_toml).await?;
         
         // Run wasm-pack build
+        let _target_dir = TARGET_DIR_LOCK.lock().await;
         let output = tokio::process::Command::new("wasm-pack")
             .args(["build", "--target", "web", "--release"])
-            .current_dir(&temp_dir)
+            .current_dir(temp_dir)
+            .env("CARGO_TARGET_DIR", std::env::temp_dir().join("wasm_compile_target"))
             .output()
             .await
             .context("Failed to run wasm-pack")?;
         
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            error!("WASM compilation failed: {}", stderr);
-            return Err(anyhow::anyhow!("WASM compilation failed"));
+            return Err(anyhow::anyhow!("WASM compilation failed: {}", stderr.trim()));
         }
         
         // Read the generated WASM file
@@ -219,6 +411,22 @@ panic = "abort"
         Ok(wasm_bytes)
     }
     
+    /// Checks `bytes` is a binary WASM module this engine can load. The
+    /// text format is not accepted.
+    pub fn validate_wasm(&self, bytes: &[u8]) -> Result<(), WasmValidationError> {
+        if !bytes.starts_with(b"\0asm") {
+            return Err(WasmValidationError::NotWasm);
+        }
+        Module::validate(&self.engine, bytes).map_err(WasmValidationError::from_validation)
+    }
+    
+    /// Like `analyze_wasm_module`, for bytes from an untrusted source:
+    /// they are validated first, and failures say what was wrong.
+    pub async fn analyze_wasm_module_checked(&self, wasm_bytes: &[u8]) -> Result<WasmAnalysis, WasmValidationError> {
+        self.validate_wasm(wasm_bytes)?;
+        self.analyze_wasm_module(wasm_bytes).await.map_err(WasmValidationError::Compile)
+    }
+    
     pub async fn analyze_wasm_module(&self, wasm_bytes: &[u8]) -> Result<WasmAnalysis> {
         info!("Analyzing WASM module...");
         
@@ -260,17 +468,30 @@ panic = "abort"
             wasm_compatibility: true,
             memory_usage: binary_size / 2,
             export_functions,
+            estimated: false,
         })
     }
     
     pub async fn optimize_wasm(&self, wasm_bytes: &[u8]) -> Result<Vec<u8>> {
+        let _permit = compile_permit().await?;
         info!("Optimizing WASM module...");
         
+        // Own files per run, so concurrent runs don't swap modules
+        let temp_dir = std::env::temp_dir().join(format!("wasm_opt-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&temp_dir).await?;
+        let result = Self::run_wasm_opt(&temp_dir, wasm_bytes).await;
+        if let Err(e) = fs::remove_dir_all(&temp_dir).await {
+            debug!("Failed to remove {}: {}", temp_dir.display(), e);
+        }
+        result
+    }
+    
+    async fn run_wasm_opt(temp_dir: &Path, wasm_bytes: &[u8]) -> Result<Vec<u8>> {
         // Use wasm-opt if available
-        let temp_file = std::env::temp_dir().join("input.wasm");
+        let temp_file = temp_dir.join("input.wasm");
         fs::write(&temp_file, wasm_bytes).await?;
         
-        let output_file = std::env::temp_dir().join("optimized.wasm");
+        let output_file = temp_dir.join("optimized.wasm");
         
         let output = tokio::process::Command::new("wasm-opt")
             .args(["-O4", "-o", output_file.to_str().unwrap(), temp_file.to_str().unwrap()])
@@ -337,4 +558,91 @@ panic = "abort"
         
         String::new()
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn sizes_are_marked_estimated_unless_compiled() {
+        let mut agent = WasmAgent::new().await.unwrap();
+        let source = "#[no_mangle]\npub extern \"C\" fn add(a: i32, b: i32) -> i32 { a + b }\n";
+        let estimate = agent.analyze_rust_file(source).await.unwrap();
+        assert!(estimate.estimated);
+        assert_eq!(estimate.binary_size, 200);
+        assert_eq!(estimate.export_functions, ["add"]);
+        
+        let module = br#"(module (func (export "add") (param i32 i32) (result i32) local.get 0 local.get 1 i32.add))"#;
+        let compiled = agent.analyze_wasm_module(module).await.unwrap();
+        assert!(!compiled.estimated);
+        assert_eq!(compiled.binary_size, module.len());
+        assert_eq!(compiled.export_functions, ["add"]);
+        
+        // A source built before isn't built again
+        agent.set_compile(true);
+        let key = format!("{:x}", Sha256::digest(source.as_bytes()));
+        agent.compiled.lock().unwrap().insert(key, Some(compiled));
+        let analysis = agent.analyze_rust_file(source).await.unwrap();
+        assert!(!analysis.estimated);
+        assert_eq!(analysis.binary_size, module.len());
+    }
+    
+    #[tokio::test]
+    async fn compilations_past_the_limit_wait_for_a_slot() {
+        use futures::FutureExt;
+        
+        let mut held = Vec::new();
+        for _ in 0..DEFAULT_MAX_COMPILATIONS {
+            held.push(compile_permit().await.unwrap());
+        }
+        assert!(compile_permit().now_or_never().is_none());
+        
+        held.pop();
+        assert!(compile_permit().now_or_never().is_some());
+    }
+    
+    #[tokio::test]
+    async fn exports_run_within_fuel_and_memory_limits() {
+        let mut agent = WasmAgent::new().await.unwrap();
+        let module = br#"(module
+            (memory 1)
+            (func (export "add") (param i32 i32) (result i32) local.get 0 local.get 1 i32.add)
+            (func (export "spin") (loop br 0))
+            (func (export "grow") (result i32) i32.const 1 memory.grow))"#;
+        
+        let sum = agent.run_export(module, "add", &[Val::I32(2), Val::I32(40)]).await.unwrap();
+        assert_eq!(sum[0].i32(), Some(42));
+        
+        let error = agent.run_export(module, "spin", &[]).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<wasmtime::Trap>(), Some(&wasmtime::Trap::OutOfFuel));
+        assert!(agent.run_export(module, "missing", &[]).await.is_err());
+        
+        // memory.grow reports -1 rather than trapping once the cap is hit
+        agent.set_run_limits(RunLimits { max_memory_bytes: 64 * 1024, ..RunLimits::default() });
+        let grown = agent.run_export(module, "grow", &[]).await.unwrap();
+        assert_eq!(grown[0].i32(), Some(-1));
+    }
+    
+    #[tokio::test]
+    async fn malformed_modules_get_specific_errors() {
+        let agent = WasmAgent::new().await.unwrap();
+        let empty_module = b"\0asm\x01\0\0\0";
+        assert!(agent.validate_wasm(empty_module).is_ok());
+        assert!(agent.analyze_wasm_module_checked(empty_module).await.unwrap().export_functions.is_empty());
+        
+        for not_wasm in [&b"PK\x03\x04"[..], b"", b"(module)"] {
+            assert!(matches!(agent.analyze_wasm_module_checked(not_wasm).await, Err(WasmValidationError::NotWasm)));
+        }
+        
+        // A type section claiming 5 bytes with only 1 present
+        let truncated = b"\0asm\x01\0\0\0\x01\x05\x01";
+        match agent.analyze_wasm_module_checked(truncated).await {
+            Err(error @ WasmValidationError::Invalid { offset: Some(offset), .. }) => {
+                assert!((8..=truncated.len()).contains(&offset), "offset {}", offset);
+                assert!(error.to_string().starts_with(&format!("WebAssembly validation failed at offset {:#x}: ", offset)));
+            }
+            other => panic!("expected a validation error with an offset, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, ChildStdout};
+
+use crate::subprocess::ChildProcess;
+use crate::supervisor::Supervised;
+
+/// Longest wait for a transcription before the child is considered hung.
+const TRANSCRIBE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// One line of whisper's output.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Transcription {
+    pub text: String,
+    #[serde(default = "Transcription::default_confidence")]
+    pub confidence: f32,
+}
+
+impl Transcription {
+    fn default_confidence() -> f32 {
+        1.0
+    }
+}
+
+/// A whisper.cpp child speaking the stdin protocol: each request is the
+/// sample rate and sample count as little-endian `u32`s followed by that
+/// many 16-bit little-endian PCM samples; each reply is one line of JSON
+/// with `text` and optionally `confidence`. Other output lines are skipped.
+/// The child is (re)started on demand, so one that died is replaced on the
+/// next request.
+pub struct WhisperProcess {
+    process: ChildProcess,
+}
+
+impl WhisperProcess {
+    pub fn new(program: PathBuf, args: Vec<String>) -> Self {
+        Self { process: ChildProcess::new("Whisper.cpp", program, args) }
+    }
+    
+    pub fn start(&mut self) -> Result<()> {
+        self.process.start()
+    }
+    
+    pub fn is_running(&mut self) -> bool {
+        self.process.is_running()
+    }
+    
+    pub async fn transcribe(&mut self, samples: &[f32], sample_rate: u32) -> Result<Transcription> {
+        if !self.is_running() {
+            self.start()?;
+        }
+        
+        let (stdin, stdout) = self.process.pipes().context("Whisper process is not running")?;
+        let result = match tokio::time::timeout(TRANSCRIBE_TIMEOUT, exchange(stdin, stdout, samples, sample_rate)).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow::anyhow!("Whisper did not answer within {:?}", TRANSCRIBE_TIMEOUT)),
+        };
+        
+        // The stream may be out of step after a failure, so start over
+        if result.is_err() {
+            self.kill().await;
+        }
+        result
+    }
+    
+    pub async fn kill(&mut self) {
+        self.process.kill().await;
+    }
+}
+
+impl Supervised for WhisperProcess {
+    fn is_running(&mut self) -> bool {
+        self.process.is_running()
+    }
+    
+    fn start(&mut self) -> Result<()> {
+        self.process.start()
+    }
+}
+
+async fn exchange(
+    stdin: &mut ChildStdin,
+    stdout: &mut BufReader<ChildStdout>,
+    samples: &[f32],
+    sample_rate: u32,
+) -> Result<Transcription> {
+    let mut request = Vec::with_capacity(8 + samples.len() * 2);
+    request.extend_from_slice(&sample_rate.to_le_bytes());
+    request.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+    for sample in samples {
+        let pcm = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        request.extend_from_slice(&pcm.to_le_bytes());
+    }
+    stdin.write_all(&request).await.context("Failed to send audio to Whisper")?;
+    stdin.flush().await?;
+    
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if stdout.read_line(&mut line).await? == 0 {
+            bail!("Whisper exited before answering");
+        }
+        let line = line.trim();
+        if line.starts_with('{') {
+            return serde_json::from_str(line)
+                .with_context(|| format!("Unexpected Whisper output: {}", line));
+        }
+    }
+}
+
+#[cfg(all(test, unix, feature = "whisper-integration"))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    
+    /// Reads each request, then logs a line and answers with canned JSON
+    /// that echoes the header back.
+    const FAKE_WHISPER: &str = r#"#!/bin/sh
+while header=$(dd bs=1 count=8 2>/dev/null | od -An -tu4) && [ -n "$header" ]; do
+    set -- $header
+    dd bs=1 count=$(($2 * 2)) of=/dev/null 2>/dev/null
+    echo "whisper_full: processing"
+    printf '{"text": "%s samples at %s Hz", "confidence": 0.8}\n' "$2" "$1"
+done
+"#;
+    
+    #[tokio::test]
+    async fn audio_round_trips_through_a_whisper_child() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("fake-whisper.sh");
+        std::fs::write(&script, FAKE_WHISPER).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        
+        let mut whisper = WhisperProcess::new(script.clone(), Vec::new());
+        let transcription = whisper.transcribe(&[0.5; 1600], 16_000).await.unwrap();
+        assert_eq!(transcription.text, "1600 samples at 16000 Hz");
+        assert_eq!(transcription.confidence, 0.8);
+        
+        // A crashed child is replaced on the next request
+        let pid = whisper.process.id().unwrap();
+        std::process::Command::new("kill").arg("-9").arg(pid.to_string()).status().unwrap();
+        while whisper.is_running() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let transcription = whisper.transcribe(&[0.0; 80], 8_000).await.unwrap();
+        assert_eq!(transcription.text, "80 samples at 8000 Hz");
+        
+        whisper.kill().await;
+    }
+}
@@ -0,0 +1,135 @@
+use std::future::Future;
+use std::time::Duration;
+use anyhow::{anyhow, Context, Result};
+use tokio::sync::{mpsc, oneshot};
+use tracing::info;
+
+/// The caller's side of a background worker: each request goes out on a
+/// shared queue with its own reply channel, so concurrent callers wait in
+/// the queue rather than on each other.
+pub struct WorkerChannel<Req, Resp> {
+    name: &'static str,
+    requests: mpsc::Sender<(Req, oneshot::Sender<Result<Resp>>)>,
+}
+
+impl<Req, Resp> WorkerChannel<Req, Resp>
+where
+    Req: Send + 'static,
+    Resp: Send + 'static,
+{
+    /// Spawns a task that runs `handle` on each request in order. Failures
+    /// are sent back too, so a caller never waits on a result that won't
+    /// come.
+    pub fn spawn<F, Fut>(name: &'static str, capacity: usize, mut handle: F) -> Self
+    where
+        F: FnMut(Req) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Resp>> + Send,
+    {
+        let (request_tx, mut request_rx) = mpsc::channel::<(Req, oneshot::Sender<Result<Resp>>)>(capacity);
+        
+        tokio::spawn(async move {
+            info!("{} worker started", name);
+            
+            while let Some((request, reply)) = request_rx.recv().await {
+                // A caller that gave up has dropped its receiver
+                let _ = reply.send(handle(request).await);
+            }
+        });
+        
+        Self { name, requests: request_tx }
+    }
+    
+    /// Sends `request` to the worker and waits for its result.
+    pub async fn call(&self, request: Req) -> Result<Resp> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        
+        self.requests.send((request, reply_tx)).await
+            .map_err(|_| anyhow!("{} worker has stopped", self.name))?;
+        reply_rx.await
+            .with_context(|| format!("{} worker stopped before responding", self.name))?
+    }
+    
+    /// Like `call`, but fails with a `tokio::time::error::Elapsed` cause
+    /// if the result takes longer than `timeout`.
+    pub async fn call_timeout(&self, request: Req, timeout: Duration) -> Result<Resp> {
+        tokio::time::timeout(timeout, self.call(request)).await
+            .with_context(|| format!("{} worker did not respond within {:?}", self.name, timeout))?
+    }
+    
+    /// Requests sent but not yet picked up by the worker.
+    pub fn queue_depth(&self) -> usize {
+        self.requests.max_capacity() - self.requests.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn results_come_back_to_the_caller_in_order() {
+        let worker = WorkerChannel::spawn("echo", 4, |text: String| async move {
+            if text.is_empty() {
+                anyhow::bail!("nothing to echo");
+            }
+            Ok(text.to_uppercase())
+        });
+        
+        assert_eq!(worker.call("hello".to_string()).await.unwrap(), "HELLO");
+        assert!(worker.call(String::new()).await.is_err());
+        
+        // Concurrent callers each get the response to their own request
+        let (first, second) = tokio::join!(worker.call("a".to_string()), worker.call("b".to_string()));
+        assert_eq!((first.unwrap(), second.unwrap()), ("A".to_string(), "B".to_string()));
+        assert_eq!(worker.queue_depth(), 0);
+    }
+    
+    #[tokio::test]
+    async fn a_timed_out_result_does_not_reach_the_next_caller() {
+        let worker = WorkerChannel::spawn("sleepy", 4, |millis: u64| async move {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            Ok(millis)
+        });
+        
+        let error = worker.call_timeout(200, Duration::from_millis(10)).await.unwrap_err();
+        assert!(error.is::<tokio::time::error::Elapsed>());
+        assert_eq!(worker.call_timeout(1, Duration::from_secs(5)).await.unwrap(), 1);
+    }
+    
+    #[tokio::test]
+    async fn requests_wait_in_the_queue_while_the_worker_is_busy() {
+        let gate = std::sync::Arc::new(tokio::sync::Semaphore::new(0));
+        let worker_gate = gate.clone();
+        let worker = std::sync::Arc::new(WorkerChannel::spawn("gated", 4, move |n: u32| {
+            let gate = worker_gate.clone();
+            async move {
+                gate.acquire().await?.forget();
+                Ok(n)
+            }
+        }));
+        
+        let calls: Vec<_> = (0..3)
+            .map(|n| {
+                let worker = worker.clone();
+                tokio::spawn(async move { worker.call(n).await })
+            })
+            .collect();
+        
+        // The worker holds the first request; the other two are queued
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while worker.queue_depth() != 2 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+        
+        gate.add_permits(3);
+        let mut results = Vec::new();
+        for call in calls {
+            results.push(call.await.unwrap().unwrap());
+        }
+        assert_eq!(results, vec![0, 1, 2]);
+        assert_eq!(worker.queue_depth(), 0);
+    }
+}
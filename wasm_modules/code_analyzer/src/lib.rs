@@ -17,19 +17,82 @@ pub struct CodeMetrics {
     pub comment_ratio: f32,
 }
 
-#[wasm_bindgen]
+#[derive(Serialize, Deserialize)]
+pub struct OptimizationResult {
+    pub optimized: String,
+    pub changes: Vec<CodeChange>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CodeChange {
+    pub line: usize,
+    pub kind: ChangeKind,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Logging,
+    ErrorHandling,
+    VarToConst,
+    TypeHint,
+}
+
+// The generated .d.ts would otherwise type every result as `any`; these
+// mirror the serde shapes above and must change with them
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+const TS_TYPES: &str = r#"
+export interface CodeMetrics {
+    lines_of_code: number;
+    function_count: number;
+    cyclomatic_complexity: number;
+    comment_ratio: number;
+}
+
+export interface CodeAnalysis {
+    issues: string[];
+    suggestions: string[];
+    score: number;
+    metrics: CodeMetrics;
+}
+
+export type ChangeKind = "logging" | "error_handling" | "var_to_const" | "type_hint";
+
+/** One rewrite of one line; several changes to a line chain, each `before` being the previous `after`. */
+export interface CodeChange {
+    /** 1-based */
+    line: number;
+    kind: ChangeKind;
+    before: string;
+    after: string;
+}
+
+export interface OptimizationResult {
+    optimized: string;
+    changes: CodeChange[];
+}
+"#;
+
+// A plain const as well, so native tests can check it against the structs
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES_SECTION: &'static str = TS_TYPES;
+
+#[wasm_bindgen(unchecked_return_type = "CodeAnalysis")]
 pub fn analyze_code(code: &str) -> Result<JsValue, JsValue> {
     let analysis = perform_analysis(code);
     Ok(serde_wasm_bindgen::to_value(&analysis)?)
 }
 
-#[wasm_bindgen]
+/// The rewritten code and every change made, for a before/after view.
+#[wasm_bindgen(unchecked_return_type = "OptimizationResult")]
 pub fn optimize_code(code: &str) -> Result<JsValue, JsValue> {
     let optimized = perform_optimization(code);
     Ok(serde_wasm_bindgen::to_value(&optimized)?)
 }
 
-#[wasm_bindgen]
+#[wasm_bindgen(unchecked_return_type = "string[]")]
 pub fn generate_suggestions(code: &str) -> Result<JsValue, JsValue> {
     let suggestions = generate_code_suggestions(code);
     Ok(serde_wasm_bindgen::to_value(&suggestions)?)
@@ -38,7 +101,7 @@ pub fn generate_suggestions(code: &str) -> Result<JsValue, JsValue> {
 fn perform_analysis(code: &str) -> CodeAnalysis {
     let mut issues = Vec::new();
     let mut suggestions = Vec::new();
-    let mut score = 1.0;
+    let mut score: f32 = 1.0;
     
     let lines: Vec<&str> = code.lines().collect();
     let total_lines = lines.len() as f32;
@@ -164,33 +227,182 @@ fn perform_analysis(code: &str) -> CodeAnalysis {
     }
 }
 
-fn perform_optimization(code: &str) -> String {
-    let mut optimized = code.to_string();
+fn perform_optimization(code: &str) -> OptimizationResult {
+    let mut new_lines = Vec::new();
+    let mut changes = Vec::new();
+    let mut lexer = LexState::default();
     
-    // Replace println! with tracing::info!
-    optimized = optimized.replace("println!", "tracing::info!");
+    for (i, line) in code.lines().enumerate() {
+        let starts_in_code = !lexer.in_block_comment && !lexer.in_string;
+        let mut segments = split_code_segments(line, &mut lexer);
+        let mut current = line.to_string();
+        
+        let substitutions = [
+            // Replace println! with tracing::info!
+            (ChangeKind::Logging, "println!", "tracing::info!"),
+            // Replace unwrap() with proper error handling
+            (ChangeKind::ErrorHandling, ".unwrap()", ".expect(\"Error message\")"),
+            // Replace var with const/let in JavaScript
+            (ChangeKind::VarToConst, "var ", "const "),
+        ];
+        
+        for (kind, from, to) in substitutions {
+            // Only rewrite code, never string literals or comments
+            let mut touched = false;
+            for (is_code, text) in segments.iter_mut() {
+                if *is_code && text.contains(from) {
+                    *text = text.replace(from, to);
+                    touched = true;
+                }
+            }
+            
+            if touched {
+                let after: String = segments.iter().map(|(_, text)| text.as_str()).collect();
+                changes.push(CodeChange {
+                    line: i + 1,
+                    kind,
+                    before: current.clone(),
+                    after: after.clone(),
+                });
+                current = after;
+            }
+        }
+        
+        // Add type hints to Python functions
+        if let Some(after) = add_return_type_hint(&current).filter(|_| starts_in_code) {
+            changes.push(CodeChange {
+                line: i + 1,
+                kind: ChangeKind::TypeHint,
+                before: current.clone(),
+                after: after.clone(),
+            });
+            current = after;
+        }
+        
+        new_lines.push(current);
+    }
     
-    // Replace unwrap() with proper error handling
-    optimized = optimized.replace(".unwrap()", ".expect(\"Error message\")");
+    OptimizationResult {
+        optimized: new_lines.join("\n"),
+        changes,
+    }
+}
+
+#[derive(Default)]
+struct LexState {
+    in_block_comment: bool,
+    in_string: bool,
+}
+
+// Splits a line into (is_code, text) segments. Block comments and
+// double-quoted strings may span lines, so state carries over between
+// calls. Single quotes only count as a literal when they close on the
+// same line, which keeps Rust lifetimes from swallowing the rest of it.
+fn split_code_segments(line: &str, state: &mut LexState) -> Vec<(bool, String)> {
+    let bytes = line.as_bytes();
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
     
-    // Replace var with const/let in JavaScript
-    optimized = optimized.replace("var ", "const ");
+    let push = |segments: &mut Vec<(bool, String)>, is_code: bool, from: usize, to: usize| {
+        if from < to {
+            segments.push((is_code, line[from..to].to_string()));
+        }
+    };
     
-    // Add type hints to Python functions
-    let lines: Vec<&str> = optimized.lines().collect();
-    let mut new_lines = Vec::new();
+    while i < bytes.len() {
+        if state.in_block_comment {
+            if bytes[i..].starts_with(b"*/") {
+                i += 2;
+                push(&mut segments, false, start, i);
+                start = i;
+                state.in_block_comment = false;
+            } else {
+                i += 1;
+            }
+            continue;
+        }
+        
+        if state.in_string {
+            match bytes[i] {
+                b'\\' => i += 2,
+                b'"' => {
+                    i += 1;
+                    push(&mut segments, false, start, i);
+                    start = i;
+                    state.in_string = false;
+                }
+                _ => i += 1,
+            }
+            continue;
+        }
+        
+        if bytes[i..].starts_with(b"//") {
+            push(&mut segments, true, start, i);
+            push(&mut segments, false, i, bytes.len());
+            return segments;
+        }
+        
+        if bytes[i..].starts_with(b"/*") {
+            push(&mut segments, true, start, i);
+            start = i;
+            state.in_block_comment = true;
+            i += 2;
+            continue;
+        }
+        
+        if bytes[i] == b'"' {
+            push(&mut segments, true, start, i);
+            start = i;
+            state.in_string = true;
+            i += 1;
+            continue;
+        }
+        
+        if bytes[i] == b'\'' {
+            if let Some(end) = closing_single_quote(bytes, i + 1) {
+                push(&mut segments, true, start, i);
+                push(&mut segments, false, i, end + 1);
+                start = end + 1;
+                i = end + 1;
+                continue;
+            }
+        }
+        
+        i += 1;
+    }
     
-    for line in lines {
-        if line.contains("def ") && !line.contains("->") && !line.contains(":") {
-            // Simple type hint addition
-            let new_line = line.replace("def ", "def ") + " -> None:";
-            new_lines.push(new_line);
-        } else {
-            new_lines.push(line.to_string());
+    let i = i.min(bytes.len());
+    push(&mut segments, !state.in_block_comment && !state.in_string, start, i);
+    segments
+}
+
+fn closing_single_quote(bytes: &[u8], mut i: usize) -> Option<usize> {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\'' => return Some(i),
+            _ => i += 1,
         }
     }
+    None
+}
+
+// Only single-line signatures ending in ':' are touched; the hint goes
+// before the colon so the line stays valid Python.
+fn add_return_type_hint(line: &str) -> Option<String> {
+    let trimmed = line.trim_end();
+    
+    if !trimmed.trim_start().starts_with("def ") || trimmed.contains("->") {
+        return None;
+    }
     
-    new_lines.join("\n")
+    let signature = trimmed.strip_suffix(':')?;
+    if !signature.ends_with(')') {
+        return None;
+    }
+    
+    Some(format!("{} -> None:", signature))
 }
 
 fn generate_code_suggestions(code: &str) -> Vec<String> {
@@ -232,6 +444,41 @@ fn generate_code_suggestions(code: &str) -> Vec<String> {
 mod tests {
     use super::*;
     
+    /// Field names `interface` declares in `TS_TYPES`, sorted.
+    fn declared_fields(interface: &str) -> Vec<String> {
+        let start = TS_TYPES.find(&format!("export interface {} {{", interface)).unwrap();
+        let body = &TS_TYPES[start..start + TS_TYPES[start..].find('}').unwrap()];
+        let mut fields: Vec<String> = body.lines().skip(1)
+            .filter_map(|line| line.trim().split_once(':'))
+            .filter(|(name, _)| !name.starts_with("/**"))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        fields.sort();
+        fields
+    }
+    
+    fn keys(value: &serde_json::Value) -> Vec<String> {
+        // serde_json keeps object keys sorted
+        value.as_object().unwrap().keys().cloned().collect()
+    }
+    
+    #[test]
+    fn serialized_results_match_the_typescript_interfaces() {
+        let analysis = serde_json::to_value(perform_analysis("fn main() {\n    println!(\"hi\");\n}\n")).unwrap();
+        assert_eq!(keys(&analysis), declared_fields("CodeAnalysis"));
+        assert_eq!(keys(&analysis["metrics"]), declared_fields("CodeMetrics"));
+        
+        let optimized = serde_json::to_value(perform_optimization("println!(\"test\");")).unwrap();
+        assert_eq!(keys(&optimized), declared_fields("OptimizationResult"));
+        assert_eq!(keys(&optimized["changes"][0]), declared_fields("CodeChange"));
+        
+        // Each kind serializes to a member of the `ChangeKind` union
+        for kind in [ChangeKind::Logging, ChangeKind::ErrorHandling, ChangeKind::VarToConst, ChangeKind::TypeHint] {
+            let kind = serde_json::to_string(&kind).unwrap();
+            assert!(TS_TYPES.contains(&kind), "{}", kind);
+        }
+    }
+    
     #[test]
     fn test_analyze_code() {
         let code = r#"
@@ -249,8 +496,33 @@ fn main() {
     #[test]
     fn test_optimize_code() {
         let code = "println!(\"test\");";
-        let optimized = perform_optimization(code);
-        assert!(optimized.contains("tracing::info!"));
-        assert!(!optimized.contains("println!"));
+        let result = perform_optimization(code);
+        assert!(result.optimized.contains("tracing::info!"));
+        assert!(!result.optimized.contains("println!"));
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].kind, ChangeKind::Logging);
+        assert_eq!(result.changes[0].before, code);
+    }
+    
+    #[test]
+    fn test_type_hint_keeps_valid_python() {
+        let code = "def greet(name):\n    print(name)\ndef typed() -> int:";
+        let result = perform_optimization(code);
+        assert_eq!(result.optimized.lines().next(), Some("def greet(name) -> None:"));
+        assert!(result.optimized.ends_with("def typed() -> int:"));
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].line, 1);
+    }
+    
+    #[test]
+    fn test_optimize_skips_strings_and_comments() {
+        let code = "let s = \"println! is great\";\n// println!(\"x\")\n/* x.unwrap()\n*/ println!(\"{}\", s);";
+        let result = perform_optimization(code);
+        let lines: Vec<&str> = result.optimized.lines().collect();
+        assert_eq!(lines[0], "let s = \"println! is great\";");
+        assert_eq!(lines[1], "// println!(\"x\")");
+        assert_eq!(lines[2], "/* x.unwrap()");
+        assert_eq!(lines[3], "*/ tracing::info!(\"{}\", s);");
+        assert_eq!(result.changes.len(), 1);
     }
 } 
\ No newline at end of file